@@ -1,18 +1,142 @@
 //! Identity commands.
 
-use clap::Subcommand;
-use anyhow::Result;
+use clap::{Args, Subcommand, ValueEnum};
+use anyhow::{bail, Context, Result};
+use opennet_core::NodeId;
+use opennet_core::fs::write_atomic;
+use opennet_core::types::Timestamp;
+use opennet_identity::backup::{combine_shares, split_secret, SeedShare};
+use opennet_identity::rotation::RotationPolicy;
+use opennet_identity::{FileStorage, KeyPair, KeyUnlock};
+use opennet_node::integration::{IdentityIntegration, IdentityStore};
+use opennet_node::runtime::{IDENTITY_KEY_NAME, ROTATION_LOG_FILE};
+use opennet_revocation::revocation::certificate::{certificate_file_name, generate_self_revocation, CERTIFICATE_SPOOL_DIR};
+use opennet_revocation::revocation::trigger::RevocationTrigger;
+use opennet_revocation::revocation::{validation, RevocationObject};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
 
 #[derive(Subcommand, Debug)]
 pub enum IdentityAction {
     /// Show current identity.
     Show,
-    /// Generate new identity.
-    Generate,
+    /// Generate a new identity in the node's data directory, together with
+    /// a revocation certificate for its key.
+    Generate {
+        #[command(flatten)]
+        key: KeyLocation,
+        /// Revocation certificate output (default: `<node_id>-1.rev`).
+        #[arg(long)]
+        revocation_out: Option<PathBuf>,
+    },
     /// Rotate keys.
     Rotate,
     /// Export public key.
     Export,
+    /// Split the identity key into k-of-n mnemonic backup shares.
+    ///
+    /// The shares hold the current epoch's key only. Once the node
+    /// rotates they restore a retired key, so back up again after each
+    /// rotation and keep a copy of the rotation log, without which only
+    /// an epoch-1 key can be restored.
+    Backup {
+        #[command(flatten)]
+        key: KeyLocation,
+        /// Shares needed to restore.
+        #[arg(short = 'k', long, default_value_t = 3)]
+        threshold: u8,
+        /// Shares to create.
+        #[arg(short = 'n', long, default_value_t = 5)]
+        shares: u8,
+    },
+    /// Restore the identity key from backup shares read from stdin, one per line.
+    ///
+    /// With the rotation log in place the key must be the log's current
+    /// key. Without it the key must be the genesis key of `--node-id`.
+    Restore {
+        #[command(flatten)]
+        key: KeyLocation,
+        /// Full hex NodeId the key belongs to; required without a rotation log.
+        #[arg(long)]
+        node_id: Option<String>,
+        /// Replace an existing key.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Self-revocation certificates.
+    Revocation {
+        #[command(subcommand)]
+        action: RevocationAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RevocationAction {
+    /// Sign a revocation of the identity key now, to be stored offline
+    /// and published if the key is ever lost or stolen.
+    Generate {
+        #[command(flatten)]
+        key: KeyLocation,
+        /// Epoch to revoke (default: the current epoch in the rotation log).
+        #[arg(long)]
+        epoch: Option<u64>,
+        /// Reason recorded in the certificate.
+        #[arg(long, value_enum, default_value_t = Reason::Compromise)]
+        reason: Reason,
+        /// Output file (default: `<node_id>-<epoch>.rev`).
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Replace an existing file.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Hand a certificate to the local node, which verifies and gossips it.
+    Publish {
+        /// Certificate file.
+        cert: PathBuf,
+        /// Node data directory.
+        #[arg(long, default_value = "./opennet-data")]
+        dir: PathBuf,
+    },
+}
+
+/// Reason recorded in a self-revocation.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Reason {
+    /// The key was lost or stolen.
+    Compromise,
+    /// The key is retired.
+    Voluntary,
+}
+
+impl From<Reason> for RevocationTrigger {
+    fn from(reason: Reason) -> Self {
+        match reason {
+            Reason::Compromise => RevocationTrigger::KeyCompromise,
+            Reason::Voluntary => RevocationTrigger::Voluntary,
+        }
+    }
+}
+
+/// Where the node keeps its encrypted identity keys and rotation log.
+#[derive(Args, Debug)]
+pub struct KeyLocation {
+    /// Node data directory.
+    #[arg(long, default_value = "./opennet-data")]
+    dir: PathBuf,
+    /// Key name (stored as `<name>-<epoch>.key`).
+    #[arg(long, default_value = IDENTITY_KEY_NAME)]
+    name: String,
+    /// Environment variable holding the keystore passphrase.
+    #[arg(long, default_value = "OPENNET_PASSPHRASE")]
+    passphrase_env: String,
+}
+
+impl KeyLocation {
+    fn store(&self) -> IdentityStore {
+        let storage = FileStorage::new(&self.dir, KeyUnlock::Env(self.passphrase_env.clone()));
+        IdentityStore::new(Box::new(storage), &self.name, self.dir.join(ROTATION_LOG_FILE))
+    }
 }
 
 pub async fn run(action: IdentityAction) -> Result<()> {
@@ -22,15 +146,170 @@ pub async fn run(action: IdentityAction) -> Result<()> {
             println!("  NodeId: (would be shown)");
             println!("  Epoch: 1");
         }
-        IdentityAction::Generate => {
-            println!("Generating new identity...");
-        }
+        IdentityAction::Generate { key, revocation_out } => generate(&key, revocation_out)?,
         IdentityAction::Rotate => {
             println!("Rotating keys...");
         }
         IdentityAction::Export => {
             println!("Public key: (would be exported)");
         }
+        IdentityAction::Backup { key, threshold, shares } => backup(&key, threshold, shares)?,
+        IdentityAction::Restore { key, node_id, force } => restore(&key, node_id.as_deref(), force)?,
+        IdentityAction::Revocation { action: RevocationAction::Generate { key, epoch, reason, out, force } } => {
+            generate_revocation(&key, epoch, reason, out, force)?
+        }
+        IdentityAction::Revocation { action: RevocationAction::Publish { cert, dir } } => {
+            publish_revocation(&cert, &dir)?
+        }
+    }
+    Ok(())
+}
+
+fn generate(key: &KeyLocation, revocation_out: Option<PathBuf>) -> Result<()> {
+    let store = key.store();
+    if store.exists() {
+        bail!("an identity already exists in {}", key.dir.display());
+    }
+    if let Some(out) = revocation_out.as_ref().filter(|out| out.exists()) {
+        bail!("{} already exists", out.display());
+    }
+    std::fs::create_dir_all(&key.dir).with_context(|| format!("creating {}", key.dir.display()))?;
+    let integration = IdentityIntegration::create(KeyPair::random(), now()?.as_secs(), store, RotationPolicy::default())
+        .context("creating identity")?;
+    let identity = integration.identity();
+    println!("Generated NodeId: {} (epoch {})", identity.node_id(), identity.epoch_id());
+
+    // Sign the revocation while the key is at hand, as PGP does.
+    let cert = generate_self_revocation(
+        *identity.node_id(),
+        identity.epoch_id(),
+        RevocationTrigger::KeyCompromise,
+        timestamp()?,
+        identity.signer(),
+    )?;
+    let out = revocation_out.unwrap_or_else(|| PathBuf::from(certificate_file_name(&cert)));
+    write_atomic(&out, &cert.encode()).with_context(|| format!("writing {}", out.display()))?;
+    println!("Revocation certificate written to {}", out.display());
+    println!("Anyone holding it can revoke this key. Store it offline, away from the key.");
+    Ok(())
+}
+
+fn backup(key: &KeyLocation, threshold: u8, shares: u8) -> Result<()> {
+    let store = key.store();
+    let log = store.load_log().context("reading rotation log")?;
+    let keypair = store.load_key(log.head_epoch()).context("loading identity key")?;
+    let split = split_secret(keypair.expose_secret().as_bytes(), threshold, shares)?;
+    println!("NodeId: {} (epoch {})", log.node_id().to_hex(), log.head_epoch());
+    println!("Any {} of these {} shares restore the key. Store them apart.", threshold, shares);
+    println!("They hold the epoch {} key only and go stale when the node next rotates;", log.head_epoch());
+    println!("back up again after every rotation.");
+    if log.head_epoch() > 1 {
+        println!("Restoring them also needs the rotation log ({}); keep a copy of it.", store.log_path().display());
+    }
+    for share in &split {
+        println!();
+        println!("Share {}/{}:", share.index(), shares);
+        println!("{}", share.to_mnemonic().as_str());
+    }
+    Ok(())
+}
+
+/// Restore the current key of an existing identity, or start the
+/// identity afresh from a restored genesis key if its rotation log is
+/// gone too. Without the log only `expected`'s genesis key is accepted.
+fn restore(key: &KeyLocation, expected: Option<&str>, force: bool) -> Result<()> {
+    let mut store = key.store();
+    let expected = expected.map(NodeId::from_hex).transpose().context("parsing --node-id")?;
+    let log = if store.exists() { Some(store.load_log().context("reading rotation log")?) } else { None };
+    match (&log, expected) {
+        (Some(log), Some(expected)) if *log.node_id() != expected => {
+            bail!("the rotation log in {} is for {}, not {}", key.dir.display(), log.node_id().to_hex(), expected.to_hex());
+        }
+        (Some(log), _) if store.has_key(log.head_epoch()) && !force => {
+            bail!("{} already exists in {}; pass --force to replace it", store.key_name(log.head_epoch()), key.dir.display());
+        }
+        (None, None) => bail!("no rotation log in {}; pass --node-id with the full NodeId to restore", key.dir.display()),
+        _ => {}
+    }
+    let mut shares = Vec::new();
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.split_whitespace().next().is_none() {
+            continue;
+        }
+        let share = SeedShare::from_mnemonic(&line).with_context(|| format!("share {}", shares.len() + 1))?;
+        let threshold = usize::from(share.threshold());
+        shares.push(share);
+        if shares.len() >= threshold {
+            break;
+        }
+    }
+    let secret = combine_shares(&shares)?;
+    let keypair = KeyPair::from_bytes(&secret)?;
+    match log {
+        Some(log) => {
+            if *log.head_key() != keypair.public_key() {
+                bail!("the restored key is not the current key of {}", log.node_id());
+            }
+            store.store_key(log.head_epoch(), &keypair).context("storing identity key")?;
+            println!("Restored NodeId: {} (epoch {})", log.node_id(), log.head_epoch());
+        }
+        None => {
+            // Only the genesis key derives the NodeId; a later epoch's key
+            // would silently start a different identity.
+            let node_id = NodeId::from_public_key(keypair.public_key().as_bytes());
+            if let Some(expected) = expected.filter(|expected| *expected != node_id) {
+                bail!(
+                    "the restored key is not the genesis key of {}; shares taken after a rotation need the rotation log",
+                    expected.to_hex()
+                );
+            }
+            std::fs::create_dir_all(&key.dir).with_context(|| format!("creating {}", key.dir.display()))?;
+            let integration = IdentityIntegration::create(keypair, now()?.as_secs(), store, RotationPolicy::default())
+                .context("creating identity")?;
+            println!("Restored NodeId: {} (epoch 1)", integration.identity().node_id());
+        }
     }
     Ok(())
 }
+
+fn generate_revocation(key: &KeyLocation, epoch: Option<u64>, reason: Reason, out: Option<PathBuf>, force: bool) -> Result<()> {
+    let store = key.store();
+    let log = store.load_log().context("reading rotation log")?;
+    let node_id = *log.node_id();
+    let epoch = epoch.unwrap_or(log.head_epoch());
+    let keypair = store.load_key(epoch).with_context(|| format!("loading the epoch {} key", epoch))?;
+    let cert = generate_self_revocation(node_id, epoch, reason.into(), timestamp()?, &keypair)?;
+    let out = out.unwrap_or_else(|| PathBuf::from(certificate_file_name(&cert)));
+    if out.exists() && !force {
+        bail!("{} already exists; pass --force to replace it", out.display());
+    }
+    write_atomic(&out, &cert.encode()).with_context(|| format!("writing {}", out.display()))?;
+    println!("Revocation certificate for {} epoch {} written to {}", node_id, epoch, out.display());
+    println!("Anyone holding it can revoke this key. Store it offline, away from the key.");
+    Ok(())
+}
+
+fn publish_revocation(cert: &Path, dir: &Path) -> Result<()> {
+    let bytes = std::fs::read(cert).with_context(|| format!("reading {}", cert.display()))?;
+    let obj = RevocationObject::decode(&bytes)?;
+    validation::validate(&obj)?;
+    if obj.self_signature().is_none() {
+        bail!("{} is not signed by the node it revokes", cert.display());
+    }
+    let spool = dir.join(CERTIFICATE_SPOOL_DIR);
+    std::fs::create_dir_all(&spool).with_context(|| format!("creating {}", spool.display()))?;
+    let target = spool.join(certificate_file_name(&obj));
+    write_atomic(&target, &bytes).with_context(|| format!("writing {}", target.display()))?;
+    println!("Queued revocation of {} epoch {} in {}", obj.node_id, obj.revoked_epoch, target.display());
+    println!("The node verifies it against the epoch key and gossips it on its next pass.");
+    Ok(())
+}
+
+fn now() -> Result<std::time::Duration> {
+    Ok(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?)
+}
+
+fn timestamp() -> Result<Timestamp> {
+    Ok(Timestamp::from_millis(u64::try_from(now()?.as_millis())?))
+}
@@ -11,6 +11,19 @@
 //! # Show identity info
 //! opennet identity show
 //!
+//! # Create the node's identity and a revocation certificate for its key
+//! opennet identity generate
+//!
+//! # Split the identity key into 3-of-5 mnemonic shares, and restore it;
+//! # shares go stale when the key rotates, so back up after each rotation
+//! opennet identity backup -k 3 -n 5
+//! opennet identity restore --node-id <node_id> < shares.txt
+//!
+//! # Pre-sign a revocation of the current key to keep offline, and
+//! # publish it through the local node if the key is lost
+//! opennet identity revocation generate --out revoke.rev
+//! opennet identity revocation publish revoke.rev
+//!
 //! # Inspect trust graph
 //! opennet trust inspect <node_id>
 //!
@@ -15,6 +15,47 @@ pub struct Config {
 pub struct NodeConfig {
     pub data_dir: String,
     pub identity_file: String,
+    /// Where the identity key lives; the keystore in `data_dir` if unset.
+    #[serde(default)]
+    pub signer: SignerConfig,
+}
+
+/// Signing backend for the node's identity key.
+///
+/// ```toml
+/// [node.signer]
+/// kind = "pkcs11"
+/// module = "/usr/lib/softhsm/libsofthsm2.so"
+/// token_label = "opennet"
+/// key_label = "node-identity"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SignerConfig {
+    /// Encrypted keystore in the data directory, unlocked with
+    /// `OPENNET_PASSPHRASE`.
+    #[default]
+    File,
+    /// Ed25519 key on a PKCS#11 token. Needs the `pkcs11` feature.
+    Pkcs11 {
+        /// Path to the vendor's PKCS#11 module.
+        module: String,
+        /// `CKA_LABEL` of the token.
+        token_label: String,
+        /// `CKA_LABEL` of the key pair.
+        key_label: String,
+        /// Environment variable holding the user PIN.
+        #[serde(default = "default_pin_env")]
+        pin_env: String,
+    },
+    /// Signer process listening on a Unix socket.
+    Remote {
+        socket: String,
+    },
+}
+
+fn default_pin_env() -> String {
+    "OPENNET_PKCS11_PIN".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +77,7 @@ impl Default for Config {
             node: NodeConfig {
                 data_dir: "./opennet-data".to_string(),
                 identity_file: "identity.key".to_string(),
+                signer: SignerConfig::default(),
             },
             network: NetworkConfig {
                 listen_addr: "0.0.0.0:9000".to_string(),
@@ -50,6 +92,20 @@ impl Default for Config {
     }
 }
 
+impl Config {
+    /// Settings for the node itself; the rest keep their defaults.
+    pub fn node_config(&self) -> opennet_node::NodeConfig {
+        opennet_node::NodeConfig {
+            data_dir: self.node.data_dir.clone().into(),
+            listen_addr: self.network.listen_addr.clone(),
+            bootstrap_peers: self.network.bootstrap_peers.clone(),
+            trust_warn_threshold: self.trust.warn_threshold,
+            trust_critical_threshold: self.trust.critical_threshold,
+            ..Default::default()
+        }
+    }
+}
+
 pub fn load<P: AsRef<Path>>(path: P) -> Result<Config> {
     if path.as_ref().exists() {
         let content = std::fs::read_to_string(path)?;
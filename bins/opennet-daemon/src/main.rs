@@ -12,14 +12,25 @@
 //!
 //! The daemon reads configuration from a TOML file.
 //! See `docs/configuration.md` for details.
+//!
+//! The identity key is kept in the keystore in the data directory unless
+//! `[node.signer]` selects a PKCS#11 token or a remote signer.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use opennet_identity::{FileStorage, KeyUnlock};
+use opennet_node::runtime::TICK_INTERVAL_SECS;
+use opennet_node::NodeRuntime;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 mod cli;
 mod config;
 mod logging;
 mod signals;
 mod metrics;
+mod signer;
+
+/// Environment variable holding the keystore passphrase.
+const PASSPHRASE_ENV: &str = "OPENNET_PASSPHRASE";
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -35,8 +46,54 @@ async fn main() -> Result<()> {
     // Setup signal handlers
     signals::setup()?;
     
-    // TODO: Initialize and run node
     tracing::info!("OpenNet daemon starting...");
+    let node_config = config.node_config();
+    std::fs::create_dir_all(&node_config.data_dir)
+        .with_context(|| format!("creating {}", node_config.data_dir.display()))?;
+    let storage = Box::new(FileStorage::new(&node_config.data_dir, KeyUnlock::Env(PASSPHRASE_ENV.to_string())));
+    let mut node = match signer::open(&config.node.signer)? {
+        Some(signer) => NodeRuntime::open_with_signer(node_config, storage, signer, unix_secs()),
+        None => NodeRuntime::open(node_config, storage, unix_secs()),
+    }
+    .context("opening node identity")?;
+    tracing::info!(node_id = %node.identity().identity().node_id(), "identity loaded");
+
+    let shutdown = signals::wait_for_shutdown();
+    tokio::pin!(shutdown);
+    let mut interval = tokio::time::interval(Duration::from_secs(TICK_INTERVAL_SECS));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => tick(&mut node),
+            _ = &mut shutdown => break,
+        }
+    }
+    if let Err(e) = node.shutdown() {
+        tracing::warn!(error = %e, "saving node state on shutdown failed");
+    }
+    tracing::info!("OpenNet daemon stopped");
     
     Ok(())
 }
+
+/// Periodic work: rotation, published revocations and their gossip.
+fn tick(node: &mut NodeRuntime) {
+    match node.tick(unix_secs()) {
+        Ok(Some(rotation)) => tracing::info!(epoch = rotation.new_epoch, "identity key rotated"),
+        Ok(None) => {}
+        Err(e) => tracing::warn!(error = %e, "node tick failed"),
+    }
+    if let Err(e) = node.import_revocations() {
+        tracing::warn!(error = %e, "importing revocation spool failed");
+    }
+    for gossip in node.drain_revocations() {
+        tracing::info!(
+            node_id = %gossip.message.node_id,
+            epoch = gossip.message.revoked_epoch,
+            "revocation accepted for gossip"
+        );
+    }
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
@@ -0,0 +1,43 @@
+//! Signer selection.
+
+use anyhow::{Context, Result};
+use opennet_identity::NodeSigner;
+use crate::config::SignerConfig;
+
+/// Open the signer named by `config`, or `None` for the keystore.
+pub fn open(config: &SignerConfig) -> Result<Option<Box<dyn NodeSigner>>> {
+    match config {
+        SignerConfig::File => Ok(None),
+        SignerConfig::Pkcs11 { module, token_label, key_label, pin_env } => {
+            pkcs11(module, token_label, key_label, pin_env).map(Some)
+        }
+        SignerConfig::Remote { socket } => remote(socket).map(Some),
+    }
+}
+
+#[cfg(feature = "pkcs11")]
+fn pkcs11(module: &str, token_label: &str, key_label: &str, pin_env: &str) -> Result<Box<dyn NodeSigner>> {
+    use opennet_identity::signer::{CryptokiBackend, Pkcs11Signer};
+    let pin = std::env::var(pin_env).with_context(|| format!("reading the token PIN from {pin_env}"))?;
+    let backend = CryptokiBackend::open(module, token_label, &pin)
+        .with_context(|| format!("opening PKCS#11 token {token_label:?} via {module}"))?;
+    let signer = Pkcs11Signer::open(backend, key_label).with_context(|| format!("opening token key {key_label:?}"))?;
+    Ok(Box::new(signer))
+}
+
+#[cfg(not(feature = "pkcs11"))]
+fn pkcs11(_module: &str, _token_label: &str, _key_label: &str, _pin_env: &str) -> Result<Box<dyn NodeSigner>> {
+    anyhow::bail!("this daemon was built without PKCS#11 support; rebuild with --features pkcs11")
+}
+
+#[cfg(unix)]
+fn remote(socket: &str) -> Result<Box<dyn NodeSigner>> {
+    let signer = opennet_identity::signer::RemoteSigner::connect(socket)
+        .with_context(|| format!("connecting to the signer at {socket}"))?;
+    Ok(Box::new(signer))
+}
+
+#[cfg(not(unix))]
+fn remote(_socket: &str) -> Result<Box<dyn NodeSigner>> {
+    anyhow::bail!("remote signers need Unix domain sockets")
+}
@@ -3,11 +3,23 @@
 //! RFC: OpenNet Core Protocol
 
 /// Protocol version number.
-pub const PROTOCOL_VERSION: u16 = 1;
+///
+/// 2: timestamps carry milliseconds instead of seconds.
+pub const PROTOCOL_VERSION: u16 = 2;
+
+/// Oldest protocol version we talk to. Version 1 timestamps are in
+/// seconds and would be misread as milliseconds.
+pub const MIN_PROTOCOL_VERSION: u16 = 2;
 
 /// Wire format version.
 pub const WIRE_VERSION: u16 = 1;
 
+/// Feature bit: hybrid Ed25519 + ML-DSA-65 signatures.
+///
+/// Advertised in `NodeHello.features`; peers use the hybrid scheme only
+/// if both set it.
+pub const FEATURE_HYBRID_SIGNATURES: u64 = 1 << 0;
+
 /// NodeId byte length (SHA-256 hash).
 pub const NODE_ID_LEN: usize = 32;
 
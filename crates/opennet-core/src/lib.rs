@@ -7,6 +7,7 @@
 
 pub mod error;
 pub mod types;
+pub mod fs;
 
 mod node_id;
 mod service_id;
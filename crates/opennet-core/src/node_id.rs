@@ -5,6 +5,7 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 use crate::constants::NODE_ID_LEN;
+use crate::types::{AnyPublicKey, SignatureAlgorithm};
 use crate::error::{CoreError, Result};
 
 /// Unique, immutable node identifier.
@@ -34,6 +35,26 @@ impl NodeId {
         Self(bytes)
     }
 
+    /// Derive NodeId from a key of any algorithm.
+    ///
+    /// ```text
+    /// Ed25519: NodeId = SHA256(public_key)
+    /// other:   NodeId = SHA256(algorithm_id || public_key)
+    /// ```
+    ///
+    /// Ed25519 keeps the original rule, so existing NodeIds do not change.
+    /// Other algorithms are prefixed by their id, so a hybrid key never
+    /// derives the NodeId of its own Ed25519 half.
+    pub fn from_any_public_key(public_key: &AnyPublicKey) -> Self {
+        if public_key.algorithm() == SignatureAlgorithm::Ed25519 {
+            return Self::from_public_key(public_key.as_bytes());
+        }
+        let mut hasher = Sha256::new();
+        hasher.update([public_key.algorithm().id()]);
+        hasher.update(public_key.as_bytes());
+        Self(hasher.finalize().into())
+    }
+
     /// Parse NodeId from hex string.
     pub fn from_hex(hex: &str) -> Result<Self> {
         let bytes = hex::decode(hex)
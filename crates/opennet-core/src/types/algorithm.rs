@@ -0,0 +1,228 @@
+//! Algorithm-agile key and signature types.
+//!
+//! [`PublicKey`] and [`Signature`] stay the fixed-size Ed25519 types used
+//! by the classic protocol. [`AnyPublicKey`] and [`AnySignature`] carry an
+//! algorithm id so a node can move to a post-quantum hybrid scheme without
+//! another format change. Encoded form: `algorithm id (1) | key or signature`.
+//!
+//! A hybrid key or signature is the Ed25519 part followed by the ML-DSA-65
+//! part; both must verify.
+
+use serde::{Deserialize, Serialize};
+use crate::error::{CoreError, Result};
+use super::{PublicKey, Signature};
+
+/// Ed25519 public key length.
+pub const ED25519_PUBLIC_KEY_LEN: usize = 32;
+
+/// Ed25519 signature length.
+pub const ED25519_SIGNATURE_LEN: usize = 64;
+
+/// ML-DSA-65 public key length.
+pub const ML_DSA_65_PUBLIC_KEY_LEN: usize = 1952;
+
+/// ML-DSA-65 signature length.
+pub const ML_DSA_65_SIGNATURE_LEN: usize = 3309;
+
+/// Signature algorithm. The discriminant is the on-wire id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum SignatureAlgorithm {
+    /// Ed25519.
+    Ed25519 = 1,
+    /// Ed25519 and ML-DSA-65, both required.
+    HybridEd25519MlDsa65 = 2,
+}
+
+impl SignatureAlgorithm {
+    /// On-wire id.
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Parse an on-wire id.
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            1 => Ok(Self::Ed25519),
+            2 => Ok(Self::HybridEd25519MlDsa65),
+            other => Err(CoreError::CryptoError(format!("unknown signature algorithm {}", other))),
+        }
+    }
+
+    /// Public key length in bytes.
+    pub fn public_key_len(self) -> usize {
+        match self {
+            Self::Ed25519 => ED25519_PUBLIC_KEY_LEN,
+            Self::HybridEd25519MlDsa65 => ED25519_PUBLIC_KEY_LEN + ML_DSA_65_PUBLIC_KEY_LEN,
+        }
+    }
+
+    /// Signature length in bytes.
+    pub fn signature_len(self) -> usize {
+        match self {
+            Self::Ed25519 => ED25519_SIGNATURE_LEN,
+            Self::HybridEd25519MlDsa65 => ED25519_SIGNATURE_LEN + ML_DSA_65_SIGNATURE_LEN,
+        }
+    }
+}
+
+/// Public key of any supported algorithm.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Tagged")]
+pub struct AnyPublicKey {
+    algorithm: SignatureAlgorithm,
+    bytes: Vec<u8>,
+}
+
+impl AnyPublicKey {
+    /// Wrap raw key bytes, checking the length for `algorithm`.
+    pub fn new(algorithm: SignatureAlgorithm, bytes: Vec<u8>) -> Result<Self> {
+        check_len("public key", algorithm.public_key_len(), bytes.len())?;
+        Ok(Self { algorithm, bytes })
+    }
+
+    /// Algorithm of this key.
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        self.algorithm
+    }
+
+    /// Raw key bytes, without the algorithm id.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The Ed25519 key, alone or as the classical half of a hybrid key.
+    pub fn ed25519(&self) -> Result<PublicKey> {
+        Ok(PublicKey::from_bytes(prefix(&self.bytes)?))
+    }
+
+    /// The ML-DSA part of a hybrid key.
+    pub fn ml_dsa(&self) -> Option<&[u8]> {
+        match self.algorithm {
+            SignatureAlgorithm::Ed25519 => None,
+            SignatureAlgorithm::HybridEd25519MlDsa65 => Some(&self.bytes[ED25519_PUBLIC_KEY_LEN..]),
+        }
+    }
+
+    /// Encode as `algorithm id | key`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        tagged(self.algorithm, &self.bytes)
+    }
+
+    /// Decode `algorithm id | key`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (algorithm, rest) = untag(bytes)?;
+        Self::new(algorithm, rest.to_vec())
+    }
+}
+
+impl From<PublicKey> for AnyPublicKey {
+    fn from(key: PublicKey) -> Self {
+        Self { algorithm: SignatureAlgorithm::Ed25519, bytes: key.as_bytes().to_vec() }
+    }
+}
+
+/// Signature of any supported algorithm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Tagged")]
+pub struct AnySignature {
+    algorithm: SignatureAlgorithm,
+    bytes: Vec<u8>,
+}
+
+impl AnySignature {
+    /// Wrap raw signature bytes, checking the length for `algorithm`.
+    pub fn new(algorithm: SignatureAlgorithm, bytes: Vec<u8>) -> Result<Self> {
+        check_len("signature", algorithm.signature_len(), bytes.len())?;
+        Ok(Self { algorithm, bytes })
+    }
+
+    /// Algorithm of this signature.
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        self.algorithm
+    }
+
+    /// Raw signature bytes, without the algorithm id.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The Ed25519 signature, alone or as the classical half of a hybrid.
+    pub fn ed25519(&self) -> Result<Signature> {
+        Ok(Signature::from_bytes(prefix(&self.bytes)?))
+    }
+
+    /// The ML-DSA part of a hybrid signature.
+    pub fn ml_dsa(&self) -> Option<&[u8]> {
+        match self.algorithm {
+            SignatureAlgorithm::Ed25519 => None,
+            SignatureAlgorithm::HybridEd25519MlDsa65 => Some(&self.bytes[ED25519_SIGNATURE_LEN..]),
+        }
+    }
+
+    /// Encode as `algorithm id | signature`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        tagged(self.algorithm, &self.bytes)
+    }
+
+    /// Decode `algorithm id | signature`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (algorithm, rest) = untag(bytes)?;
+        Self::new(algorithm, rest.to_vec())
+    }
+}
+
+impl From<Signature> for AnySignature {
+    fn from(signature: Signature) -> Self {
+        Self { algorithm: SignatureAlgorithm::Ed25519, bytes: signature.as_bytes().to_vec() }
+    }
+}
+
+/// Serialized form of both types, checked through `new` when decoded.
+#[derive(Deserialize)]
+struct Tagged {
+    algorithm: SignatureAlgorithm,
+    bytes: Vec<u8>,
+}
+
+impl TryFrom<Tagged> for AnyPublicKey {
+    type Error = CoreError;
+
+    fn try_from(tagged: Tagged) -> Result<Self> {
+        Self::new(tagged.algorithm, tagged.bytes)
+    }
+}
+
+impl TryFrom<Tagged> for AnySignature {
+    type Error = CoreError;
+
+    fn try_from(tagged: Tagged) -> Result<Self> {
+        Self::new(tagged.algorithm, tagged.bytes)
+    }
+}
+
+fn prefix<const N: usize>(bytes: &[u8]) -> Result<[u8; N]> {
+    bytes
+        .get(..N)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| CoreError::CryptoError(format!("{} bytes, expected at least {}", bytes.len(), N)))
+}
+
+fn check_len(what: &str, expected: usize, got: usize) -> Result<()> {
+    if expected != got {
+        return Err(CoreError::CryptoError(format!("{} is {} bytes, expected {}", what, got, expected)));
+    }
+    Ok(())
+}
+
+fn tagged(algorithm: SignatureAlgorithm, bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + bytes.len());
+    out.push(algorithm.id());
+    out.extend_from_slice(bytes);
+    out
+}
+
+fn untag(bytes: &[u8]) -> Result<(SignatureAlgorithm, &[u8])> {
+    let (&id, rest) = bytes.split_first().ok_or_else(|| CoreError::CryptoError("empty".into()))?;
+    Ok((SignatureAlgorithm::from_id(id)?, rest))
+}
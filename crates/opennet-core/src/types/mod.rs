@@ -4,8 +4,10 @@ pub mod hash;
 pub mod signature;
 pub mod public_key;
 pub mod timestamp;
+pub mod algorithm;
 
 pub use hash::Hash256;
 pub use signature::Signature;
 pub use public_key::PublicKey;
 pub use timestamp::Timestamp;
+pub use algorithm::{AnyPublicKey, AnySignature, SignatureAlgorithm};
@@ -1,48 +1,86 @@
 //! Timestamp type (NOT system time).
 //!
 //! This is a protocol timestamp, not a direct system time access.
+//!
+//! Since protocol version 2 timestamps carry milliseconds. On the wire
+//! they are a single CBOR unsigned integer of milliseconds since the UNIX
+//! epoch, in the shortest form; version 1 carried whole seconds.
 
 use serde::{Deserialize, Serialize};
 
-/// Protocol timestamp (seconds since UNIX epoch).
+/// Milliseconds per second.
+const MILLIS: u64 = 1_000;
+
+/// Protocol timestamp (milliseconds since UNIX epoch).
 ///
 /// This type represents timestamps exchanged in the protocol.
 /// It does NOT directly access system time.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
-pub struct Timestamp(pub u64);
+pub struct Timestamp(u64);
 
 impl Timestamp {
-    /// Create a new timestamp.
+    /// Create a new timestamp from whole seconds.
     pub fn new(secs: u64) -> Self {
-        Self(secs)
+        Self(secs.saturating_mul(MILLIS))
+    }
+
+    /// Create a timestamp from milliseconds since epoch.
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(millis)
     }
 
-    /// Get seconds since epoch.
+    /// Get whole seconds since epoch, rounded down.
     pub fn as_secs(&self) -> u64 {
+        self.0 / MILLIS
+    }
+
+    /// Get milliseconds since epoch.
+    pub const fn as_millis(&self) -> u64 {
         self.0
     }
 
+    /// Millisecond part within the second.
+    pub fn subsec_millis(&self) -> u64 {
+        self.0 % MILLIS
+    }
+
     /// Zero timestamp.
     pub const ZERO: Self = Self(0);
 
     /// Check if timestamp is within range of another.
     pub fn within_range(&self, other: Timestamp, tolerance_secs: u64) -> bool {
-        let diff = if self.0 > other.0 {
-            self.0 - other.0
-        } else {
-            other.0 - self.0
-        };
-        diff <= tolerance_secs
+        self.abs_diff_millis(other) <= tolerance_secs.saturating_mul(MILLIS)
+    }
+
+    /// Distance to another timestamp in milliseconds.
+    pub fn abs_diff_millis(&self, other: Timestamp) -> u64 {
+        self.0.abs_diff(other.0)
+    }
+
+    /// Signed `self - other` in milliseconds, saturating.
+    pub fn signed_diff_millis(&self, other: Timestamp) -> i64 {
+        let diff = i128::from(self.0) - i128::from(other.0);
+        diff.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
     }
 
     /// Add seconds.
     pub fn add_secs(&self, secs: u64) -> Self {
-        Self(self.0.saturating_add(secs))
+        Self(self.0.saturating_add(secs.saturating_mul(MILLIS)))
     }
 
     /// Subtract seconds.
     pub fn sub_secs(&self, secs: u64) -> Self {
-        Self(self.0.saturating_sub(secs))
+        Self(self.0.saturating_sub(secs.saturating_mul(MILLIS)))
+    }
+
+    /// Add milliseconds.
+    pub fn add_millis(&self, millis: u64) -> Self {
+        Self(self.0.saturating_add(millis))
+    }
+
+    /// Add signed milliseconds, saturating at zero and `u64::MAX`.
+    pub fn add_signed_millis(&self, millis: i64) -> Self {
+        Self(self.0.saturating_add_signed(millis))
     }
 }
 
@@ -54,6 +92,6 @@ impl Default for Timestamp {
 
 impl std::fmt::Display for Timestamp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}.{:03}", self.as_secs(), self.subsec_millis())
     }
 }
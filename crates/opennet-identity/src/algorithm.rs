@@ -0,0 +1,73 @@
+//! Signature algorithm selection and verification.
+//!
+//! Ed25519 is always available. The hybrid Ed25519 + ML-DSA-65 scheme is
+//! compiled in with the `pq-hybrid` feature and used with a peer only when
+//! both sides advertise [`FEATURE_HYBRID_SIGNATURES`].
+
+use opennet_core::FEATURE_HYBRID_SIGNATURES;
+use opennet_core::types::{AnyPublicKey, AnySignature, SignatureAlgorithm};
+use crate::keypair::verify_signature;
+use crate::error::{IdentityError, Result};
+
+#[cfg(feature = "pq-hybrid")]
+pub use crate::hybrid::HybridKeyPair;
+
+/// Feature bits this build supports, for `NodeHello.features`.
+pub fn supported_features() -> u64 {
+    if cfg!(feature = "pq-hybrid") {
+        FEATURE_HYBRID_SIGNATURES
+    } else {
+        0
+    }
+}
+
+/// Feature bits to advertise for a key of `algorithm`. Hybrid signatures
+/// are offered only with a hybrid key, since a session that negotiates
+/// them refuses Ed25519 keys.
+pub fn features_for(algorithm: SignatureAlgorithm) -> u64 {
+    match algorithm {
+        SignatureAlgorithm::Ed25519 => supported_features() & !FEATURE_HYBRID_SIGNATURES,
+        SignatureAlgorithm::HybridEd25519MlDsa65 => supported_features(),
+    }
+}
+
+/// Feature bits both sides support, for `NodeWelcome.features`.
+pub fn negotiate_features(local: u64, remote: u64) -> u64 {
+    local & remote
+}
+
+/// Strongest algorithm allowed by negotiated feature bits.
+pub fn negotiated_algorithm(features: u64) -> SignatureAlgorithm {
+    if features & FEATURE_HYBRID_SIGNATURES != 0 {
+        SignatureAlgorithm::HybridEd25519MlDsa65
+    } else {
+        SignatureAlgorithm::Ed25519
+    }
+}
+
+/// Verify a signature of any algorithm. Key and signature algorithms must
+/// match; a hybrid signature needs both halves to verify.
+pub fn verify_any(public_key: &AnyPublicKey, message: &[u8], signature: &AnySignature) -> Result<()> {
+    if public_key.algorithm() != signature.algorithm() {
+        return Err(IdentityError::SignatureVerificationFailed);
+    }
+    match public_key.algorithm() {
+        SignatureAlgorithm::Ed25519 => {
+            let (Ok(key), Ok(sig)) = (public_key.ed25519(), signature.ed25519()) else {
+                return Err(IdentityError::SignatureVerificationFailed);
+            };
+            verify_signature(&key, message, &sig)
+        }
+        SignatureAlgorithm::HybridEd25519MlDsa65 => verify_hybrid(public_key, message, signature),
+    }
+}
+
+#[cfg(feature = "pq-hybrid")]
+fn verify_hybrid(public_key: &AnyPublicKey, message: &[u8], signature: &AnySignature) -> Result<()> {
+    crate::hybrid::verify(public_key, message, signature)
+}
+
+#[cfg(not(feature = "pq-hybrid"))]
+fn verify_hybrid(_: &AnyPublicKey, _: &[u8], _: &AnySignature) -> Result<()> {
+    Err(IdentityError::CryptoError("hybrid signatures need the pq-hybrid feature".into()))
+}
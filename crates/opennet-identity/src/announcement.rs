@@ -1,11 +1,21 @@
 //! Identity announcement messages.
+//!
+//! An announcement is a node's self-signed statement of its current epoch
+//! and public key. Signing bytes and the gossiped form are canonical CBOR,
+//! so every node signs and verifies exactly the same bytes.
 
 use opennet_core::{NodeId, Epoch};
 use opennet_core::types::{PublicKey, Signature, Timestamp};
+use opennet_wire::cbor::{CborDecoder, CborEncoder};
 use serde::{Deserialize, Serialize};
+use crate::context::SigningContext;
+use crate::keypair::verify_signature;
+use crate::node_identity::NodeIdentity;
+use crate::signer::NodeSigner;
+use crate::error::{IdentityError, Result};
 
 /// Identity announcement broadcast to network.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IdentityAnnouncement {
     /// Node identifier.
     pub node_id: NodeId,
@@ -20,13 +30,102 @@ pub struct IdentityAnnouncement {
 }
 
 impl IdentityAnnouncement {
-    /// Create announcement bytes for signing.
+    /// Announce `identity`'s current epoch and key.
+    pub fn sign(identity: &NodeIdentity, timestamp: Timestamp) -> Result<Self> {
+        Self::create(*identity.node_id(), identity.epoch().clone(), timestamp, identity.signer())
+    }
+
+    /// Build an announcement self-signed by `signer`.
+    pub fn create(node_id: NodeId, epoch: Epoch, timestamp: Timestamp, signer: &dyn NodeSigner) -> Result<Self> {
+        let mut announcement = Self {
+            node_id,
+            epoch,
+            public_key: signer.public_key(),
+            timestamp,
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        announcement.signature = signer.sign(&announcement.signing_bytes())?;
+        Ok(announcement)
+    }
+
+    /// Signed fields `[node_id, epoch_id, epoch_start, public_key,
+    /// timestamp]` in the announcement context.
     pub fn signing_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(self.node_id.as_bytes());
-        bytes.extend_from_slice(&self.epoch.id.to_be_bytes());
-        bytes.extend_from_slice(self.public_key.as_bytes());
-        bytes.extend_from_slice(&self.timestamp.as_secs().to_be_bytes());
-        bytes
+        SigningContext::Announcement.signing_bytes(|enc| {
+            enc.encode_array_header(5);
+            self.encode_fields(enc);
+        })
+    }
+
+    /// Check the self-signature and that the announcement is internally
+    /// consistent.
+    ///
+    /// For epoch 1 this also proves the key owns the NodeId. A later
+    /// epoch's key must be checked against the node's rotation history,
+    /// see [`IdentityDirectory`](crate::directory::IdentityDirectory).
+    pub fn verify(&self) -> Result<()> {
+        if self.epoch.id == 0 {
+            return Err(IdentityError::EpochChainBroken("epoch 0".into()));
+        }
+        if self.epoch.id == 1 && NodeId::from_public_key(self.public_key.as_bytes()) != self.node_id {
+            return Err(IdentityError::InvalidKey("epoch 1 key does not match NodeId".into()));
+        }
+        if !self.epoch.is_valid_at(self.timestamp.as_secs()) {
+            return Err(IdentityError::EpochChainBroken("announcement outside its epoch".into()));
+        }
+        verify_signature(&self.public_key, &self.signing_bytes(), &self.signature)
+    }
+
+    /// Canonical CBOR for gossip:
+    /// `[node_id, epoch_id, epoch_start, public_key, timestamp, signature]`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = CborEncoder::new();
+        enc.encode_array_header(6);
+        self.encode_fields(&mut enc);
+        enc.encode_bytes(self.signature.as_bytes());
+        enc.into_bytes()
     }
+
+    /// Decode a gossiped announcement. Does not verify it.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut dec = CborDecoder::new(bytes);
+        if dec.decode_array_header().map_err(malformed)? != 6 {
+            return Err(malformed("expected 6-element announcement"));
+        }
+        let node_id = NodeId::from_bytes(fixed(&mut dec)?);
+        let epoch_id = dec.decode_uint().map_err(malformed)?;
+        let epoch_start = dec.decode_uint().map_err(malformed)?;
+        let public_key = PublicKey::from_bytes(fixed(&mut dec)?);
+        let timestamp = dec.decode_timestamp().map_err(malformed)?;
+        let signature = Signature::from_bytes(fixed(&mut dec)?);
+        if !dec.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        // Same key_hash convention as NodeIdentity.
+        let key_hash = if epoch_id == 1 { *node_id.as_bytes() } else { *public_key.as_bytes() };
+        Ok(Self {
+            node_id,
+            epoch: Epoch::new(epoch_id, epoch_start, key_hash),
+            public_key,
+            timestamp,
+            signature,
+        })
+    }
+
+    fn encode_fields(&self, enc: &mut CborEncoder) {
+        enc.encode_bytes(self.node_id.as_bytes())
+            .encode_uint(self.epoch.id)
+            .encode_uint(self.epoch.start_time)
+            .encode_bytes(self.public_key.as_bytes())
+            .encode_timestamp(self.timestamp);
+    }
+}
+
+fn fixed<const N: usize>(dec: &mut CborDecoder) -> Result<[u8; N]> {
+    let bytes = dec.decode_bytes().map_err(malformed)?;
+    bytes.as_slice().try_into().map_err(|_| malformed(format!("expected {} bytes, got {}", N, bytes.len())))
+}
+
+fn malformed(e: impl std::fmt::Display) -> IdentityError {
+    IdentityError::InvalidKey(format!("malformed announcement: {}", e))
 }
@@ -0,0 +1,245 @@
+//! Offline backup of a key's 32-byte secret as Shamir shares.
+//!
+//! The secret is split byte-wise over GF(2^8) into `n` shares, any `k` of
+//! which recover it. Each share is 41 bytes:
+//!
+//! ```text
+//! version (1) | set id (2) | threshold (1) | index (1) | value (32) | checksum (4)
+//! ```
+//!
+//! The checksum is the first four bytes of SHA-256 over the preceding
+//! bytes and catches transcription errors in a single share. The set id
+//! is derived from the secret, so shares from different splits are not
+//! mixed and a wrong reconstruction is detected. For writing down, a share
+//! is encoded as 30 words from the BIP-39 English list (11 bits per word,
+//! the last two bits zero).
+
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, Zeroizing};
+use crate::error::{IdentityError, Result};
+
+/// Share format version.
+pub const SHARE_VERSION: u8 = 1;
+
+/// Encoded share length in bytes.
+pub const SHARE_LEN: usize = 41;
+
+/// Words in a mnemonic-encoded share.
+pub const SHARE_WORDS: usize = 30;
+
+/// One share of a split secret.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SeedShare {
+    set_id: u16,
+    threshold: u8,
+    index: u8,
+    value: Zeroizing<[u8; 32]>,
+}
+
+impl SeedShare {
+    /// Identifier shared by all shares of one split.
+    pub fn set_id(&self) -> u16 {
+        self.set_id
+    }
+
+    /// Shares needed to recover the secret.
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Share number, 1-based.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Binary encoding with checksum.
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(SHARE_LEN));
+        bytes.push(SHARE_VERSION);
+        bytes.extend_from_slice(&self.set_id.to_be_bytes());
+        bytes.push(self.threshold);
+        bytes.push(self.index);
+        bytes.extend_from_slice(self.value.as_ref());
+        let checksum = Sha256::digest(&bytes[..]);
+        bytes.extend_from_slice(&checksum[..4]);
+        bytes
+    }
+
+    /// Decode and check a binary share.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != SHARE_LEN {
+            return Err(backup_err(format!("share is {} bytes, expected {}", bytes.len(), SHARE_LEN)));
+        }
+        if bytes[0] != SHARE_VERSION {
+            return Err(backup_err(format!("unsupported share version {}", bytes[0])));
+        }
+        let checksum = Sha256::digest(&bytes[..SHARE_LEN - 4]);
+        if checksum[..4] != bytes[SHARE_LEN - 4..] {
+            return Err(backup_err("share checksum mismatch"));
+        }
+        let (threshold, index) = (bytes[3], bytes[4]);
+        if threshold == 0 || index == 0 {
+            return Err(backup_err("share has zero threshold or index"));
+        }
+        let mut value = Zeroizing::new([0u8; 32]);
+        value.copy_from_slice(&bytes[5..37]);
+        Ok(Self { set_id: u16::from_be_bytes([bytes[1], bytes[2]]), threshold, index, value })
+    }
+
+    /// Encode as space-separated BIP-39 English words.
+    pub fn to_mnemonic(&self) -> Zeroizing<String> {
+        let words = bip39::Language::English.word_list();
+        let bytes = self.to_bytes();
+        let mut out = Zeroizing::new(String::new());
+        for i in 0..SHARE_WORDS {
+            let word = (0..11).fold(0usize, |acc, bit| {
+                let pos = i * 11 + bit;
+                let set = pos < SHARE_LEN * 8 && bytes[pos / 8] & (0x80 >> (pos % 8)) != 0;
+                (acc << 1) | usize::from(set)
+            });
+            if i > 0 {
+                out.push(' ');
+            }
+            out.push_str(words[word]);
+        }
+        out
+    }
+
+    /// Decode a mnemonic produced by [`to_mnemonic`](Self::to_mnemonic).
+    pub fn from_mnemonic(phrase: &str) -> Result<Self> {
+        let words: Vec<&str> = phrase.split_whitespace().collect();
+        if words.len() != SHARE_WORDS {
+            return Err(backup_err(format!("share has {} words, expected {}", words.len(), SHARE_WORDS)));
+        }
+        let mut bytes = Zeroizing::new([0u8; SHARE_LEN]);
+        let mut padding = 0u16;
+        for (i, word) in words.iter().enumerate() {
+            let value = bip39::Language::English
+                .find_word(&word.to_lowercase())
+                .ok_or_else(|| backup_err(format!("unknown word {:?}", word)))?;
+            for bit in 0..11 {
+                let pos = i * 11 + bit;
+                let set = value & (0x400 >> bit) != 0;
+                if pos < SHARE_LEN * 8 {
+                    bytes[pos / 8] |= u8::from(set) << (7 - pos % 8);
+                } else {
+                    padding |= u16::from(set);
+                }
+            }
+        }
+        if padding != 0 {
+            return Err(backup_err("share has non-zero padding"));
+        }
+        Self::from_bytes(bytes.as_ref())
+    }
+}
+
+impl std::fmt::Debug for SeedShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SeedShare")
+            .field("set_id", &self.set_id)
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Split `secret` into `shares` shares, any `threshold` of which recover it.
+pub fn split_secret(secret: &[u8; 32], threshold: u8, shares: u8) -> Result<Vec<SeedShare>> {
+    if threshold == 0 || threshold > shares {
+        return Err(backup_err(format!("cannot split {}-of-{}", threshold, shares)));
+    }
+    let set_id = set_id(secret);
+    let mut out: Vec<SeedShare> = (1..=shares)
+        .map(|index| SeedShare { set_id, threshold, index, value: Zeroizing::new([0u8; 32]) })
+        .collect();
+
+    let mut coefficients = Zeroizing::new(vec![0u8; usize::from(threshold)]);
+    for (byte, secret_byte) in secret.iter().enumerate() {
+        coefficients[0] = *secret_byte;
+        rand::rngs::OsRng.fill_bytes(&mut coefficients[1..]);
+        for share in &mut out {
+            // Horner evaluation at x = index.
+            share.value[byte] = coefficients.iter().rev().fold(0, |acc, c| gf_mul(acc, share.index) ^ c);
+        }
+    }
+    coefficients.zeroize();
+    Ok(out)
+}
+
+/// Recover the secret from at least `threshold` shares of one split.
+pub fn combine_shares(shares: &[SeedShare]) -> Result<Zeroizing<[u8; 32]>> {
+    let first = shares.first().ok_or_else(|| backup_err("no shares"))?;
+    if shares.iter().any(|s| s.set_id != first.set_id || s.threshold != first.threshold) {
+        return Err(backup_err("shares come from different backups"));
+    }
+    let mut used: Vec<&SeedShare> = Vec::new();
+    for share in shares {
+        if !used.iter().any(|u| u.index == share.index) {
+            used.push(share);
+        }
+    }
+    if used.len() < usize::from(first.threshold) {
+        return Err(backup_err(format!("need {} distinct shares, have {}", first.threshold, used.len())));
+    }
+    used.truncate(usize::from(first.threshold));
+
+    // Lagrange basis at x = 0: prod x_j / (x_j - x_i); subtraction is XOR.
+    let basis: Vec<u8> = used
+        .iter()
+        .map(|i| {
+            used.iter().filter(|j| j.index != i.index).fold(1, |acc, j| {
+                gf_mul(acc, gf_mul(j.index, gf_inv(j.index ^ i.index)))
+            })
+        })
+        .collect();
+    let mut secret = Zeroizing::new([0u8; 32]);
+    for (byte, out) in secret.iter_mut().enumerate() {
+        *out = used.iter().zip(&basis).fold(0, |acc, (share, b)| acc ^ gf_mul(share.value[byte], *b));
+    }
+    if set_id(&secret) != first.set_id {
+        return Err(backup_err("recovered secret does not match the backup"));
+    }
+    Ok(secret)
+}
+
+fn set_id(secret: &[u8; 32]) -> u16 {
+    let mut hasher = Sha256::new();
+    hasher.update(b"opennet/backup");
+    hasher.update(secret);
+    let digest = hasher.finalize();
+    u16::from_be_bytes([digest[0], digest[1]])
+}
+
+/// Multiply in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1, without
+/// data-dependent branches.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// Inverse as a^254.
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+fn backup_err(msg: impl Into<String>) -> IdentityError {
+    IdentityError::BackupError(msg.into())
+}
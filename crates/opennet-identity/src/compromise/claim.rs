@@ -0,0 +1,144 @@
+//! Signed statements the compromise detector reasons about.
+
+use opennet_core::{EpochId, NodeId};
+use opennet_core::types::{PublicKey, Signature, Timestamp};
+use opennet_wire::cbor::CborEncoder;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use crate::context::SigningContext;
+use crate::keypair::verify_signature;
+use crate::rotation::PeerKeyRegistry;
+use crate::signer::NodeSigner;
+use crate::error::{IdentityError, Result};
+
+/// A node's signed, sequenced statement about a payload.
+///
+/// Honest nodes never sign two different payloads under the same
+/// `(epoch, sequence)`; retransmitting the same claim is harmless.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedClaim {
+    /// Signing node.
+    pub node_id: NodeId,
+    /// Epoch of the signing key.
+    pub epoch: EpochId,
+    /// Per-epoch message sequence number.
+    pub sequence: u64,
+    /// Signer's timestamp.
+    pub timestamp: Timestamp,
+    /// SHA-256 of the payload.
+    pub payload_hash: [u8; 32],
+    /// Signature over [`signing_bytes`](Self::signing_bytes).
+    pub signature: Signature,
+}
+
+impl SignedClaim {
+    /// Sign a claim over `payload`.
+    pub fn sign(
+        node_id: NodeId,
+        epoch: EpochId,
+        sequence: u64,
+        timestamp: Timestamp,
+        payload: &[u8],
+        signer: &dyn NodeSigner,
+    ) -> Result<Self> {
+        let mut claim = Self {
+            node_id,
+            epoch,
+            sequence,
+            timestamp,
+            payload_hash: Sha256::digest(payload).into(),
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        claim.signature = signer.sign(&claim.signing_bytes())?;
+        Ok(claim)
+    }
+
+    /// Bytes covered by the signature.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        SigningContext::Claim.signing_bytes(|enc| self.encode_body(enc))
+    }
+
+    fn encode_body(&self, enc: &mut CborEncoder) {
+        enc.encode_array_header(5)
+            .encode_bytes(self.node_id.as_bytes())
+            .encode_uint(self.epoch)
+            .encode_uint(self.sequence)
+            .encode_timestamp(self.timestamp)
+            .encode_bytes(&self.payload_hash);
+    }
+
+    /// Check the signature.
+    pub fn verify(&self, public_key: &PublicKey) -> Result<()> {
+        verify_signature(public_key, &self.signing_bytes(), &self.signature)
+    }
+}
+
+/// A peer's signed report of where and when it received a claim.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Observation {
+    /// Claim as received.
+    pub claim: SignedClaim,
+    /// Observing peer.
+    pub observer: NodeId,
+    /// Observer's current public key.
+    pub observer_key: PublicKey,
+    /// Source address the claim arrived from.
+    pub source: IpAddr,
+    /// Observer's receive time in milliseconds.
+    pub received_at_ms: u64,
+    /// Observer's signature over [`signing_bytes`](Self::signing_bytes).
+    pub observer_signature: Signature,
+}
+
+impl Observation {
+    /// Sign an observation as `observer`.
+    pub fn sign(
+        claim: SignedClaim,
+        observer: NodeId,
+        source: IpAddr,
+        received_at_ms: u64,
+        signer: &dyn NodeSigner,
+    ) -> Result<Self> {
+        let mut observation = Self {
+            claim,
+            observer,
+            observer_key: signer.public_key(),
+            source,
+            received_at_ms,
+            observer_signature: Signature::from_bytes([0u8; 64]),
+        };
+        observation.observer_signature = signer.sign(&observation.signing_bytes())?;
+        Ok(observation)
+    }
+
+    /// Bytes covered by the observer's signature.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        SigningContext::Observation.signing_bytes(|enc| {
+            enc.encode_array_header(5);
+            self.claim.encode_body(enc);
+            enc.encode_bytes(self.claim.signature.as_bytes()).encode_bytes(self.observer.as_bytes());
+            match self.source {
+                IpAddr::V4(addr) => enc.encode_bytes(&addr.octets()),
+                IpAddr::V6(addr) => enc.encode_bytes(&addr.octets()),
+            };
+            enc.encode_uint(self.received_at_ms);
+        })
+    }
+
+    /// Whether `observer_key` belongs to `observer`: it is the observer's
+    /// genesis key, or a key `keys` knows for it.
+    pub fn observer_key_bound(&self, keys: &PeerKeyRegistry) -> bool {
+        NodeId::from_public_key(self.observer_key.as_bytes()) == self.observer
+            || keys.knows_key(&self.observer, &self.observer_key)
+    }
+
+    /// Check both the claim's and the observer's signatures, and that the
+    /// observer key is bound to the observer.
+    pub fn verify(&self, claimant_key: &PublicKey, keys: &PeerKeyRegistry) -> Result<()> {
+        if !self.observer_key_bound(keys) {
+            return Err(IdentityError::InvalidKey("observer key is not bound to the observer".into()));
+        }
+        self.claim.verify(claimant_key)?;
+        verify_signature(&self.observer_key, &self.signing_bytes(), &self.observer_signature)
+    }
+}
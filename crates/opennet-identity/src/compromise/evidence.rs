@@ -0,0 +1,286 @@
+//! Self-contained, verifiable compromise evidence.
+//!
+//! Each bundle carries the signed statements that contradict each other
+//! and the key that signed them, so any peer can re-check it without
+//! trusting the reporter. Binding that key to the NodeId is left to the
+//! receiver (it is the NodeId's genesis key or a key from its rotation
+//! log). Observer keys are bound the same way, against the receiver's
+//! [`PeerKeyRegistry`]; whether the observers are trusted to report
+//! honest receive times is also the receiver's call.
+
+use opennet_core::NodeId;
+use opennet_core::types::{PublicKey, Signature};
+use opennet_wire::cbor::{CborDecoder, CborEncoder};
+use std::net::IpAddr;
+use super::claim::{Observation, SignedClaim};
+use crate::rotation::{validate_rotation, PeerKeyRegistry, RotationRequest};
+use crate::error::{IdentityError, Result};
+
+/// Claims from the same key received fewer than this many milliseconds
+/// apart, from unrelated networks, cannot come from a single honest node.
+pub const MIN_RELOCATION_MS: u64 = 30_000;
+
+/// Evidence of potential compromise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompromiseEvidence {
+    /// Two different payloads signed under the same epoch and sequence.
+    Equivocation {
+        /// Key that signed both.
+        public_key: PublicKey,
+        /// First claim.
+        first: Box<SignedClaim>,
+        /// Contradicting claim.
+        second: Box<SignedClaim>,
+    },
+    /// Distinct claims received from unrelated networks too close together
+    /// in time.
+    ImpossibleTiming {
+        /// Key that signed both claims.
+        public_key: PublicKey,
+        /// First observation.
+        first: Box<Observation>,
+        /// Second observation, from a different observer and network.
+        second: Box<Observation>,
+    },
+    /// Two different keys rotated into the same epoch by one old key.
+    ConflictingEpoch {
+        /// Key of the epoch both rotations leave.
+        old_key: PublicKey,
+        /// First rotation.
+        first: Box<RotationRequest>,
+        /// Contradicting rotation.
+        second: Box<RotationRequest>,
+    },
+}
+
+impl CompromiseEvidence {
+    /// NodeId the evidence is about.
+    pub fn node_id(&self) -> &NodeId {
+        match self {
+            Self::Equivocation { first, .. } => &first.node_id,
+            Self::ImpossibleTiming { first, .. } => &first.claim.node_id,
+            Self::ConflictingEpoch { first, .. } => &first.node_id,
+        }
+    }
+
+    /// Wire kind: 1 = equivocation, 2 = impossible timing,
+    /// 3 = conflicting epoch.
+    pub fn kind(&self) -> u8 {
+        match self {
+            Self::Equivocation { .. } => 1,
+            Self::ImpossibleTiming { .. } => 2,
+            Self::ConflictingEpoch { .. } => 3,
+        }
+    }
+
+    /// Key the evidence shows to be compromised.
+    pub fn public_key(&self) -> &PublicKey {
+        match self {
+            Self::Equivocation { public_key, .. } | Self::ImpossibleTiming { public_key, .. } => public_key,
+            Self::ConflictingEpoch { old_key, .. } => old_key,
+        }
+    }
+
+    /// Check that the bundle really shows a contradiction. Observer keys
+    /// must be genesis keys or known to `keys`.
+    pub fn verify(&self, keys: &PeerKeyRegistry) -> Result<()> {
+        match self {
+            Self::Equivocation { public_key, first, second } => {
+                if first.node_id != second.node_id
+                    || first.epoch != second.epoch
+                    || first.sequence != second.sequence
+                    || first.payload_hash == second.payload_hash
+                {
+                    return Err(invalid("claims do not equivocate"));
+                }
+                first.verify(public_key)?;
+                second.verify(public_key)
+            }
+            Self::ImpossibleTiming { public_key, first, second } => {
+                if first.claim.node_id != second.claim.node_id
+                    || first.claim == second.claim
+                    || first.observer == second.observer
+                    || same_network(&first.source, &second.source)
+                    || first.received_at_ms.abs_diff(second.received_at_ms) >= MIN_RELOCATION_MS
+                {
+                    return Err(invalid("observations are consistent with one node"));
+                }
+                first.verify(public_key, keys)?;
+                second.verify(public_key, keys)
+            }
+            Self::ConflictingEpoch { old_key, first, second } => {
+                if first.node_id != second.node_id
+                    || first.current_epoch != second.current_epoch
+                    || first.new_public_key == second.new_public_key
+                {
+                    return Err(invalid("rotations do not conflict"));
+                }
+                validate_rotation(first, old_key)?;
+                validate_rotation(second, old_key)
+            }
+        }
+    }
+
+    /// Canonical CBOR encoding for gossip.
+    ///
+    /// `[kind, key, first, second]`, see [`kind`](Self::kind).
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = CborEncoder::new();
+        enc.encode_array_header(4)
+            .encode_uint(u64::from(self.kind()))
+            .encode_bytes(self.public_key().as_bytes());
+        match self {
+            Self::Equivocation { first, second, .. } => {
+                encode_claim(&mut enc, first);
+                encode_claim(&mut enc, second);
+            }
+            Self::ImpossibleTiming { first, second, .. } => {
+                encode_observation(&mut enc, first);
+                encode_observation(&mut enc, second);
+            }
+            Self::ConflictingEpoch { first, second, .. } => {
+                encode_rotation(&mut enc, first);
+                encode_rotation(&mut enc, second);
+            }
+        }
+        enc.into_bytes()
+    }
+
+    /// Decode a bundle. Does not verify it.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut dec = CborDecoder::new(bytes);
+        expect_array(&mut dec, 4)?;
+        let kind = dec.decode_uint().map_err(malformed)?;
+        let key = PublicKey::from_bytes(fixed(&mut dec)?);
+        let evidence = match kind {
+            1 => Self::Equivocation {
+                public_key: key,
+                first: Box::new(decode_claim(&mut dec)?),
+                second: Box::new(decode_claim(&mut dec)?),
+            },
+            2 => Self::ImpossibleTiming {
+                public_key: key,
+                first: Box::new(decode_observation(&mut dec)?),
+                second: Box::new(decode_observation(&mut dec)?),
+            },
+            3 => Self::ConflictingEpoch {
+                old_key: key,
+                first: Box::new(decode_rotation(&mut dec)?),
+                second: Box::new(decode_rotation(&mut dec)?),
+            },
+            other => return Err(malformed(format!("unknown evidence kind {}", other))),
+        };
+        if !dec.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(evidence)
+    }
+}
+
+/// Whether two addresses are in the same coarse network (IPv4 /16,
+/// IPv6 /32), which one node may legitimately move within.
+pub fn same_network(a: &IpAddr, b: &IpAddr) -> bool {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => a.octets()[..2] == b.octets()[..2],
+        (IpAddr::V6(a), IpAddr::V6(b)) => a.octets()[..4] == b.octets()[..4],
+        _ => false,
+    }
+}
+
+fn encode_claim(enc: &mut CborEncoder, c: &SignedClaim) {
+    enc.encode_array_header(6)
+        .encode_bytes(c.node_id.as_bytes())
+        .encode_uint(c.epoch)
+        .encode_uint(c.sequence)
+        .encode_timestamp(c.timestamp)
+        .encode_bytes(&c.payload_hash)
+        .encode_bytes(c.signature.as_bytes());
+}
+
+fn decode_claim(dec: &mut CborDecoder) -> Result<SignedClaim> {
+    expect_array(dec, 6)?;
+    Ok(SignedClaim {
+        node_id: NodeId::from_bytes(fixed(dec)?),
+        epoch: dec.decode_uint().map_err(malformed)?,
+        sequence: dec.decode_uint().map_err(malformed)?,
+        timestamp: dec.decode_timestamp().map_err(malformed)?,
+        payload_hash: fixed(dec)?,
+        signature: Signature::from_bytes(fixed(dec)?),
+    })
+}
+
+fn encode_observation(enc: &mut CborEncoder, o: &Observation) {
+    enc.encode_array_header(6);
+    encode_claim(enc, &o.claim);
+    enc.encode_bytes(o.observer.as_bytes()).encode_bytes(o.observer_key.as_bytes());
+    match o.source {
+        IpAddr::V4(addr) => enc.encode_bytes(&addr.octets()),
+        IpAddr::V6(addr) => enc.encode_bytes(&addr.octets()),
+    };
+    enc.encode_uint(o.received_at_ms).encode_bytes(o.observer_signature.as_bytes());
+}
+
+fn decode_observation(dec: &mut CborDecoder) -> Result<Observation> {
+    expect_array(dec, 6)?;
+    let claim = decode_claim(dec)?;
+    let observer = NodeId::from_bytes(fixed(dec)?);
+    let observer_key = PublicKey::from_bytes(fixed(dec)?);
+    let source_bytes = dec.decode_bytes().map_err(malformed)?;
+    let source = match source_bytes.len() {
+        4 => IpAddr::from(<[u8; 4]>::try_from(source_bytes.as_slice()).map_err(malformed)?),
+        16 => IpAddr::from(<[u8; 16]>::try_from(source_bytes.as_slice()).map_err(malformed)?),
+        n => return Err(malformed(format!("{}-byte address", n))),
+    };
+    Ok(Observation {
+        claim,
+        observer,
+        observer_key,
+        source,
+        received_at_ms: dec.decode_uint().map_err(malformed)?,
+        observer_signature: Signature::from_bytes(fixed(dec)?),
+    })
+}
+
+fn encode_rotation(enc: &mut CborEncoder, r: &RotationRequest) {
+    enc.encode_array_header(7)
+        .encode_bytes(r.node_id.as_bytes())
+        .encode_uint(r.current_epoch)
+        .encode_uint(r.new_epoch)
+        .encode_bytes(r.new_public_key.as_bytes())
+        .encode_timestamp(r.timestamp)
+        .encode_bytes(r.old_key_signature.as_bytes())
+        .encode_bytes(r.new_key_signature.as_bytes());
+}
+
+fn decode_rotation(dec: &mut CborDecoder) -> Result<RotationRequest> {
+    expect_array(dec, 7)?;
+    Ok(RotationRequest {
+        node_id: NodeId::from_bytes(fixed(dec)?),
+        current_epoch: dec.decode_uint().map_err(malformed)?,
+        new_epoch: dec.decode_uint().map_err(malformed)?,
+        new_public_key: PublicKey::from_bytes(fixed(dec)?),
+        timestamp: dec.decode_timestamp().map_err(malformed)?,
+        old_key_signature: Signature::from_bytes(fixed(dec)?),
+        new_key_signature: Signature::from_bytes(fixed(dec)?),
+    })
+}
+
+fn expect_array(dec: &mut CborDecoder, len: usize) -> Result<()> {
+    if dec.decode_array_header().map_err(malformed)? != len {
+        return Err(malformed(format!("expected {}-element array", len)));
+    }
+    Ok(())
+}
+
+fn fixed<const N: usize>(dec: &mut CborDecoder) -> Result<[u8; N]> {
+    let bytes = dec.decode_bytes().map_err(malformed)?;
+    bytes.as_slice().try_into().map_err(|_| malformed(format!("expected {} bytes, got {}", N, bytes.len())))
+}
+
+fn malformed(e: impl std::fmt::Display) -> IdentityError {
+    IdentityError::CompromiseDetected(format!("malformed evidence: {}", e))
+}
+
+fn invalid(reason: &str) -> IdentityError {
+    IdentityError::CompromiseDetected(format!("invalid evidence: {}", reason))
+}
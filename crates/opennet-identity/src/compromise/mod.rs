@@ -0,0 +1,342 @@
+//! Key compromise detection.
+//!
+//! The detector looks for statements no single honest key holder would
+//! make: two different payloads under one `(epoch, sequence)`, or two
+//! claims by the same key received from unrelated networks within
+//! [`MIN_RELOCATION_MS`] of each other. Every
+//! finding is a [`CompromiseEvidence`] bundle that peers can verify on
+//! their own.
+//!
+//! Memory is bounded: claims are kept in detail only for the last two
+//! epochs of each node and for [`DetectorConfig::window_secs`], after which
+//! they are folded into fixed-size Bloom filters. A later claim that hits a
+//! summarised slot with a different payload cannot be proven (the original
+//! is gone) and only raises the node's suspicion count. The number of
+//! tracked nodes and per-node records is capped.
+
+mod claim;
+mod evidence;
+mod summary;
+
+pub use claim::{Observation, SignedClaim};
+pub use evidence::{same_network, CompromiseEvidence, MIN_RELOCATION_MS};
+
+use opennet_core::{EpochId, NodeId};
+use opennet_core::types::PublicKey;
+use std::collections::{BTreeMap, VecDeque};
+use summary::{BloomFilter, CountMinSketch};
+use crate::rotation::PeerKeyRegistry;
+use crate::error::Result;
+
+/// Retention limits for [`CompromiseDetector`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectorConfig {
+    /// How long claims and observations are kept in detail.
+    pub window_secs: u64,
+    /// How long a summary generation lives; summaries cover up to twice this.
+    pub summary_secs: u64,
+    /// Most nodes tracked in detail; the least recently seen is evicted.
+    pub max_nodes: usize,
+    /// Most claims kept in detail per node.
+    pub max_claims_per_node: usize,
+    /// Most observations kept in detail per node.
+    pub max_observations_per_node: usize,
+    /// Bits per Bloom filter (four filters are kept).
+    pub bloom_bits: usize,
+    /// Probes per Bloom filter lookup.
+    pub bloom_hashes: u32,
+    /// Count-min sketch width (counters per row).
+    pub sketch_width: usize,
+    /// Count-min sketch depth (rows).
+    pub sketch_depth: u32,
+}
+
+impl Default for DetectorConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 3600,
+            summary_secs: 86400,
+            max_nodes: 4096,
+            max_claims_per_node: 256,
+            max_observations_per_node: 64,
+            bloom_bits: 1 << 20,
+            bloom_hashes: 7,
+            sketch_width: 2048,
+            sketch_depth: 4,
+        }
+    }
+}
+
+#[derive(Default)]
+struct NodeRecord {
+    /// Verified claims keyed by `(epoch, sequence)`, with receive time.
+    claims: BTreeMap<(EpochId, u64), (SignedClaim, u64)>,
+    /// Verified observations with receive time, oldest first.
+    observations: VecDeque<(Observation, u64)>,
+    last_seen: u64,
+}
+
+/// One generation of claim summaries.
+struct Summary {
+    /// `(node, epoch, sequence)` slots.
+    slots: BloomFilter,
+    /// `(node, epoch, sequence, payload hash)` claims.
+    claims: BloomFilter,
+}
+
+impl Summary {
+    fn new(config: &DetectorConfig) -> Self {
+        Self {
+            slots: BloomFilter::new(config.bloom_bits, config.bloom_hashes),
+            claims: BloomFilter::new(config.bloom_bits, config.bloom_hashes),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.slots.clear();
+        self.claims.clear();
+    }
+}
+
+/// Compromise detector tracks potential key misuse.
+pub struct CompromiseDetector {
+    config: DetectorConfig,
+    nodes: BTreeMap<NodeId, NodeRecord>,
+    current: Summary,
+    previous: Summary,
+    generation_start: u64,
+    /// Summarised-slot collisions per node.
+    suspicion: CountMinSketch,
+}
+
+impl CompromiseDetector {
+    /// Create new detector.
+    pub fn new() -> Self {
+        Self::with_config(DetectorConfig::default())
+    }
+
+    /// Create a detector with custom limits.
+    pub fn with_config(config: DetectorConfig) -> Self {
+        Self {
+            current: Summary::new(&config),
+            previous: Summary::new(&config),
+            suspicion: CountMinSketch::new(config.sketch_width, config.sketch_depth),
+            config,
+            nodes: BTreeMap::new(),
+            generation_start: 0,
+        }
+    }
+
+    /// Record a claim signed by `public_key`, received at `now_secs`.
+    ///
+    /// Fails if the signature does not verify; nothing is stored then.
+    /// Returns evidence if the claim equivocates with a stored one.
+    pub fn record_claim(
+        &mut self,
+        claim: SignedClaim,
+        public_key: &PublicKey,
+        now_secs: u64,
+    ) -> Result<Option<CompromiseEvidence>> {
+        claim.verify(public_key)?;
+        self.prune(now_secs);
+        Ok(self.insert_claim(claim, public_key, now_secs))
+    }
+
+    /// Record a peer's observation of a claim signed by `public_key`.
+    ///
+    /// The observer's key must be its genesis key or known to `keys`.
+    /// Checks the embedded claim for equivocation, then compares the
+    /// observation with earlier ones of other claims by other observers.
+    pub fn record_observation(
+        &mut self,
+        observation: Observation,
+        public_key: &PublicKey,
+        keys: &PeerKeyRegistry,
+        now_secs: u64,
+    ) -> Result<Option<CompromiseEvidence>> {
+        observation.verify(public_key, keys)?;
+        self.prune(now_secs);
+        if let Some(evidence) = self.insert_claim(observation.claim.clone(), public_key, now_secs) {
+            return Ok(Some(evidence));
+        }
+
+        let max = self.config.max_observations_per_node;
+        let record = self.record_mut(observation.claim.node_id, now_secs);
+        let conflict = record.observations.iter().map(|(earlier, _)| earlier).find(|earlier| {
+            earlier.observer != observation.observer
+                && earlier.claim != observation.claim
+                && !same_network(&earlier.source, &observation.source)
+                && earlier.received_at_ms.abs_diff(observation.received_at_ms) < MIN_RELOCATION_MS
+        });
+        if let Some(earlier) = conflict {
+            return Ok(Some(CompromiseEvidence::ImpossibleTiming {
+                public_key: *public_key,
+                first: Box::new(earlier.clone()),
+                second: Box::new(observation),
+            }));
+        }
+        record.observations.push_back((observation, now_secs));
+        while record.observations.len() > max {
+            record.observations.pop_front();
+        }
+        Ok(None)
+    }
+
+    /// Drop detail older than the window and age out summaries.
+    ///
+    /// Called by the record methods; call it directly when idle.
+    pub fn prune(&mut self, now_secs: u64) {
+        if now_secs.saturating_sub(self.generation_start) >= self.config.summary_secs {
+            std::mem::swap(&mut self.current, &mut self.previous);
+            self.current.clear();
+            if now_secs.saturating_sub(self.generation_start) >= 2 * self.config.summary_secs {
+                self.previous.clear();
+            }
+            // Suspicion decays as the summaries it came from age out.
+            self.suspicion.halve();
+            self.generation_start = now_secs;
+        }
+
+        let cutoff = now_secs.saturating_sub(self.config.window_secs);
+        let current = &mut self.current;
+        self.nodes.retain(|node_id, record| {
+            record.claims.retain(|_, (claim, seen)| {
+                let keep = *seen >= cutoff;
+                if !keep {
+                    summarise(current, node_id, claim);
+                }
+                keep
+            });
+            record.observations.retain(|(_, seen)| *seen >= cutoff);
+            !(record.claims.is_empty() && record.observations.is_empty())
+        });
+    }
+
+    /// Estimated number of times a node signed a different payload into a
+    /// slot that had already been summarised. Never underestimates.
+    pub fn suspicion(&self, node_id: &NodeId) -> u32 {
+        self.suspicion.estimate(node_id.as_bytes())
+    }
+
+    /// Forget everything about a node (e.g. after it is revoked).
+    pub fn forget(&mut self, node_id: &NodeId) {
+        self.nodes.remove(node_id);
+    }
+
+    /// Number of nodes tracked in detail.
+    pub fn tracked_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Approximate heap usage in bytes.
+    pub fn memory_usage(&self) -> usize {
+        let summaries = 2 * (self.current.slots.size_bytes() + self.current.claims.size_bytes());
+        let detail: usize = self
+            .nodes
+            .values()
+            .map(|r| {
+                r.claims.len() * std::mem::size_of::<((EpochId, u64), (SignedClaim, u64))>()
+                    + r.observations.len() * std::mem::size_of::<(Observation, u64)>()
+            })
+            .sum();
+        summaries + self.suspicion.size_bytes() + detail + self.nodes.len() * std::mem::size_of::<(NodeId, NodeRecord)>()
+    }
+
+    fn record_mut(&mut self, node_id: NodeId, now_secs: u64) -> &mut NodeRecord {
+        if !self.nodes.contains_key(&node_id) && self.nodes.len() >= self.config.max_nodes {
+            self.evict_oldest();
+        }
+        let record = self.nodes.entry(node_id).or_default();
+        record.last_seen = record.last_seen.max(now_secs);
+        record
+    }
+
+    fn evict_oldest(&mut self) {
+        let Some(oldest) = self.nodes.iter().min_by_key(|(_, r)| r.last_seen).map(|(id, _)| *id) else {
+            return;
+        };
+        if let Some(record) = self.nodes.remove(&oldest) {
+            for (claim, _) in record.claims.values() {
+                summarise(&mut self.current, &oldest, claim);
+            }
+        }
+    }
+
+    fn insert_claim(&mut self, claim: SignedClaim, public_key: &PublicKey, now_secs: u64) -> Option<CompromiseEvidence> {
+        let node_id = claim.node_id;
+        let slot = (claim.epoch, claim.sequence);
+        let max_claims = self.config.max_claims_per_node;
+        let record = self.record_mut(node_id, now_secs);
+
+        if let Some((earlier, _)) = record.claims.get(&slot) {
+            if earlier.payload_hash == claim.payload_hash {
+                return None;
+            }
+            return Some(CompromiseEvidence::Equivocation {
+                public_key: *public_key,
+                first: Box::new(earlier.clone()),
+                second: Box::new(claim),
+            });
+        }
+
+        // Detail is kept for the newest two epochs only.
+        let newest = record.claims.keys().next_back().map_or(claim.epoch, |(e, _)| (*e).max(claim.epoch));
+        let oldest_kept = newest.saturating_sub(1);
+        let mut folded = Vec::new();
+        while let Some((&(epoch, _), _)) = record.claims.first_key_value() {
+            if epoch >= oldest_kept && record.claims.len() < max_claims {
+                break;
+            }
+            folded.extend(record.claims.pop_first().map(|(_, (old, _))| old));
+        }
+        let keep = claim.epoch >= oldest_kept;
+        if keep {
+            record.claims.insert(slot, (claim.clone(), now_secs));
+        }
+
+        for old in &folded {
+            summarise(&mut self.current, &node_id, old);
+        }
+        self.check_summary(&claim);
+        if !keep {
+            summarise(&mut self.current, &node_id, &claim);
+        }
+        None
+    }
+
+    /// A claim whose slot was summarised with a different payload.
+    fn check_summary(&mut self, claim: &SignedClaim) {
+        let slot = slot_key(&claim.node_id, claim);
+        let full = claim_key(&claim.node_id, claim);
+        let slot_seen = self.current.slots.contains(&slot) || self.previous.slots.contains(&slot);
+        let claim_seen = self.current.claims.contains(&full) || self.previous.claims.contains(&full);
+        if slot_seen && !claim_seen {
+            self.suspicion.increment(claim.node_id.as_bytes());
+        }
+    }
+}
+
+impl Default for CompromiseDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn summarise(summary: &mut Summary, node_id: &NodeId, claim: &SignedClaim) {
+    summary.slots.insert(&slot_key(node_id, claim));
+    summary.claims.insert(&claim_key(node_id, claim));
+}
+
+fn slot_key(node_id: &NodeId, claim: &SignedClaim) -> Vec<u8> {
+    let mut key = Vec::with_capacity(48);
+    key.extend_from_slice(node_id.as_bytes());
+    key.extend_from_slice(&claim.epoch.to_be_bytes());
+    key.extend_from_slice(&claim.sequence.to_be_bytes());
+    key
+}
+
+fn claim_key(node_id: &NodeId, claim: &SignedClaim) -> Vec<u8> {
+    let mut key = slot_key(node_id, claim);
+    key.extend_from_slice(&claim.payload_hash);
+    key
+}
@@ -0,0 +1,86 @@
+//! Fixed-size summaries of claims that left the detailed window.
+//!
+//! Both structures hash with SHA-256 and derive their indices by double
+//! hashing, so memory is set once at construction and never grows.
+
+use sha2::{Digest, Sha256};
+
+/// Bloom filter over byte strings.
+pub(crate) struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// Filter with `num_bits` bits (rounded up to a multiple of 64) and
+    /// `hashes` probes per item.
+    pub(crate) fn new(num_bits: usize, hashes: u32) -> Self {
+        let words = num_bits.div_ceil(64).max(1);
+        Self { bits: vec![0; words], num_bits: words as u64 * 64, hashes: hashes.max(1) }
+    }
+
+    pub(crate) fn insert(&mut self, item: &[u8]) {
+        for index in indices(item, self.hashes, self.num_bits) {
+            self.bits[(index / 64) as usize] |= 1 << (index % 64);
+        }
+    }
+
+    /// False positives possible, false negatives not.
+    pub(crate) fn contains(&self, item: &[u8]) -> bool {
+        indices(item, self.hashes, self.num_bits).all(|index| self.bits[(index / 64) as usize] & (1 << (index % 64)) != 0)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+    }
+
+    pub(crate) fn size_bytes(&self) -> usize {
+        self.bits.len() * 8
+    }
+}
+
+/// Count-min sketch; estimates never undercount.
+pub(crate) struct CountMinSketch {
+    counters: Vec<u32>,
+    width: u64,
+    depth: u32,
+}
+
+impl CountMinSketch {
+    pub(crate) fn new(width: usize, depth: u32) -> Self {
+        let width = width.max(1);
+        let depth = depth.max(1);
+        Self { counters: vec![0; width * depth as usize], width: width as u64, depth }
+    }
+
+    pub(crate) fn increment(&mut self, item: &[u8]) {
+        for (row, index) in indices(item, self.depth, self.width).enumerate() {
+            let counter = &mut self.counters[row * self.width as usize + index as usize];
+            *counter = counter.saturating_add(1);
+        }
+    }
+
+    pub(crate) fn estimate(&self, item: &[u8]) -> u32 {
+        indices(item, self.depth, self.width)
+            .enumerate()
+            .map(|(row, index)| self.counters[row * self.width as usize + index as usize])
+            .min()
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn halve(&mut self) {
+        self.counters.iter_mut().for_each(|c| *c /= 2);
+    }
+
+    pub(crate) fn size_bytes(&self) -> usize {
+        self.counters.len() * 4
+    }
+}
+
+fn indices(item: &[u8], count: u32, modulus: u64) -> impl Iterator<Item = u64> {
+    let digest = Sha256::digest(item);
+    let h1 = u64::from_be_bytes(digest[..8].try_into().expect("8 bytes"));
+    let h2 = u64::from_be_bytes(digest[8..16].try_into().expect("8 bytes")) | 1;
+    (0..u64::from(count)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % modulus)
+}
@@ -0,0 +1,121 @@
+//! Domain-separated signing contexts.
+//!
+//! Every signed object signs
+//!
+//! ```text
+//! CBOR [context: text, context_version: uint, body]
+//! ```
+//!
+//! where `body` is the object's fields as a canonical CBOR array. The
+//! context string names the object type and the role of the signer, so a
+//! signature made for one purpose never verifies as another, even when
+//! the bodies happen to encode identically.
+//!
+//! Each context carries its own version, bumped only when that object's
+//! body layout changes; a protocol version bump leaves signatures over
+//! unchanged objects valid. Fixed vectors live in `test-vectors/signing`.
+
+use opennet_wire::cbor::CborEncoder;
+
+/// Body version of [`SigningContext::Announcement`].
+pub const ANNOUNCEMENT_VERSION: u64 = 1;
+/// Body version of [`SigningContext::RotationOldKey`].
+pub const ROTATION_OLD_KEY_VERSION: u64 = 1;
+/// Body version of [`SigningContext::RotationNewKey`].
+pub const ROTATION_NEW_KEY_VERSION: u64 = 1;
+/// Body version of [`SigningContext::Claim`].
+pub const CLAIM_VERSION: u64 = 1;
+/// Body version of [`SigningContext::Observation`].
+pub const OBSERVATION_VERSION: u64 = 1;
+/// Body version of [`SigningContext::Revocation`].
+pub const REVOCATION_VERSION: u64 = 1;
+/// Body version of [`SigningContext::RecoveryRequest`].
+pub const RECOVERY_REQUEST_VERSION: u64 = 1;
+/// Body version of [`SigningContext::RecoveryAttestation`].
+pub const RECOVERY_ATTESTATION_VERSION: u64 = 1;
+/// Body version of [`SigningContext::GuardianSet`].
+pub const GUARDIAN_SET_VERSION: u64 = 1;
+/// Body version of [`SigningContext::Handshake`].
+pub const HANDSHAKE_VERSION: u64 = 1;
+
+/// What a signature is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SigningContext {
+    /// Identity announcement, by the announced key.
+    Announcement,
+    /// Key rotation, by the outgoing key.
+    RotationOldKey,
+    /// Key rotation, by the incoming key.
+    RotationNewKey,
+    /// Sequenced claim checked by the compromise detector.
+    Claim,
+    /// Peer's report of receiving a claim.
+    Observation,
+    /// Revocation, by a quorum member.
+    Revocation,
+    /// Recovery request, by the recovered key.
+    RecoveryRequest,
+    /// Guardian attestation of a recovery request.
+    RecoveryAttestation,
+    /// Guardian set registration, by the owner.
+    GuardianSet,
+    /// Transport handshake transcript.
+    Handshake,
+}
+
+impl SigningContext {
+    /// All contexts, for exhaustive checks.
+    pub const ALL: [SigningContext; 10] = [
+        Self::Announcement,
+        Self::RotationOldKey,
+        Self::RotationNewKey,
+        Self::Claim,
+        Self::Observation,
+        Self::Revocation,
+        Self::RecoveryRequest,
+        Self::RecoveryAttestation,
+        Self::GuardianSet,
+        Self::Handshake,
+    ];
+
+    /// Context string. Never reuse or change one.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Announcement => "opennet/identity/announcement",
+            Self::RotationOldKey => "opennet/identity/rotation/old-key",
+            Self::RotationNewKey => "opennet/identity/rotation/new-key",
+            Self::Claim => "opennet/identity/claim",
+            Self::Observation => "opennet/identity/observation",
+            Self::Revocation => "opennet/revocation/revocation",
+            Self::RecoveryRequest => "opennet/revocation/recovery-request",
+            Self::RecoveryAttestation => "opennet/revocation/recovery-attestation",
+            Self::GuardianSet => "opennet/revocation/guardian-set",
+            Self::Handshake => "opennet/transport/handshake",
+        }
+    }
+
+    /// Version of the body signed under this context.
+    pub fn version(self) -> u64 {
+        match self {
+            Self::Announcement => ANNOUNCEMENT_VERSION,
+            Self::RotationOldKey => ROTATION_OLD_KEY_VERSION,
+            Self::RotationNewKey => ROTATION_NEW_KEY_VERSION,
+            Self::Claim => CLAIM_VERSION,
+            Self::Observation => OBSERVATION_VERSION,
+            Self::Revocation => REVOCATION_VERSION,
+            Self::RecoveryRequest => RECOVERY_REQUEST_VERSION,
+            Self::RecoveryAttestation => RECOVERY_ATTESTATION_VERSION,
+            Self::GuardianSet => GUARDIAN_SET_VERSION,
+            Self::Handshake => HANDSHAKE_VERSION,
+        }
+    }
+
+    /// Bytes to sign: `body` writes exactly one CBOR item, normally an
+    /// array of the object's fields.
+    pub fn signing_bytes(self, body: impl FnOnce(&mut CborEncoder)) -> Vec<u8> {
+        let mut enc = CborEncoder::new();
+        enc.encode_array_header(3).encode_text(self.label()).encode_uint(self.version());
+        body(&mut enc);
+        enc.into_bytes()
+    }
+}
@@ -0,0 +1,163 @@
+//! Verified-identity directory.
+//!
+//! Maps each NodeId to the epoch and public key it most recently proved
+//! it holds. Entries come only from verified announcements: an epoch-1
+//! key is bound to the NodeId by derivation, a later key must match the
+//! node's rotation history in the [`PeerKeyRegistry`], and revoked epochs
+//! are refused. Entries not re-announced within the maximum age are
+//! expired.
+
+use opennet_core::{EpochId, NodeId};
+use opennet_core::types::PublicKey;
+use std::collections::BTreeMap;
+use crate::announcement::IdentityAnnouncement;
+use crate::rotation::PeerKeyRegistry;
+use crate::error::{IdentityError, Result};
+
+/// Default time an entry stays without a fresh announcement.
+pub const DEFAULT_MAX_AGE_SECS: u64 = 24 * 3600;
+
+/// A node's verified current identity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    /// Current epoch.
+    pub epoch: EpochId,
+    /// Key for `epoch`.
+    pub public_key: PublicKey,
+    /// Start of `epoch`.
+    pub epoch_start: u64,
+    /// Timestamp of the newest announcement.
+    pub announced_at: u64,
+    /// Local time the entry was last confirmed.
+    pub seen_at: u64,
+}
+
+/// Effect of applying an announcement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectoryUpdate {
+    /// First announcement from this node; gossip it.
+    New,
+    /// The node moved to a newer epoch; gossip it.
+    EpochAdvanced {
+        /// Previously known epoch.
+        from: EpochId,
+    },
+    /// Newer announcement for the known epoch; no need to gossip.
+    Refreshed,
+    /// Older than, or identical to, what is known; drop.
+    Stale,
+}
+
+/// Directory of verified identities.
+pub struct IdentityDirectory {
+    entries: BTreeMap<NodeId, DirectoryEntry>,
+    max_age_secs: u64,
+}
+
+impl IdentityDirectory {
+    /// Create an empty directory expiring entries after `max_age_secs`.
+    pub fn new(max_age_secs: u64) -> Self {
+        Self { entries: BTreeMap::new(), max_age_secs }
+    }
+
+    /// Verify an announcement and record it if it is news.
+    pub fn apply(
+        &mut self,
+        announcement: &IdentityAnnouncement,
+        keys: &PeerKeyRegistry,
+        now_secs: u64,
+    ) -> Result<DirectoryUpdate> {
+        announcement.verify()?;
+        let node_id = announcement.node_id;
+        let epoch = announcement.epoch.id;
+        if keys.is_revoked(&node_id, epoch) {
+            return Err(IdentityError::EpochChainBroken(format!("epoch {} is revoked", epoch)));
+        }
+        if epoch > 1 && keys.key_at(&node_id, epoch) != Some(announcement.public_key) {
+            return Err(IdentityError::EpochChainBroken(format!(
+                "key for epoch {} is not in the node's rotation history",
+                epoch
+            )));
+        }
+        if keys.current(&node_id).is_some_and(|(current, _)| current > epoch) {
+            return Ok(DirectoryUpdate::Stale);
+        }
+
+        let entry = DirectoryEntry {
+            epoch,
+            public_key: announcement.public_key,
+            epoch_start: announcement.epoch.start_time,
+            announced_at: announcement.timestamp.as_secs(),
+            seen_at: now_secs,
+        };
+        let update = match self.entries.get(&node_id) {
+            None => DirectoryUpdate::New,
+            Some(known) if epoch < known.epoch => DirectoryUpdate::Stale,
+            Some(known) if epoch == known.epoch && entry.announced_at <= known.announced_at => {
+                DirectoryUpdate::Stale
+            }
+            Some(known) if epoch == known.epoch => DirectoryUpdate::Refreshed,
+            Some(known) => DirectoryUpdate::EpochAdvanced { from: known.epoch },
+        };
+        if update != DirectoryUpdate::Stale {
+            self.entries.insert(node_id, entry);
+        }
+        Ok(update)
+    }
+
+    /// Record a rotation accepted through the rotation registry.
+    pub fn on_rotation(&mut self, node_id: NodeId, epoch: EpochId, public_key: PublicKey, now_secs: u64) {
+        let Some(entry) = self.entries.get_mut(&node_id) else {
+            return;
+        };
+        if epoch > entry.epoch {
+            *entry = DirectoryEntry { epoch, public_key, epoch_start: now_secs, announced_at: now_secs, seen_at: now_secs };
+        }
+    }
+
+    /// Verified identity of a node.
+    pub fn lookup(&self, node_id: &NodeId) -> Option<&DirectoryEntry> {
+        self.entries.get(node_id)
+    }
+
+    /// Current epoch and key of a node.
+    pub fn current_key(&self, node_id: &NodeId) -> Option<(EpochId, PublicKey)> {
+        self.entries.get(node_id).map(|e| (e.epoch, e.public_key))
+    }
+
+    /// Drop entries not confirmed within the maximum age. Returns them.
+    pub fn expire(&mut self, now_secs: u64) -> Vec<NodeId> {
+        let max_age = self.max_age_secs;
+        let expired: Vec<NodeId> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| now_secs.saturating_sub(e.seen_at) > max_age)
+            .map(|(id, _)| *id)
+            .collect();
+        for node_id in &expired {
+            self.entries.remove(node_id);
+        }
+        expired
+    }
+
+    /// Forget a node (e.g. after revocation).
+    pub fn remove(&mut self, node_id: &NodeId) {
+        self.entries.remove(node_id);
+    }
+
+    /// Number of known nodes.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for IdentityDirectory {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_AGE_SECS)
+    }
+}
@@ -32,6 +32,18 @@ pub enum IdentityError {
     #[error("storage error: {0}")]
     StorageError(String),
 
+    /// Signing backend failure.
+    #[error("signer error: {0}")]
+    SignerError(String),
+
+    /// Social recovery failed.
+    #[error("recovery failed: {0}")]
+    RecoveryFailed(String),
+
+    /// Backup share split or recovery failed.
+    #[error("backup error: {0}")]
+    BackupError(String),
+
     /// Cryptographic error.
     #[error("crypto error: {0}")]
     CryptoError(String),
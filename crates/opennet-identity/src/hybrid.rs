@@ -0,0 +1,126 @@
+//! Hybrid Ed25519 + ML-DSA-65 keys.
+//!
+//! Both halves sign `HYBRID_PREFIX || message`, so the Ed25519 half of a
+//! hybrid signature is never a valid plain Ed25519 signature over the
+//! same message and cannot be stripped off and replayed alone.
+
+use ml_dsa::{EncodedSignature, EncodedVerifyingKey, KeyGen, MlDsa65, B32};
+use opennet_core::types::{AnyPublicKey, AnySignature, PublicKey, Signature, SignatureAlgorithm};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+use crate::keypair::{verify_signature, KeyPair};
+use crate::signer::NodeSigner;
+use crate::error::{IdentityError, Result};
+
+const HYBRID_PREFIX: &[u8] = b"opennet/hybrid";
+
+/// Ed25519 key and ML-DSA-65 key used together.
+pub struct HybridKeyPair {
+    classic: KeyPair,
+    pq: ml_dsa::KeyPair<MlDsa65>,
+}
+
+impl HybridKeyPair {
+    /// Derive both halves from one seed.
+    pub fn generate(seed: &[u8; 32]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(b"opennet/ml-dsa-seed");
+        hasher.update(seed);
+        let xi = Zeroizing::new(<[u8; 32]>::from(hasher.finalize()));
+        Self { classic: KeyPair::generate(seed), pq: MlDsa65::key_gen_internal(&B32::from(*xi)) }
+    }
+
+    /// Fresh keypair from the OS random number generator.
+    pub fn random() -> Self {
+        let mut seed = Zeroizing::new([0u8; 32]);
+        rand::rngs::OsRng.fill_bytes(seed.as_mut());
+        Self::generate(&seed)
+    }
+
+    /// The Ed25519 half.
+    pub fn classic(&self) -> &KeyPair {
+        &self.classic
+    }
+
+    /// Combined public key.
+    pub fn public_key_any(&self) -> AnyPublicKey {
+        let mut bytes = self.classic.public_key().as_bytes().to_vec();
+        bytes.extend_from_slice(self.pq.verifying_key().encode().as_slice());
+        AnyPublicKey::new(SignatureAlgorithm::HybridEd25519MlDsa65, bytes).expect("hybrid key length")
+    }
+
+    /// Sign with both halves.
+    pub fn sign_any(&self, message: &[u8]) -> Result<AnySignature> {
+        let prefixed = prefixed(message);
+        let pq = self
+            .pq
+            .signing_key()
+            .sign_deterministic(&prefixed, &[])
+            .map_err(|_| IdentityError::CryptoError("ML-DSA signing failed".into()))?;
+        let mut bytes = self.classic.sign(&prefixed).as_bytes().to_vec();
+        bytes.extend_from_slice(pq.encode().as_slice());
+        AnySignature::new(SignatureAlgorithm::HybridEd25519MlDsa65, bytes)
+            .map_err(|e| IdentityError::CryptoError(e.to_string()))
+    }
+}
+
+impl NodeSigner for HybridKeyPair {
+    /// Ed25519 half, for objects that only carry a classic key.
+    fn public_key(&self) -> PublicKey {
+        self.classic.public_key()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.classic.sign(message))
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::HybridEd25519MlDsa65
+    }
+
+    fn public_key_any(&self) -> AnyPublicKey {
+        HybridKeyPair::public_key_any(self)
+    }
+
+    fn sign_any(&self, message: &[u8]) -> Result<AnySignature> {
+        HybridKeyPair::sign_any(self, message)
+    }
+}
+
+impl std::fmt::Debug for HybridKeyPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HybridKeyPair")
+            .field("classic", &self.classic)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Verify both halves of a hybrid signature.
+pub(crate) fn verify(public_key: &AnyPublicKey, message: &[u8], signature: &AnySignature) -> Result<()> {
+    let (Some(pq_key), Some(pq_sig), Ok(key), Ok(sig)) =
+        (public_key.ml_dsa(), signature.ml_dsa(), public_key.ed25519(), signature.ed25519())
+    else {
+        return Err(IdentityError::SignatureVerificationFailed);
+    };
+    verify_signature(&key, &prefixed(message), &sig)?;
+
+    let key = EncodedVerifyingKey::<MlDsa65>::try_from(pq_key)
+        .map_err(|_| IdentityError::InvalidKey("ML-DSA key length".into()))?;
+    let key = ml_dsa::VerifyingKey::<MlDsa65>::decode(&key);
+    let sig = EncodedSignature::<MlDsa65>::try_from(pq_sig)
+        .ok()
+        .and_then(|s| ml_dsa::Signature::<MlDsa65>::decode(&s))
+        .ok_or(IdentityError::SignatureVerificationFailed)?;
+    if !key.verify_with_context(&prefixed, &[], &sig) {
+        return Err(IdentityError::SignatureVerificationFailed);
+    }
+    Ok(())
+}
+
+fn prefixed(message: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HYBRID_PREFIX.len() + message.len());
+    bytes.extend_from_slice(HYBRID_PREFIX);
+    bytes.extend_from_slice(message);
+    bytes
+}
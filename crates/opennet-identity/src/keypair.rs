@@ -4,9 +4,14 @@ use ed25519_dalek::{SigningKey, VerifyingKey, Signer, Verifier};
 use rand_chacha::ChaCha20Rng;
 use rand::SeedableRng;
 use opennet_core::types::{PublicKey, Signature};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 use crate::error::{IdentityError, Result};
 
 /// Ed25519 keypair wrapper.
+///
+/// The signing key is wiped when the keypair is dropped, `Debug` shows only
+/// the public key, and equality is decided in constant time.
 pub struct KeyPair {
     signing_key: SigningKey,
 }
@@ -14,11 +19,17 @@ pub struct KeyPair {
 impl KeyPair {
     /// Generate a new random keypair with given seed.
     pub fn generate(seed: &[u8; 32]) -> Self {
+        let seed = Zeroizing::new(*seed);
         let mut rng = ChaCha20Rng::from_seed(*seed);
         let signing_key = SigningKey::generate(&mut rng);
         Self { signing_key }
     }
 
+    /// Generate a fresh keypair from the OS random number generator.
+    pub fn random() -> Self {
+        Self { signing_key: SigningKey::generate(&mut rand::rngs::OsRng) }
+    }
+
     /// Create from existing secret key bytes.
     pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self> {
         let signing_key = SigningKey::from_bytes(bytes);
@@ -31,9 +42,9 @@ impl KeyPair {
         PublicKey::from_bytes(vk.to_bytes())
     }
 
-    /// Get the secret key bytes (be careful!).
-    pub fn secret_bytes(&self) -> [u8; 32] {
-        self.signing_key.to_bytes()
+    /// Expose the secret key bytes in a guard that wipes them on drop.
+    pub fn expose_secret(&self) -> SecretBytes {
+        SecretBytes(Zeroizing::new(self.signing_key.to_bytes()))
     }
 
     /// Sign a message.
@@ -60,8 +71,61 @@ pub fn verify_signature(public_key: &PublicKey, message: &[u8], signature: &Sign
         .map_err(|_| IdentityError::SignatureVerificationFailed)
 }
 
-impl Drop for KeyPair {
-    fn drop(&mut self) {
-        // Zeroize would be applied here in production
+// `SigningKey` zeroizes its secret scalar on drop.
+impl ZeroizeOnDrop for KeyPair {}
+
+impl PartialEq for KeyPair {
+    fn eq(&self, other: &Self) -> bool {
+        self.expose_secret().ct_eq(&other.expose_secret()).into()
+    }
+}
+
+impl Eq for KeyPair {}
+
+impl std::fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("public_key", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Secret key bytes, wiped on drop.
+///
+/// Not `Clone`; `Debug` is redacted and comparisons are constant-time.
+pub struct SecretBytes(Zeroizing<[u8; 32]>);
+
+impl SecretBytes {
+    /// Borrow the raw bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl ConstantTimeEq for SecretBytes {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        self.0.ct_eq(&*other.0)
+    }
+}
+
+impl PartialEq for SecretBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SecretBytes {}
+
+impl Zeroize for SecretBytes {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretBytes {}
+
+impl std::fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretBytes(..)")
     }
 }
@@ -0,0 +1,185 @@
+//! Encrypted at-rest key format.
+//!
+//! A keystore file is a fixed header followed by the XChaCha20-Poly1305
+//! encryption of the 32-byte secret key. The encryption key is derived
+//! from the unlock secret with Argon2id; the whole header is bound as
+//! associated data, so tampering with the KDF parameters fails decryption.
+//!
+//! ```text
+//! magic "ONKS" | version u8 | m_cost u32 | t_cost u32 | p_cost u32 | salt[16] | nonce[24] | ciphertext[48]
+//! ```
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use std::path::PathBuf;
+use zeroize::Zeroizing;
+use crate::error::{IdentityError, Result};
+
+/// File magic.
+const MAGIC: &[u8; 4] = b"ONKS";
+
+/// Current format version.
+pub const KEYSTORE_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const SECRET_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+
+/// Header length in bytes.
+pub const HEADER_LEN: usize = 4 + 1 + 12 + SALT_LEN + NONCE_LEN;
+
+/// Total keystore file length in bytes.
+pub const KEYSTORE_LEN: usize = HEADER_LEN + SECRET_LEN + TAG_LEN;
+
+/// Largest memory cost accepted from a keystore header (2 GiB), so a
+/// crafted file cannot make unlocking exhaust memory or time.
+const MAX_M_COST_KIB: u32 = 2 * 1024 * 1024;
+
+/// Largest iteration count accepted from a keystore header.
+const MAX_T_COST: u32 = 64;
+
+/// Argon2id cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB.
+    pub m_cost_kib: u32,
+    /// Iterations.
+    pub t_cost: u32,
+    /// Parallelism.
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // OWASP minimum recommendation for Argon2id.
+        Self { m_cost_kib: 19 * 1024, t_cost: 2, p_cost: 1 }
+    }
+}
+
+/// Where the keystore passphrase comes from.
+#[derive(Clone)]
+pub enum KeyUnlock {
+    /// Passphrase supplied directly.
+    Passphrase(Zeroizing<String>),
+    /// Passphrase read from an environment variable.
+    Env(String),
+    /// Secret read from a key file.
+    KeyFile(PathBuf),
+}
+
+impl KeyUnlock {
+    /// Unlock with a passphrase.
+    pub fn passphrase(passphrase: impl Into<String>) -> Self {
+        Self::Passphrase(Zeroizing::new(passphrase.into()))
+    }
+
+    /// Resolve the unlock secret.
+    pub fn secret(&self) -> Result<Zeroizing<Vec<u8>>> {
+        let secret = match self {
+            Self::Passphrase(passphrase) => Zeroizing::new(passphrase.as_bytes().to_vec()),
+            Self::Env(var) => {
+                let value = std::env::var(var)
+                    .map_err(|_| IdentityError::StorageError(format!("passphrase variable {} not set", var)))?;
+                Zeroizing::new(value.into_bytes())
+            }
+            Self::KeyFile(path) => Zeroizing::new(
+                std::fs::read(path)
+                    .map_err(|e| IdentityError::StorageError(format!("key file {}: {}", path.display(), e)))?,
+            ),
+        };
+        if secret.is_empty() {
+            return Err(IdentityError::StorageError("empty unlock secret".into()));
+        }
+        Ok(secret)
+    }
+}
+
+impl std::fmt::Debug for KeyUnlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Passphrase(_) => f.write_str("Passphrase(..)"),
+            Self::Env(var) => f.debug_tuple("Env").field(var).finish(),
+            Self::KeyFile(path) => f.debug_tuple("KeyFile").field(path).finish(),
+        }
+    }
+}
+
+/// Encrypt a secret key into keystore bytes.
+pub fn seal(secret_key: &[u8; 32], unlock_secret: &[u8], params: KdfParams) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+    let mut out = Vec::with_capacity(KEYSTORE_LEN);
+    out.extend_from_slice(MAGIC);
+    out.push(KEYSTORE_VERSION);
+    out.extend_from_slice(&params.m_cost_kib.to_be_bytes());
+    out.extend_from_slice(&params.t_cost.to_be_bytes());
+    out.extend_from_slice(&params.p_cost.to_be_bytes());
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+
+    let cipher = cipher(unlock_secret, &salt, params)?;
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: secret_key, aad: &out })
+        .map_err(|_| IdentityError::CryptoError("keystore encryption failed".into()))?;
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt keystore bytes back into the secret key.
+pub fn open(bytes: &[u8], unlock_secret: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+    if bytes.len() != KEYSTORE_LEN {
+        return Err(IdentityError::StorageError(format!(
+            "keystore is {} bytes, expected {}",
+            bytes.len(),
+            KEYSTORE_LEN
+        )));
+    }
+    if &bytes[..4] != MAGIC {
+        return Err(IdentityError::StorageError("not a keystore file".into()));
+    }
+    if bytes[4] != KEYSTORE_VERSION {
+        return Err(IdentityError::StorageError(format!("unsupported keystore version {}", bytes[4])));
+    }
+    let params = KdfParams {
+        m_cost_kib: read_u32(&bytes[5..9]),
+        t_cost: read_u32(&bytes[9..13]),
+        p_cost: read_u32(&bytes[13..17]),
+    };
+    if params.m_cost_kib > MAX_M_COST_KIB || params.t_cost > MAX_T_COST {
+        return Err(IdentityError::StorageError("keystore KDF cost too high".into()));
+    }
+    let salt = &bytes[17..17 + SALT_LEN];
+    let nonce = &bytes[17 + SALT_LEN..HEADER_LEN];
+    let (header, ciphertext) = bytes.split_at(HEADER_LEN);
+
+    let plaintext = Zeroizing::new(
+        cipher(unlock_secret, salt, params)?
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: header })
+            .map_err(|_| IdentityError::CryptoError("wrong passphrase or corrupted keystore".into()))?,
+    );
+    let mut secret = Zeroizing::new([0u8; 32]);
+    secret.copy_from_slice(&plaintext);
+    Ok(secret)
+}
+
+fn cipher(unlock_secret: &[u8], salt: &[u8], params: KdfParams) -> Result<XChaCha20Poly1305> {
+    let argon_params = Params::new(params.m_cost_kib, params.t_cost, params.p_cost, Some(32))
+        .map_err(|e| IdentityError::StorageError(format!("invalid KDF parameters: {}", e)))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+        .hash_password_into(unlock_secret, salt, key.as_mut())
+        .map_err(|e| IdentityError::CryptoError(e.to_string()))?;
+    Ok(XChaCha20Poly1305::new(key.as_ref().into()))
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(bytes);
+    u32::from_be_bytes(buf)
+}
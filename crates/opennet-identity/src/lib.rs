@@ -6,7 +6,14 @@
 #![warn(missing_docs)]
 
 pub mod rotation;
+pub mod recovery;
 pub mod error;
+pub mod keystore;
+pub mod signer;
+pub mod backup;
+pub mod directory;
+pub mod context;
+pub mod algorithm;
 
 mod keypair;
 mod node_identity;
@@ -14,11 +21,17 @@ mod announcement;
 mod compromise;
 mod storage;
 mod watcher;
+#[cfg(feature = "pq-hybrid")]
+mod hybrid;
 
-pub use keypair::{KeyPair, verify_signature};
+pub use keypair::{KeyPair, SecretBytes, verify_signature};
 pub use node_identity::NodeIdentity;
 pub use announcement::IdentityAnnouncement;
-pub use compromise::{CompromiseDetector, CompromiseEvidence};
+pub use directory::{IdentityDirectory, DirectoryEntry, DirectoryUpdate};
+pub use compromise::{CompromiseDetector, CompromiseEvidence, DetectorConfig, Observation, SignedClaim, MIN_RELOCATION_MS};
 pub use storage::{SecureStorage, FileStorage};
+pub use keystore::{KeyUnlock, KdfParams};
+pub use signer::NodeSigner;
+pub use context::SigningContext;
 pub use watcher::{IdentityWatcher, IdentityEvent};
 pub use error::{IdentityError, Result};
@@ -1,27 +1,30 @@
 //! Complete node identity management.
 
 use opennet_core::{NodeId, Epoch, EpochId};
-use opennet_core::types::PublicKey;
-use crate::keypair::KeyPair;
+use opennet_core::types::{AnyPublicKey, PublicKey, Signature, Timestamp};
+use crate::recovery::ValidatedRecovery;
+use crate::rotation::{RotationLog, RotationRequest};
+use crate::signer::NodeSigner;
 use crate::error::{IdentityError, Result};
 
 /// Complete node identity.
 pub struct NodeIdentity {
     /// Immutable NodeId.
     node_id: NodeId,
-    /// Current keypair.
-    keypair: KeyPair,
+    /// Signer for the current key.
+    signer: Box<dyn NodeSigner>,
     /// Current epoch.
     epoch: Epoch,
-    /// Epoch history (for verification).
-    epoch_history: Vec<EpochId>,
+    /// Signed rotation history back to epoch 1.
+    log: RotationLog,
 }
 
 impl NodeIdentity {
-    /// Create a new identity from a keypair.
-    pub fn new(keypair: KeyPair, start_time: u64) -> Self {
-        let public_key = keypair.public_key();
-        let node_id = NodeId::from_public_key(public_key.as_bytes());
+    /// Create a new identity from a signer (e.g. a `KeyPair`). The NodeId
+    /// derives from the signer's full key, hybrid or not.
+    pub fn new<S: NodeSigner + 'static>(signer: S, start_time: u64) -> Self {
+        let log = RotationLog::new(signer.public_key(), start_time).with_genesis_key(signer.public_key_any());
+        let node_id = *log.node_id();
         
         let mut key_hash = [0u8; 32];
         key_hash.copy_from_slice(node_id.as_bytes());
@@ -30,12 +33,47 @@ impl NodeIdentity {
         
         Self {
             node_id,
-            keypair,
+            signer: Box::new(signer),
             epoch,
-            epoch_history: vec![1],
+            log,
         }
     }
 
+    /// Restore an identity from its own rotation log and the signer for
+    /// the log's head key.
+    pub fn from_log<S: NodeSigner + 'static>(signer: S, log: RotationLog) -> Result<Self> {
+        let head_key = log.verify_own()?;
+        if signer.public_key() != head_key {
+            return Err(IdentityError::InvalidKey("signer does not hold the log's head key".into()));
+        }
+        let node_id = *log.node_id();
+        let key_hash = if log.head_epoch() == 1 { *node_id.as_bytes() } else { *head_key.as_bytes() };
+        let epoch = Epoch::new(log.head_epoch(), log.head_time(), key_hash);
+        Ok(Self {
+            node_id,
+            signer: Box::new(signer),
+            epoch,
+            log,
+        })
+    }
+
+    /// Resume an identity under a new key after social recovery.
+    ///
+    /// `signer` must hold the key `recovery` issued; the rotation log
+    /// restarts at the issued epoch.
+    pub fn recover<S: NodeSigner + 'static>(signer: S, recovery: &ValidatedRecovery, start_time: u64) -> Result<Self> {
+        let public_key = signer.public_key();
+        if &public_key != recovery.public_key() {
+            return Err(IdentityError::InvalidKey("signer does not hold the recovered key".into()));
+        }
+        Ok(Self {
+            node_id: *recovery.node_id(),
+            signer: Box::new(signer),
+            epoch: Epoch::new(recovery.epoch(), start_time, *public_key.as_bytes()),
+            log: RotationLog::recovered(recovery, start_time),
+        })
+    }
+
     /// Get the NodeId.
     pub fn node_id(&self) -> &NodeId {
         &self.node_id
@@ -43,7 +81,17 @@ impl NodeIdentity {
 
     /// Get the current public key.
     pub fn public_key(&self) -> PublicKey {
-        self.keypair.public_key()
+        self.signer.public_key()
+    }
+
+    /// Get the current public key with its algorithm.
+    pub fn public_key_any(&self) -> AnyPublicKey {
+        self.signer.public_key_any()
+    }
+
+    /// Signer for the current key.
+    pub fn signer(&self) -> &dyn NodeSigner {
+        self.signer.as_ref()
     }
 
     /// Get the current epoch.
@@ -57,14 +105,20 @@ impl NodeIdentity {
     }
 
     /// Sign a message.
-    pub fn sign(&self, message: &[u8]) -> opennet_core::types::Signature {
-        self.keypair.sign(message)
+    pub fn sign(&self, message: &[u8]) -> Result<Signature> {
+        self.signer.sign(message)
     }
 
-    /// Rotate to a new keypair.
-    pub fn rotate(&mut self, new_keypair: KeyPair, rotation_time: u64) -> Result<()> {
+    /// Rotate to a new signer.
+    ///
+    /// Returns the rotation request, signed by both the old and new keys,
+    /// to announce to peers.
+    pub fn rotate<S: NodeSigner + 'static>(&mut self, new_signer: S, rotation_time: u64) -> Result<RotationRequest> {
+        let new_public_key = new_signer.public_key();
+        if new_public_key == self.public_key() {
+            return Err(IdentityError::RotationFailed("new key equals current key".into()));
+        }
         let new_epoch_id = self.epoch.id + 1;
-        let new_public_key = new_keypair.public_key();
         
         let mut key_hash = [0u8; 32];
         key_hash.copy_from_slice(new_public_key.as_bytes());
@@ -74,16 +128,29 @@ impl NodeIdentity {
         // Validate transition
         Epoch::validate_transition(&self.epoch, &new_epoch)
             .map_err(|e| IdentityError::RotationFailed(e.to_string()))?;
-        
-        self.keypair = new_keypair;
+
+        let request = RotationRequest::create(
+            self.node_id,
+            self.epoch.id,
+            Timestamp::new(rotation_time),
+            self.signer.as_ref(),
+            &new_signer,
+        )?;
+
+        self.log.append(request.clone())?;
+        self.signer = Box::new(new_signer);
         self.epoch = new_epoch;
-        self.epoch_history.push(new_epoch_id);
-        
-        Ok(())
+
+        Ok(request)
     }
 
     /// Check if epoch is in history.
     pub fn has_epoch(&self, epoch_id: EpochId) -> bool {
-        self.epoch_history.contains(&epoch_id)
+        self.log.contains(epoch_id)
+    }
+
+    /// Signed rotation history, for persisting or handing to peers.
+    pub fn rotation_log(&self) -> &RotationLog {
+        &self.log
     }
 }
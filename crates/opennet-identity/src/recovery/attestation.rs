@@ -0,0 +1,31 @@
+//! Guardian attestations to a recovery request.
+
+use opennet_core::NodeId;
+use opennet_core::types::{PublicKey, Signature};
+use crate::error::Result;
+use crate::keypair::verify_signature;
+use crate::signer::NodeSigner;
+use super::request::RecoveryRequest;
+
+/// A guardian's signature vouching for a recovery request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerAttestation {
+    /// Guardian vouching for the request.
+    pub attester: NodeId,
+    /// Guardian's signature over the request's attestation bytes.
+    pub signature: Signature,
+}
+
+impl PeerAttestation {
+    /// Attest to `request` as guardian `attester`.
+    pub fn sign(request: &RecoveryRequest, attester: NodeId, signer: &dyn NodeSigner) -> Result<Self> {
+        let signature = signer.sign(&request.attestation_bytes())?;
+        Ok(Self { attester, signature })
+    }
+
+    /// Check the attestation against the guardian's current key.
+    pub fn verify(&self, request: &RecoveryRequest, guardian_key: &PublicKey) -> Result<()> {
+        verify_signature(guardian_key, &request.attestation_bytes(), &self.signature)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+//! Recovery certificates and their validation.
+
+use opennet_core::NodeId;
+use opennet_core::types::{PublicKey, Signature};
+use opennet_wire::cbor::{CborDecoder, CborEncoder};
+use std::collections::BTreeSet;
+use crate::error::{IdentityError, Result};
+use crate::rotation::PeerKeyRegistry;
+use super::attestation::PeerAttestation;
+use super::guardians::GuardianSet;
+use super::request::RecoveryRequest;
+use super::{fixed, malformed};
+
+/// A recovery request with the guardian attestations collected for it.
+///
+/// Carries no weight until [`validate`](Self::validate) has checked it
+/// against the node's guardian set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveryCertificate {
+    /// The request the guardians vouched for.
+    pub request: RecoveryRequest,
+    /// Attestations from the node's guardians.
+    pub attestations: Vec<PeerAttestation>,
+}
+
+impl RecoveryCertificate {
+    /// Node being recovered.
+    pub fn node_id(&self) -> &NodeId {
+        &self.request.node_id
+    }
+
+    /// Epoch issued to the new key.
+    pub fn new_epoch(&self) -> u64 {
+        self.request.new_epoch()
+    }
+
+    /// Check the certificate against the node's verified guardian set.
+    ///
+    /// Each attestation must verify under the guardian's current,
+    /// unrevoked key in `keys`. Invalid or duplicate attestations and
+    /// attestations from non-guardians or unknown nodes are dropped; the
+    /// certificate is valid if the rest meet the threshold, and keeps
+    /// only those.
+    pub fn validate(mut self, guardians: &GuardianSet, keys: &PeerKeyRegistry) -> Result<ValidatedRecovery> {
+        if guardians.node_id != self.request.node_id {
+            return Err(IdentityError::RecoveryFailed("guardian set is for another node".into()));
+        }
+        if guardians.epoch > self.request.revoked_epoch {
+            return Err(IdentityError::RecoveryFailed("guardian set registered after revoked epoch".into()));
+        }
+        self.request.verify()?;
+
+        let mut seen = BTreeSet::new();
+        self.attestations.retain(|a| {
+            guardians.guardians.contains(&a.attester)
+                && keys.current(&a.attester).is_some_and(|(epoch, key)| {
+                    !keys.is_revoked(&a.attester, epoch) && a.verify(&self.request, &key).is_ok()
+                })
+                && seen.insert(a.attester)
+        });
+        if self.attestations.len() < guardians.threshold as usize {
+            return Err(IdentityError::RecoveryFailed(format!(
+                "{} valid attestations, {} needed",
+                self.attestations.len(),
+                guardians.threshold
+            )));
+        }
+        Ok(ValidatedRecovery { certificate: self })
+    }
+
+    /// Canonical CBOR for storage and gossip:
+    /// `[request, [[attester, signature]...]]`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = CborEncoder::new();
+        enc.encode_array_header(2);
+        self.request.encode_into(&mut enc);
+        enc.encode_array_header(self.attestations.len());
+        for a in &self.attestations {
+            enc.encode_array_header(2)
+                .encode_bytes(a.attester.as_bytes())
+                .encode_bytes(a.signature.as_bytes());
+        }
+        enc.into_bytes()
+    }
+
+    /// Decode a certificate. Does not validate it.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut dec = CborDecoder::new(bytes);
+        if dec.decode_array_header().map_err(malformed)? != 2 {
+            return Err(malformed("expected 2-element recovery certificate"));
+        }
+        let request = RecoveryRequest::decode_from(&mut dec)?;
+        let count = dec.decode_array_header().map_err(malformed)?;
+        let mut attestations = Vec::with_capacity(count.min(64));
+        for _ in 0..count {
+            if dec.decode_array_header().map_err(malformed)? != 2 {
+                return Err(malformed("expected 2-element attestation"));
+            }
+            let attester = NodeId::from_bytes(fixed(&mut dec)?);
+            let signature = Signature::from_bytes(fixed(&mut dec)?);
+            attestations.push(PeerAttestation { attester, signature });
+        }
+        if !dec.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(Self { request, attestations })
+    }
+}
+
+/// A recovery certificate that passed [`RecoveryCertificate::validate`].
+/// Only validation creates one.
+#[derive(Debug, Clone)]
+pub struct ValidatedRecovery {
+    certificate: RecoveryCertificate,
+}
+
+impl ValidatedRecovery {
+    /// The certificate, holding only the attestations that verified.
+    pub fn certificate(&self) -> &RecoveryCertificate {
+        &self.certificate
+    }
+
+    /// Node being recovered.
+    pub fn node_id(&self) -> &NodeId {
+        self.certificate.node_id()
+    }
+
+    /// Epoch issued to the new key.
+    pub fn epoch(&self) -> u64 {
+        self.certificate.new_epoch()
+    }
+
+    /// Key issued the new epoch.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.certificate.request.new_public_key
+    }
+
+    /// Take the certificate back.
+    pub fn into_certificate(self) -> RecoveryCertificate {
+        self.certificate
+    }
+}
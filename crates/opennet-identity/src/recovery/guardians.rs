@@ -0,0 +1,127 @@
+//! Signed guardian sets.
+
+use opennet_core::NodeId;
+use opennet_core::types::{PublicKey, Signature};
+use opennet_wire::cbor::{CborDecoder, CborEncoder};
+use std::collections::BTreeSet;
+use crate::context::SigningContext;
+use crate::error::{IdentityError, Result};
+use crate::keypair::verify_signature;
+use crate::signer::NodeSigner;
+use super::{fixed, malformed};
+
+/// Guardians a node pre-registers to vouch for its recovery.
+///
+/// Signed by the node's key for `epoch`; a higher `version` replaces an
+/// earlier set. Guardians are named by NodeId only: their attestations
+/// are checked against whatever key they currently hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuardianSet {
+    /// Node the guardians vouch for.
+    pub node_id: NodeId,
+    /// Epoch of the owner key that signed the set.
+    pub epoch: u64,
+    /// Higher versions replace lower ones.
+    pub version: u64,
+    /// Attestations needed to recover.
+    pub threshold: u32,
+    /// Guardians allowed to attest.
+    pub guardians: BTreeSet<NodeId>,
+    /// Owner's signature over [`signing_bytes`](Self::signing_bytes).
+    pub signature: Signature,
+}
+
+impl GuardianSet {
+    /// Build and sign a guardian set with the owner's current key.
+    pub fn create(
+        node_id: NodeId,
+        epoch: u64,
+        version: u64,
+        threshold: u32,
+        guardians: impl IntoIterator<Item = NodeId>,
+        signer: &dyn NodeSigner,
+    ) -> Result<Self> {
+        let mut set = Self {
+            node_id,
+            epoch,
+            version,
+            threshold,
+            guardians: guardians.into_iter().collect(),
+            signature: Signature::from_bytes([0u8; 64]),
+        };
+        set.check_shape()?;
+        set.signature = signer.sign(&set.signing_bytes())?;
+        Ok(set)
+    }
+
+    /// Bytes signed by the owner.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        SigningContext::GuardianSet.signing_bytes(|enc| {
+            enc.encode_array_header(5);
+            self.encode_fields(enc);
+        })
+    }
+
+    /// Check the threshold and the owner's signature.
+    pub fn verify(&self, owner_key: &PublicKey) -> Result<()> {
+        self.check_shape()?;
+        verify_signature(owner_key, &self.signing_bytes(), &self.signature)
+    }
+
+    /// Canonical CBOR for storage and gossip:
+    /// `[node_id, epoch, version, threshold, [guardian...], signature]`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = CborEncoder::new();
+        enc.encode_array_header(6);
+        self.encode_fields(&mut enc);
+        enc.encode_bytes(self.signature.as_bytes());
+        enc.into_bytes()
+    }
+
+    /// Decode a guardian set. Does not verify it.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut dec = CborDecoder::new(bytes);
+        if dec.decode_array_header().map_err(malformed)? != 6 {
+            return Err(malformed("expected 6-element guardian set"));
+        }
+        let node_id = NodeId::from_bytes(fixed(&mut dec)?);
+        let epoch = dec.decode_uint().map_err(malformed)?;
+        let version = dec.decode_uint().map_err(malformed)?;
+        let threshold = u32::try_from(dec.decode_uint().map_err(malformed)?).map_err(malformed)?;
+        let count = dec.decode_array_header().map_err(malformed)?;
+        let mut guardians = BTreeSet::new();
+        for _ in 0..count {
+            guardians.insert(NodeId::from_bytes(fixed(&mut dec)?));
+        }
+        let signature = Signature::from_bytes(fixed(&mut dec)?);
+        if !dec.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(Self { node_id, epoch, version, threshold, guardians, signature })
+    }
+
+    fn encode_fields(&self, enc: &mut CborEncoder) {
+        enc.encode_bytes(self.node_id.as_bytes())
+            .encode_uint(self.epoch)
+            .encode_uint(self.version)
+            .encode_uint(u64::from(self.threshold))
+            .encode_array_header(self.guardians.len());
+        for id in &self.guardians {
+            enc.encode_bytes(id.as_bytes());
+        }
+    }
+
+    fn check_shape(&self) -> Result<()> {
+        if self.guardians.contains(&self.node_id) {
+            return Err(IdentityError::RecoveryFailed("a node cannot guard itself".into()));
+        }
+        if self.threshold == 0 || self.threshold as usize > self.guardians.len() {
+            return Err(IdentityError::RecoveryFailed(format!(
+                "threshold {} with {} guardians",
+                self.threshold,
+                self.guardians.len()
+            )));
+        }
+        Ok(())
+    }
+}
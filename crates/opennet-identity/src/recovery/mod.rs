@@ -0,0 +1,36 @@
+//! Social recovery of a revoked identity.
+//!
+//! A node registers a [`GuardianSet`] naming the peers who may vouch for
+//! it. Once its key is revoked, a new key signs a [`RecoveryRequest`] and
+//! the guardians attest to it; with enough attestations the
+//! [`RecoveryCertificate`] issues the epoch after the revoked one to the
+//! new key under the same NodeId.
+//!
+//! Guardians are named by NodeId and their attestations checked against
+//! the key the [`PeerKeyRegistry`](crate::rotation::PeerKeyRegistry)
+//! currently knows for them, so guardians rotate as usual. A certificate
+//! that passes [`RecoveryCertificate::validate`] becomes a
+//! [`ValidatedRecovery`], which is what the registry, the rotation log and
+//! [`NodeIdentity::recover`](crate::NodeIdentity::recover) accept.
+
+pub mod request;
+pub mod attestation;
+pub mod guardians;
+pub mod certificate;
+
+pub use request::RecoveryRequest;
+pub use attestation::PeerAttestation;
+pub use guardians::GuardianSet;
+pub use certificate::{RecoveryCertificate, ValidatedRecovery};
+
+use opennet_wire::cbor::CborDecoder;
+use crate::error::{IdentityError, Result};
+
+fn fixed<const N: usize>(dec: &mut CborDecoder) -> Result<[u8; N]> {
+    let bytes = dec.decode_bytes().map_err(malformed)?;
+    bytes.as_slice().try_into().map_err(|_| malformed(format!("expected {} bytes, got {}", N, bytes.len())))
+}
+
+fn malformed(e: impl std::fmt::Display) -> IdentityError {
+    IdentityError::RecoveryFailed(format!("malformed: {}", e))
+}
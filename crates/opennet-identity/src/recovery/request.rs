@@ -0,0 +1,113 @@
+//! Recovery requests signed by the new key.
+
+use opennet_core::NodeId;
+use opennet_core::types::{PublicKey, Signature, Timestamp};
+use opennet_wire::cbor::{CborDecoder, CborEncoder};
+use crate::context::SigningContext;
+use crate::error::Result;
+use crate::keypair::verify_signature;
+use crate::signer::NodeSigner;
+use super::{fixed, malformed};
+
+/// Request to resume a revoked identity under a new key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveryRequest {
+    /// Node being recovered.
+    pub node_id: NodeId,
+    /// Epoch whose key was revoked.
+    pub revoked_epoch: u64,
+    /// Key to issue the next epoch to.
+    pub new_public_key: PublicKey,
+    /// When the request was made.
+    pub timestamp: Timestamp,
+    /// Signature by the new key (proves possession).
+    pub new_key_signature: Signature,
+}
+
+impl RecoveryRequest {
+    /// Build a request signed by the new key.
+    pub fn create(node_id: NodeId, revoked_epoch: u64, timestamp: Timestamp, new_signer: &dyn NodeSigner) -> Result<Self> {
+        let mut request = Self {
+            node_id,
+            revoked_epoch,
+            new_public_key: new_signer.public_key(),
+            timestamp,
+            new_key_signature: Signature::from_bytes([0u8; 64]),
+        };
+        request.new_key_signature = new_signer.sign(&request.signing_bytes())?;
+        Ok(request)
+    }
+
+    /// Epoch the recovered key is issued for.
+    pub fn new_epoch(&self) -> u64 {
+        self.revoked_epoch + 1
+    }
+
+    /// Bytes signed by the new key.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.bytes_in(SigningContext::RecoveryRequest)
+    }
+
+    /// Bytes signed by each guardian.
+    pub fn attestation_bytes(&self) -> Vec<u8> {
+        self.bytes_in(SigningContext::RecoveryAttestation)
+    }
+
+    fn bytes_in(&self, context: SigningContext) -> Vec<u8> {
+        context.signing_bytes(|enc| self.encode_body(enc))
+    }
+
+    /// Canonical CBOR for handing to guardians:
+    /// `[node_id, revoked_epoch, new_public_key, timestamp, new_key_signature]`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = CborEncoder::new();
+        self.encode_into(&mut enc);
+        enc.into_bytes()
+    }
+
+    /// Decode a request. Does not verify it.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut dec = CborDecoder::new(bytes);
+        let request = Self::decode_from(&mut dec)?;
+        if !dec.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(request)
+    }
+
+    pub(crate) fn encode_into(&self, enc: &mut CborEncoder) {
+        enc.encode_array_header(5)
+            .encode_bytes(self.node_id.as_bytes())
+            .encode_uint(self.revoked_epoch)
+            .encode_bytes(self.new_public_key.as_bytes())
+            .encode_timestamp(self.timestamp)
+            .encode_bytes(self.new_key_signature.as_bytes());
+    }
+
+    pub(crate) fn decode_from(dec: &mut CborDecoder) -> Result<Self> {
+        if dec.decode_array_header().map_err(malformed)? != 5 {
+            return Err(malformed("expected 5-element recovery request"));
+        }
+        Ok(Self {
+            node_id: NodeId::from_bytes(fixed(dec)?),
+            revoked_epoch: dec.decode_uint().map_err(malformed)?,
+            new_public_key: PublicKey::from_bytes(fixed(dec)?),
+            timestamp: dec.decode_timestamp().map_err(malformed)?,
+            new_key_signature: Signature::from_bytes(fixed(dec)?),
+        })
+    }
+
+    fn encode_body(&self, enc: &mut CborEncoder) {
+        enc.encode_array_header(4)
+            .encode_bytes(self.node_id.as_bytes())
+            .encode_uint(self.revoked_epoch)
+            .encode_bytes(self.new_public_key.as_bytes())
+            .encode_timestamp(self.timestamp);
+    }
+
+    /// Check the proof of possession.
+    pub fn verify(&self) -> Result<()> {
+        verify_signature(&self.new_public_key, &self.signing_bytes(), &self.new_key_signature)?;
+        Ok(())
+    }
+}
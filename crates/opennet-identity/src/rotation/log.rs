@@ -0,0 +1,345 @@
+//! Signed rotation log.
+//!
+//! The log starts from a base key and holds every accepted
+//! `RotationRequest`, each signed by the outgoing and incoming keys.
+//! Normally the base is the genesis key at epoch 1, from which the NodeId
+//! is derived, and replaying the log proves that the NodeId legitimately
+//! holds the head key. After social recovery the base is the recovered key
+//! at the recovery epoch; that base is vouched for by the guardians'
+//! recovery certificate rather than by the NodeId itself, so such a log
+//! only verifies together with its validated certificate. A node reading
+//! back the log it wrote itself uses [`RotationLog::load_own`], which
+//! takes the base as written.
+//!
+//! A genesis key of another algorithm, such as a hybrid key, is kept in
+//! full: the NodeId derives from all of it, and the log continues from its
+//! Ed25519 half.
+
+use opennet_core::{EpochId, NodeId};
+use opennet_core::types::{AnyPublicKey, PublicKey, Signature, SignatureAlgorithm};
+use opennet_wire::cbor::{CborDecoder, CborEncoder};
+use std::path::Path;
+use super::request::RotationRequest;
+use super::validation::validate_rotation;
+use crate::error::{IdentityError, Result};
+use crate::recovery::ValidatedRecovery;
+
+/// Export format version.
+///
+/// Version 1 logs (`[1, genesis_key, genesis_time, entries]`) are
+/// rejected: their entries were signed over timestamps in seconds, which
+/// no longer verify now that timestamps carry milliseconds.
+pub const ROTATION_LOG_VERSION: u64 = 2;
+
+/// Verifiable history of a node's key rotations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotationLog {
+    node_id: NodeId,
+    base_epoch: EpochId,
+    base_key: PublicKey,
+    base_time: u64,
+    /// Full genesis key, when it is not plain Ed25519.
+    genesis_key: Option<AnyPublicKey>,
+    entries: Vec<RotationRequest>,
+}
+
+impl RotationLog {
+    /// Start a log at epoch 1.
+    pub fn new(genesis_key: PublicKey, genesis_time: u64) -> Self {
+        Self {
+            node_id: NodeId::from_public_key(genesis_key.as_bytes()),
+            base_epoch: 1,
+            base_key: genesis_key,
+            base_time: genesis_time,
+            genesis_key: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Derive the NodeId from the full genesis key, of which the base key
+    /// must be the Ed25519 half. Plain Ed25519 keys change nothing.
+    pub fn with_genesis_key(mut self, genesis_key: AnyPublicKey) -> Self {
+        if genesis_key.algorithm() != SignatureAlgorithm::Ed25519 {
+            self.node_id = NodeId::from_any_public_key(&genesis_key);
+            self.genesis_key = Some(genesis_key);
+        }
+        self
+    }
+
+    /// Start a log at the epoch a recovery issued, keeping the NodeId.
+    pub fn recovered(recovery: &ValidatedRecovery, start_time: u64) -> Self {
+        Self {
+            node_id: *recovery.node_id(),
+            base_epoch: recovery.epoch(),
+            base_key: *recovery.public_key(),
+            base_time: start_time,
+            genesis_key: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// NodeId the log belongs to.
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    /// First epoch in the log: 1, or the recovery epoch.
+    pub fn base_epoch(&self) -> EpochId {
+        self.base_epoch
+    }
+
+    /// Public key of the base epoch.
+    pub fn base_key(&self) -> &PublicKey {
+        &self.base_key
+    }
+
+    /// Start time of the base epoch.
+    pub fn base_time(&self) -> u64 {
+        self.base_time
+    }
+
+    /// Whether the log starts from a recovery rather than genesis.
+    pub fn is_recovered(&self) -> bool {
+        self.base_epoch != 1
+    }
+
+    /// Accepted rotations, oldest first.
+    pub fn entries(&self) -> &[RotationRequest] {
+        &self.entries
+    }
+
+    /// Current epoch.
+    pub fn head_epoch(&self) -> EpochId {
+        self.entries.last().map_or(self.base_epoch, |r| r.new_epoch)
+    }
+
+    /// Current public key.
+    pub fn head_key(&self) -> &PublicKey {
+        self.entries.last().map_or(&self.base_key, |r| &r.new_public_key)
+    }
+
+    /// Start time of the current epoch.
+    pub fn head_time(&self) -> u64 {
+        self.entries.last().map_or(self.base_time, |r| r.timestamp.as_secs())
+    }
+
+    /// Public key that was valid in `epoch`.
+    pub fn key_at(&self, epoch: EpochId) -> Option<&PublicKey> {
+        if epoch == self.base_epoch {
+            return Some(&self.base_key);
+        }
+        self.entries.iter().find(|r| r.new_epoch == epoch).map(|r| &r.new_public_key)
+    }
+
+    /// Check if epoch is in the log.
+    pub fn contains(&self, epoch: EpochId) -> bool {
+        epoch >= self.base_epoch && epoch <= self.head_epoch()
+    }
+
+    /// Append a rotation after checking it against the head.
+    pub fn append(&mut self, request: RotationRequest) -> Result<()> {
+        check_entry(&self.node_id, self.head_epoch(), self.head_key(), self.head_time(), &request)?;
+        self.entries.push(request);
+        Ok(())
+    }
+
+    /// Replay the log from genesis, returning the head key.
+    ///
+    /// Recovered logs are refused; check them with
+    /// [`verify_recovered`](Self::verify_recovered).
+    pub fn verify(&self) -> Result<PublicKey> {
+        if self.is_recovered() {
+            return Err(IdentityError::EpochChainBroken("recovered log needs its recovery certificate".into()));
+        }
+        self.verify_own()
+    }
+
+    /// Replay a recovered log from the base `recovery` issued, returning
+    /// the head key.
+    pub fn verify_recovered(&self, recovery: &ValidatedRecovery) -> Result<PublicKey> {
+        if recovery.node_id() != &self.node_id
+            || recovery.epoch() != self.base_epoch
+            || recovery.public_key() != &self.base_key
+        {
+            return Err(IdentityError::EpochChainBroken("recovery certificate does not issue the base key".into()));
+        }
+        self.replay()
+    }
+
+    /// Replay a log this node wrote itself, returning the head key.
+    ///
+    /// A genesis log is checked as by [`verify`](Self::verify); a
+    /// recovered log's base is taken as written, since whoever can rewrite
+    /// the data directory can replace the keys as well.
+    pub fn verify_own(&self) -> Result<PublicKey> {
+        if self.base_epoch == 0 {
+            return Err(IdentityError::EpochChainBroken("base epoch 0".into()));
+        }
+        if !self.is_recovered() && self.genesis_node_id()? != self.node_id {
+            return Err(IdentityError::EpochChainBroken("genesis key does not match NodeId".into()));
+        }
+        self.replay()
+    }
+
+    fn replay(&self) -> Result<PublicKey> {
+        let mut epoch = self.base_epoch;
+        let mut key = self.base_key;
+        let mut time = self.base_time;
+        for request in &self.entries {
+            check_entry(&self.node_id, epoch, &key, time, request)?;
+            epoch = request.new_epoch;
+            key = request.new_public_key;
+            time = request.timestamp.as_secs();
+        }
+        Ok(key)
+    }
+
+    /// NodeId the genesis key derives; a full genesis key must have the
+    /// base key as its Ed25519 half.
+    fn genesis_node_id(&self) -> Result<NodeId> {
+        match &self.genesis_key {
+            None => Ok(NodeId::from_public_key(self.base_key.as_bytes())),
+            Some(full) if full.ed25519().ok() == Some(self.base_key) => Ok(NodeId::from_any_public_key(full)),
+            Some(_) => Err(IdentityError::EpochChainBroken("genesis key does not contain the base key".into())),
+        }
+    }
+
+    /// Encode for handing to peers or writing to disk.
+    ///
+    /// `[version, node_id, base_epoch, base_key, base_time, (genesis_key,)
+    /// [[current_epoch, new_epoch, new_key, timestamp, old_sig, new_sig], ...]]`,
+    /// where the algorithm-tagged genesis key is present only if it is not
+    /// plain Ed25519.
+    pub fn export(&self) -> Vec<u8> {
+        let mut enc = CborEncoder::new();
+        enc.encode_array_header(6 + usize::from(self.genesis_key.is_some()))
+            .encode_uint(ROTATION_LOG_VERSION)
+            .encode_bytes(self.node_id.as_bytes())
+            .encode_uint(self.base_epoch)
+            .encode_bytes(self.base_key.as_bytes())
+            .encode_uint(self.base_time);
+        if let Some(genesis_key) = &self.genesis_key {
+            enc.encode_bytes(&genesis_key.to_bytes());
+        }
+        enc.encode_array_header(self.entries.len());
+        for r in &self.entries {
+            enc.encode_array_header(6)
+                .encode_uint(r.current_epoch)
+                .encode_uint(r.new_epoch)
+                .encode_bytes(r.new_public_key.as_bytes())
+                .encode_timestamp(r.timestamp)
+                .encode_bytes(r.old_key_signature.as_bytes())
+                .encode_bytes(r.new_key_signature.as_bytes());
+        }
+        enc.into_bytes()
+    }
+
+    /// Decode and verify an exported genesis log.
+    pub fn import(bytes: &[u8]) -> Result<Self> {
+        let log = Self::decode(bytes)?;
+        log.verify()?;
+        Ok(log)
+    }
+
+    /// Decode a log this node exported itself, checked as by
+    /// [`verify_own`](Self::verify_own).
+    pub fn import_own(bytes: &[u8]) -> Result<Self> {
+        let log = Self::decode(bytes)?;
+        log.verify_own()?;
+        Ok(log)
+    }
+
+    /// Decode a log, checking each entry against the one before it.
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut dec = CborDecoder::new(bytes);
+        let len = dec.decode_array_header().map_err(malformed)?;
+        let version = dec.decode_uint().map_err(malformed)?;
+        let mut log = match (version, len) {
+            (ROTATION_LOG_VERSION, 6 | 7) => {
+                let node_id = NodeId::from_bytes(fixed(&dec.decode_bytes().map_err(malformed)?)?);
+                let base_epoch = dec.decode_uint().map_err(malformed)?;
+                let base_key = PublicKey::from_bytes(fixed(&dec.decode_bytes().map_err(malformed)?)?);
+                let base_time = dec.decode_uint().map_err(malformed)?;
+                let genesis_key = if len == 7 {
+                    Some(AnyPublicKey::from_bytes(&dec.decode_bytes().map_err(malformed)?).map_err(malformed)?)
+                } else {
+                    None
+                };
+                Self { node_id, base_epoch, base_key, base_time, genesis_key, entries: Vec::new() }
+            }
+            (ROTATION_LOG_VERSION, _) => return Err(malformed(format!("unexpected {}-element log", len))),
+            _ => {
+                return Err(IdentityError::StorageError(format!("unsupported rotation log version {}", version)));
+            }
+        };
+
+        let count = dec.decode_array_header().map_err(malformed)?;
+        for _ in 0..count {
+            if dec.decode_array_header().map_err(malformed)? != 6 {
+                return Err(malformed("expected 6-element entry"));
+            }
+            let current_epoch = dec.decode_uint().map_err(malformed)?;
+            let new_epoch = dec.decode_uint().map_err(malformed)?;
+            let new_public_key = PublicKey::from_bytes(fixed(&dec.decode_bytes().map_err(malformed)?)?);
+            let timestamp = dec.decode_timestamp().map_err(malformed)?;
+            let old_key_signature = Signature::from_bytes(fixed(&dec.decode_bytes().map_err(malformed)?)?);
+            let new_key_signature = Signature::from_bytes(fixed(&dec.decode_bytes().map_err(malformed)?)?);
+            log.append(RotationRequest {
+                node_id: log.node_id,
+                current_epoch,
+                new_epoch,
+                new_public_key,
+                timestamp,
+                old_key_signature,
+                new_key_signature,
+            })?;
+        }
+        if !dec.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(log)
+    }
+
+    /// Write the log to `path` atomically.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        opennet_core::fs::write_atomic(path.as_ref(), &self.export())
+            .map_err(|e| IdentityError::StorageError(e.to_string()))
+    }
+
+    /// Read and verify a genesis log from `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(path).map_err(|e| IdentityError::StorageError(e.to_string()))?;
+        Self::import(&bytes)
+    }
+
+    /// Read back the log this node saved to `path`, checked as by
+    /// [`verify_own`](Self::verify_own).
+    pub fn load_own<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(path).map_err(|e| IdentityError::StorageError(e.to_string()))?;
+        Self::import_own(&bytes)
+    }
+}
+
+fn check_entry(node_id: &NodeId, epoch: EpochId, key: &PublicKey, time: u64, request: &RotationRequest) -> Result<()> {
+    if &request.node_id != node_id {
+        return Err(IdentityError::EpochChainBroken("rotation for a different node".into()));
+    }
+    if request.current_epoch != epoch {
+        return Err(IdentityError::EpochChainBroken(format!(
+            "rotation from epoch {} but head is {}",
+            request.current_epoch, epoch
+        )));
+    }
+    if request.timestamp.as_secs() < time {
+        return Err(IdentityError::EpochChainBroken("rotation predates current epoch".into()));
+    }
+    validate_rotation(request, key)
+}
+
+fn fixed<const N: usize>(bytes: &[u8]) -> Result<[u8; N]> {
+    bytes.try_into().map_err(|_| malformed(format!("expected {} bytes, got {}", N, bytes.len())))
+}
+
+fn malformed(e: impl std::fmt::Display) -> IdentityError {
+    IdentityError::StorageError(format!("malformed rotation log: {}", e))
+}
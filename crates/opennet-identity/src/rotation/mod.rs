@@ -3,7 +3,13 @@
 pub mod request;
 pub mod validation;
 pub mod chain;
+pub mod log;
+pub mod scheduler;
+pub mod registry;
 
 pub use request::RotationRequest;
 pub use validation::validate_rotation;
 pub use chain::EpochChain;
+pub use log::RotationLog;
+pub use scheduler::{RotationPolicy, RotationScheduler};
+pub use registry::{PeerKeyRegistry, RotationOutcome};
@@ -0,0 +1,257 @@
+//! Peer-side view of other nodes' keys.
+//!
+//! Tracks the current epoch and key of every known NodeId and applies
+//! incoming rotations against it. A rotation is accepted only if it
+//! extends the known head and carries valid signatures from both the
+//! outgoing and incoming keys; a second, different rotation out of an
+//! epoch that was already rotated is evidence the old key is shared.
+//!
+//! Revoked (node, epoch) pairs are remembered: keys for them are not
+//! learned, and rotations into or out of them are refused.
+
+use opennet_core::{EpochId, NodeId};
+use opennet_core::types::PublicKey;
+use std::collections::{BTreeMap, BTreeSet};
+use super::log::RotationLog;
+use super::request::RotationRequest;
+use super::validation::validate_rotation;
+use crate::compromise::CompromiseEvidence;
+use crate::recovery::ValidatedRecovery;
+use crate::error::{IdentityError, Result};
+
+/// Accepted rotations remembered per node for conflict detection.
+const HISTORY_LEN: usize = 8;
+
+/// Result of applying a rotation.
+#[derive(Debug, Clone)]
+pub enum RotationOutcome {
+    /// New rotation; the key was updated and the rotation should be gossiped.
+    Accepted {
+        /// Rotated node.
+        node_id: NodeId,
+        /// Epoch left.
+        from_epoch: EpochId,
+        /// Epoch entered.
+        to_epoch: EpochId,
+    },
+    /// Already known, or too old to check; drop without gossiping.
+    Duplicate,
+    /// Validly signed rotation that contradicts an accepted one.
+    Conflict {
+        /// Node whose key signed both.
+        node_id: NodeId,
+        /// Evidence to report.
+        evidence: Box<CompromiseEvidence>,
+        /// Rotation accepted earlier.
+        accepted: Box<RotationRequest>,
+        /// Conflicting rotation.
+        conflicting: Box<RotationRequest>,
+    },
+}
+
+struct PeerKeys {
+    epoch: EpochId,
+    public_key: PublicKey,
+    since: u64,
+    /// Keys by epoch, for checking rotations out of past epochs.
+    keys: BTreeMap<EpochId, PublicKey>,
+    /// Accepted rotations keyed by the epoch they leave.
+    accepted: BTreeMap<EpochId, RotationRequest>,
+}
+
+/// Current keys of known peers.
+#[derive(Default)]
+pub struct PeerKeyRegistry {
+    peers: BTreeMap<NodeId, PeerKeys>,
+    revoked: BTreeSet<(NodeId, EpochId)>,
+}
+
+impl PeerKeyRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a key learned out of band (handshake, verified announcement).
+    ///
+    /// Ignored if an equal or newer epoch is already known, or the epoch
+    /// was revoked.
+    pub fn insert_known(&mut self, node_id: NodeId, epoch: EpochId, public_key: PublicKey, since: u64) {
+        if self.is_revoked(&node_id, epoch) || self.peers.get(&node_id).is_some_and(|p| p.epoch >= epoch) {
+            return;
+        }
+        let mut keys = self.peers.remove(&node_id).map(|p| p.keys).unwrap_or_default();
+        keys.insert(epoch, public_key);
+        self.peers.insert(node_id, PeerKeys { epoch, public_key, since, keys, accepted: BTreeMap::new() });
+    }
+
+    /// Learn a node's full history from a verified rotation log.
+    ///
+    /// Recovered logs are rejected: their base key is vouched for by a
+    /// recovery certificate, which goes through
+    /// [`apply_recovery`](Self::apply_recovery) instead.
+    pub fn import_log(&mut self, log: &RotationLog) -> Result<()> {
+        if log.is_recovered() {
+            return Err(IdentityError::EpochChainBroken("recovered log needs a recovery certificate".into()));
+        }
+        log.verify()?;
+        let node_id = *log.node_id();
+        if self.is_revoked(&node_id, log.head_epoch()) {
+            return Err(IdentityError::EpochChainBroken(format!("epoch {} is revoked", log.head_epoch())));
+        }
+        if self.peers.get(&node_id).is_some_and(|p| p.epoch > log.head_epoch()) {
+            return Ok(());
+        }
+        let mut keys = BTreeMap::new();
+        keys.insert(log.base_epoch(), *log.base_key());
+        let mut accepted = BTreeMap::new();
+        for r in log.entries() {
+            keys.insert(r.new_epoch, r.new_public_key);
+            accepted.insert(r.current_epoch, r.clone());
+        }
+        trim(&mut keys, &mut accepted);
+        self.peers.insert(node_id, PeerKeys {
+            epoch: log.head_epoch(),
+            public_key: *log.head_key(),
+            since: log.head_time(),
+            keys,
+            accepted,
+        });
+        Ok(())
+    }
+
+    /// Current epoch and key of a node.
+    pub fn current(&self, node_id: &NodeId) -> Option<(EpochId, PublicKey)> {
+        self.peers.get(node_id).map(|p| (p.epoch, p.public_key))
+    }
+
+    /// Key a node used in `epoch`, if still remembered.
+    pub fn key_at(&self, node_id: &NodeId, epoch: EpochId) -> Option<PublicKey> {
+        self.peers.get(node_id)?.keys.get(&epoch).copied()
+    }
+
+    /// Whether `public_key` is one of the node's remembered keys.
+    pub fn knows_key(&self, node_id: &NodeId, public_key: &PublicKey) -> bool {
+        self.peers.get(node_id).is_some_and(|p| p.keys.values().any(|k| k == public_key))
+    }
+
+    /// Apply an incoming rotation.
+    pub fn apply(&mut self, request: &RotationRequest) -> Result<RotationOutcome> {
+        if let Some(epoch) = [request.current_epoch, request.new_epoch]
+            .into_iter()
+            .find(|epoch| self.is_revoked(&request.node_id, *epoch))
+        {
+            return Err(IdentityError::EpochChainBroken(format!("epoch {} is revoked", epoch)));
+        }
+        let peer = self.peers.get_mut(&request.node_id).ok_or_else(|| {
+            IdentityError::EpochChainBroken("rotation for unknown node".into())
+        })?;
+
+        if request.current_epoch < peer.epoch {
+            let Some(accepted) = peer.accepted.get(&request.current_epoch) else {
+                return Ok(RotationOutcome::Duplicate);
+            };
+            if accepted == request {
+                return Ok(RotationOutcome::Duplicate);
+            }
+            let Some(old_key) = peer.keys.get(&request.current_epoch) else {
+                return Ok(RotationOutcome::Duplicate);
+            };
+            // Only a validly signed contradiction is evidence.
+            validate_rotation(request, old_key)?;
+            let accepted = Box::new(accepted.clone());
+            let conflicting = Box::new(request.clone());
+            return Ok(RotationOutcome::Conflict {
+                node_id: request.node_id,
+                evidence: Box::new(CompromiseEvidence::ConflictingEpoch {
+                    old_key: *old_key,
+                    first: accepted.clone(),
+                    second: conflicting.clone(),
+                }),
+                accepted,
+                conflicting,
+            });
+        }
+        if request.current_epoch > peer.epoch {
+            return Err(IdentityError::EpochChainBroken(format!(
+                "rotation from epoch {} but known head is {}",
+                request.current_epoch, peer.epoch
+            )));
+        }
+        if request.timestamp.as_secs() < peer.since {
+            return Err(IdentityError::EpochChainBroken("rotation predates current epoch".into()));
+        }
+        validate_rotation(request, &peer.public_key)?;
+
+        let from_epoch = peer.epoch;
+        peer.epoch = request.new_epoch;
+        peer.public_key = request.new_public_key;
+        peer.since = request.timestamp.as_secs();
+        peer.keys.insert(request.new_epoch, request.new_public_key);
+        peer.accepted.insert(from_epoch, request.clone());
+        trim(&mut peer.keys, &mut peer.accepted);
+        Ok(RotationOutcome::Accepted { node_id: request.node_id, from_epoch, to_epoch: request.new_epoch })
+    }
+
+    /// Install the key issued by a validated social recovery.
+    ///
+    /// The revoked epoch must already be marked revoked here. Everything
+    /// known from the revoked epoch onwards, including rotations made with
+    /// the stolen key, is discarded. Returns whether the recovery was new.
+    pub fn apply_recovery(&mut self, recovery: &ValidatedRecovery, since: u64) -> Result<bool> {
+        let node_id = *recovery.node_id();
+        let epoch = recovery.epoch();
+        let public_key = *recovery.public_key();
+        let revoked = recovery.certificate().request.revoked_epoch;
+        if !self.is_revoked(&node_id, revoked) {
+            return Err(IdentityError::EpochChainBroken(format!("epoch {} is not revoked", revoked)));
+        }
+        if self.key_at(&node_id, epoch) == Some(public_key) {
+            return Ok(false);
+        }
+        let (mut keys, mut accepted) = self
+            .peers
+            .remove(&node_id)
+            .map(|p| (p.keys, p.accepted))
+            .unwrap_or_default();
+        keys.retain(|e, _| *e < revoked);
+        accepted.retain(|e, _| *e < revoked);
+        keys.insert(epoch, public_key);
+        self.peers.insert(node_id, PeerKeys { epoch, public_key, since, keys, accepted });
+        Ok(true)
+    }
+
+    /// Record that `node_id`'s epoch `epoch` was revoked.
+    pub fn revoke(&mut self, node_id: NodeId, epoch: EpochId) {
+        self.revoked.insert((node_id, epoch));
+    }
+
+    /// Check whether `node_id`'s epoch `epoch` was revoked.
+    pub fn is_revoked(&self, node_id: &NodeId, epoch: EpochId) -> bool {
+        self.revoked.contains(&(*node_id, epoch))
+    }
+
+    /// Forget a node.
+    pub fn remove(&mut self, node_id: &NodeId) {
+        self.peers.remove(node_id);
+    }
+
+    /// Number of known nodes.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Check if empty.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+fn trim(keys: &mut BTreeMap<EpochId, PublicKey>, accepted: &mut BTreeMap<EpochId, RotationRequest>) {
+    while accepted.len() > HISTORY_LEN {
+        accepted.pop_first();
+    }
+    while keys.len() > HISTORY_LEN + 1 {
+        keys.pop_first();
+    }
+}
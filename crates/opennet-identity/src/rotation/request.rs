@@ -2,10 +2,15 @@
 
 use opennet_core::NodeId;
 use opennet_core::types::{PublicKey, Signature, Timestamp};
+use opennet_wire::cbor::CborEncoder;
+use opennet_wire::messages::RotationMessage;
 use serde::{Deserialize, Serialize};
+use crate::context::SigningContext;
+use crate::signer::NodeSigner;
+use crate::error::Result;
 
 /// Key rotation request.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RotationRequest {
     /// Node requesting rotation.
     pub node_id: NodeId,
@@ -24,20 +29,73 @@ pub struct RotationRequest {
 }
 
 impl RotationRequest {
+    /// Build a request signed by both the outgoing and incoming keys.
+    pub fn create(
+        node_id: NodeId,
+        current_epoch: u64,
+        timestamp: Timestamp,
+        old_signer: &dyn NodeSigner,
+        new_signer: &dyn NodeSigner,
+    ) -> Result<Self> {
+        let mut request = Self {
+            node_id,
+            current_epoch,
+            new_epoch: current_epoch + 1,
+            new_public_key: new_signer.public_key(),
+            timestamp,
+            old_key_signature: Signature::from_bytes([0u8; 64]),
+            new_key_signature: Signature::from_bytes([0u8; 64]),
+        };
+        request.old_key_signature = old_signer.sign(&request.old_key_signing_bytes())?;
+        request.new_key_signature = new_signer.sign(&request.new_key_signing_bytes())?;
+        Ok(request)
+    }
+
     /// Create signing bytes for old key signature.
     pub fn old_key_signing_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(self.node_id.as_bytes());
-        bytes.extend_from_slice(&self.current_epoch.to_be_bytes());
-        bytes.extend_from_slice(&self.new_epoch.to_be_bytes());
-        bytes.extend_from_slice(self.new_public_key.as_bytes());
-        bytes.extend_from_slice(&self.timestamp.as_secs().to_be_bytes());
-        bytes
+        SigningContext::RotationOldKey.signing_bytes(|enc| self.encode_body(enc))
     }
 
     /// Create signing bytes for new key signature.
     pub fn new_key_signing_bytes(&self) -> Vec<u8> {
-        // Same content, signed with new key
-        self.old_key_signing_bytes()
+        // Same fields, but a distinct context per key role
+        SigningContext::RotationNewKey.signing_bytes(|enc| self.encode_body(enc))
+    }
+
+    fn encode_body(&self, enc: &mut CborEncoder) {
+        enc.encode_array_header(5)
+            .encode_bytes(self.node_id.as_bytes())
+            .encode_uint(self.current_epoch)
+            .encode_uint(self.new_epoch)
+            .encode_bytes(self.new_public_key.as_bytes())
+            .encode_timestamp(self.timestamp);
+    }
+}
+
+impl From<RotationRequest> for RotationMessage {
+    fn from(r: RotationRequest) -> Self {
+        Self {
+            node_id: r.node_id,
+            current_epoch: r.current_epoch,
+            new_epoch: r.new_epoch,
+            new_public_key: r.new_public_key,
+            timestamp: r.timestamp,
+            old_key_signature: r.old_key_signature,
+            new_key_signature: r.new_key_signature,
+        }
+    }
+}
+
+impl From<RotationMessage> for RotationRequest {
+    fn from(m: RotationMessage) -> Self {
+        Self {
+            node_id: m.node_id,
+            current_epoch: m.current_epoch,
+            new_epoch: m.new_epoch,
+            new_public_key: m.new_public_key,
+            timestamp: m.timestamp,
+            old_key_signature: m.old_key_signature,
+            new_key_signature: m.new_key_signature,
+        }
     }
 }
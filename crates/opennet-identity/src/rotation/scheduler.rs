@@ -0,0 +1,75 @@
+//! Scheduled key rotation.
+//!
+//! Decides when the local node should rotate: once a configurable fraction
+//! of `MAX_EPOCH_DURATION` has elapsed, leaving the rest of the epoch for
+//! the rotation to propagate and for retries if it fails.
+
+use opennet_core::Epoch;
+use opennet_core::MAX_EPOCH_DURATION;
+
+/// When to rotate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationPolicy {
+    /// Rotate once this fraction (per mille) of `MAX_EPOCH_DURATION` has
+    /// elapsed since the epoch started.
+    pub rotate_at_permille: u32,
+    /// Wait between attempts after a failed rotation, in seconds.
+    pub retry_secs: u64,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        // Day 67.5 of 90.
+        Self { rotate_at_permille: 750, retry_secs: 3600 }
+    }
+}
+
+/// Tracks when the next rotation is due.
+pub struct RotationScheduler {
+    policy: RotationPolicy,
+    not_before: u64,
+}
+
+impl RotationScheduler {
+    /// Create a scheduler.
+    pub fn new(policy: RotationPolicy) -> Self {
+        Self { policy, not_before: 0 }
+    }
+
+    /// Policy in effect.
+    pub fn policy(&self) -> &RotationPolicy {
+        &self.policy
+    }
+
+    /// Time at which `epoch` should be rotated.
+    pub fn due_at(&self, epoch: &Epoch) -> u64 {
+        let permille = self.policy.rotate_at_permille.min(1000) as u64;
+        epoch.start_time.saturating_add(MAX_EPOCH_DURATION / 1000 * permille)
+    }
+
+    /// Whether a rotation should be attempted now.
+    pub fn is_due(&self, epoch: &Epoch, now_secs: u64) -> bool {
+        now_secs >= self.due_at(epoch) && self.may_retry(now_secs)
+    }
+
+    /// Whether the back-off from a failed attempt has elapsed.
+    pub fn may_retry(&self, now_secs: u64) -> bool {
+        now_secs >= self.not_before
+    }
+
+    /// Back off after a failed attempt.
+    pub fn record_failure(&mut self, now_secs: u64) {
+        self.not_before = now_secs.saturating_add(self.policy.retry_secs);
+    }
+
+    /// Reset back-off after a successful rotation.
+    pub fn record_success(&mut self) {
+        self.not_before = 0;
+    }
+}
+
+impl Default for RotationScheduler {
+    fn default() -> Self {
+        Self::new(RotationPolicy::default())
+    }
+}
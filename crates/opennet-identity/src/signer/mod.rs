@@ -0,0 +1,84 @@
+//! Signing backends.
+//!
+//! Everything that produces a signature on behalf of the node goes through
+//! [`NodeSigner`], so the private key can live in process memory, on a
+//! PKCS#11 token, or behind a separate signer process.
+
+pub mod pkcs11;
+#[cfg(unix)]
+pub mod remote;
+#[cfg(feature = "pkcs11")]
+pub mod token;
+
+pub use pkcs11::{Pkcs11Backend, Pkcs11Signer};
+#[cfg(unix)]
+pub use remote::{RemoteSigner, SignerDaemon};
+#[cfg(feature = "pkcs11")]
+pub use token::CryptokiBackend;
+
+use opennet_core::types::{AnyPublicKey, AnySignature, PublicKey, Signature, SignatureAlgorithm};
+use crate::keypair::{verify_signature, KeyPair};
+use crate::error::{IdentityError, Result};
+
+/// Produces signatures with the node's current key.
+pub trait NodeSigner: Send + Sync {
+    /// Public key matching the signatures.
+    fn public_key(&self) -> PublicKey;
+
+    /// Sign a message.
+    fn sign(&self, message: &[u8]) -> Result<Signature>;
+
+    /// Algorithm of the node's full key.
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::Ed25519
+    }
+
+    /// Full public key with its algorithm.
+    fn public_key_any(&self) -> AnyPublicKey {
+        self.public_key().into()
+    }
+
+    /// Sign with the node's full algorithm.
+    fn sign_any(&self, message: &[u8]) -> Result<AnySignature> {
+        self.sign(message).map(AnySignature::from)
+    }
+}
+
+impl NodeSigner for KeyPair {
+    fn public_key(&self) -> PublicKey {
+        KeyPair::public_key(self)
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature> {
+        Ok(KeyPair::sign(self, message))
+    }
+}
+
+impl<S: NodeSigner + ?Sized> NodeSigner for Box<S> {
+    fn public_key(&self) -> PublicKey {
+        (**self).public_key()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature> {
+        (**self).sign(message)
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        (**self).algorithm()
+    }
+
+    fn public_key_any(&self) -> AnyPublicKey {
+        (**self).public_key_any()
+    }
+
+    fn sign_any(&self, message: &[u8]) -> Result<AnySignature> {
+        (**self).sign_any(message)
+    }
+}
+
+/// Check a signature from an external backend before handing it out.
+fn checked(public_key: &PublicKey, message: &[u8], signature: Signature) -> Result<Signature> {
+    verify_signature(public_key, message, &signature)
+        .map_err(|_| IdentityError::SignerError("backend returned an invalid signature".into()))?;
+    Ok(signature)
+}
@@ -0,0 +1,72 @@
+//! PKCS#11 token signer.
+//!
+//! The token is reached through [`Pkcs11Backend`], a narrow view of the
+//! PKCS#11 session calls the signer needs (`C_FindObjects`, `C_GetAttributeValue`
+//! on `CKA_EC_POINT`, `C_SignInit`/`C_Sign` with `CKM_EDDSA`). With the
+//! `pkcs11` feature, `CryptokiBackend`
+//! implements it over a vendor module; tests can supply their own.
+
+use opennet_core::types::{PublicKey, Signature};
+use super::{checked, NodeSigner};
+use crate::error::{IdentityError, Result};
+
+/// PKCS#11 object handle.
+pub type ObjectHandle = u64;
+
+/// Session operations on a logged-in PKCS#11 token.
+pub trait Pkcs11Backend: Send + Sync {
+    /// Find the Ed25519 private key object with `CKA_LABEL` = `label`.
+    fn find_private_key(&self, label: &str) -> Result<ObjectHandle>;
+
+    /// Raw `CKA_EC_POINT` of the public key object paired with `label`.
+    fn ec_point(&self, label: &str) -> Result<Vec<u8>>;
+
+    /// `CKM_EDDSA` signature over `message`.
+    fn sign_eddsa(&self, key: ObjectHandle, message: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Signer whose Ed25519 key never leaves a PKCS#11 token.
+pub struct Pkcs11Signer<B> {
+    backend: B,
+    key: ObjectHandle,
+    public_key: PublicKey,
+}
+
+impl<B: Pkcs11Backend> Pkcs11Signer<B> {
+    /// Bind to the key pair labelled `label` on the token.
+    pub fn open(backend: B, label: &str) -> Result<Self> {
+        let key = backend.find_private_key(label)?;
+        let public_key = PublicKey::from_bytes(decode_ec_point(&backend.ec_point(label)?)?);
+        Ok(Self { backend, key, public_key })
+    }
+}
+
+impl<B: Pkcs11Backend> NodeSigner for Pkcs11Signer<B> {
+    fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature> {
+        let raw = self.backend.sign_eddsa(self.key, message)?;
+        let bytes: [u8; 64] = raw
+            .as_slice()
+            .try_into()
+            .map_err(|_| IdentityError::SignerError(format!("token returned {}-byte signature", raw.len())))?;
+        checked(&self.public_key, message, Signature::from_bytes(bytes))
+    }
+}
+
+/// Decode `CKA_EC_POINT` for Ed25519.
+///
+/// Tokens return either the raw 32-byte point or the point wrapped in a
+/// DER OCTET STRING, as PKCS#11 v3.0 specifies.
+pub fn decode_ec_point(bytes: &[u8]) -> Result<[u8; 32]> {
+    let point = match bytes {
+        [0x04, 0x20, rest @ ..] if rest.len() == 32 => rest,
+        raw if raw.len() == 32 => raw,
+        _ => return Err(IdentityError::InvalidKey("unrecognized CKA_EC_POINT encoding".into())),
+    };
+    let mut out = [0u8; 32];
+    out.copy_from_slice(point);
+    Ok(out)
+}
@@ -0,0 +1,203 @@
+//! Remote signer over a Unix domain socket.
+//!
+//! Frames are a big-endian `u32` length followed by the body. A request
+//! body is an opcode byte plus payload; a response body is a status byte
+//! plus payload (the key or signature on success, a UTF-8 message on
+//! error). One request is served per connection; the daemon handles
+//! each connection on its own thread.
+
+use opennet_core::types::{PublicKey, Signature};
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use super::{checked, NodeSigner};
+use crate::error::{IdentityError, Result};
+
+const OP_PUBLIC_KEY: u8 = 1;
+const OP_SIGN: u8 = 2;
+
+const STATUS_OK: u8 = 0;
+const STATUS_ERR: u8 = 1;
+
+/// How long the daemon waits on a single client.
+const DAEMON_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest frame either side accepts.
+pub const MAX_FRAME_LEN: usize = 1 << 20;
+
+/// Client for a signer process listening on a Unix socket.
+pub struct RemoteSigner {
+    path: PathBuf,
+    timeout: Duration,
+    public_key: PublicKey,
+}
+
+impl RemoteSigner {
+    /// Connect to the signer at `path` and fetch its public key.
+    pub fn connect<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_timeout(path, Duration::from_secs(5))
+    }
+
+    /// Connect with an explicit per-request I/O timeout.
+    pub fn with_timeout<P: AsRef<Path>>(path: P, timeout: Duration) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let key = request(&path, timeout, OP_PUBLIC_KEY, &[])?;
+        let bytes: [u8; 32] = key
+            .as_slice()
+            .try_into()
+            .map_err(|_| IdentityError::SignerError("remote signer sent a malformed public key".into()))?;
+        Ok(Self { path, timeout, public_key: PublicKey::from_bytes(bytes) })
+    }
+}
+
+impl NodeSigner for RemoteSigner {
+    fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature> {
+        let sig = request(&self.path, self.timeout, OP_SIGN, message)?;
+        let bytes: [u8; 64] = sig
+            .as_slice()
+            .try_into()
+            .map_err(|_| IdentityError::SignerError("remote signer sent a malformed signature".into()))?;
+        checked(&self.public_key, message, Signature::from_bytes(bytes))
+    }
+}
+
+/// Minimal signer daemon serving a [`NodeSigner`] on a Unix socket.
+///
+/// Stands in for an external signing service in tests and local setups.
+pub struct SignerDaemon<S> {
+    listener: UnixListener,
+    signer: S,
+}
+
+impl<S: NodeSigner> SignerDaemon<S> {
+    /// Bind to `path`, replacing a stale socket file. The socket is
+    /// restricted to the owning user.
+    ///
+    /// It is bound inside a fresh 0700 directory, made 0600 and only then
+    /// moved to `path`, so nobody else can connect while its permissions
+    /// are still the defaults.
+    pub fn bind<P: AsRef<Path>>(path: P, signer: S) -> Result<Self> {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        let path = path.as_ref();
+        let name = path.file_name().ok_or_else(|| IdentityError::SignerError("socket path has no file name".into()))?;
+        let private = path.with_file_name(format!(".{}.{}.bind", name.to_string_lossy(), std::process::id()));
+        let _ = std::fs::remove_dir_all(&private);
+        std::fs::DirBuilder::new().mode(0o700).create(&private).map_err(signer_io)?;
+        let staged = private.join("signer.sock");
+        let result = (|| {
+            let listener = UnixListener::bind(&staged)?;
+            std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+            std::fs::rename(&staged, path)?;
+            Ok(listener)
+        })();
+        let _ = std::fs::remove_dir_all(&private);
+        let listener = result.map_err(signer_io)?;
+        Ok(Self { listener, signer })
+    }
+
+    /// Accept and answer one connection.
+    pub fn serve_one(&self) -> Result<()> {
+        let (stream, _) = self.listener.accept().map_err(signer_io)?;
+        serve(&self.signer, stream)
+    }
+
+    /// Serve connections on a background thread until accepting fails.
+    /// Each connection gets its own thread, so a stalled client only
+    /// holds up itself.
+    pub fn spawn(self) -> std::thread::JoinHandle<()>
+    where
+        S: 'static,
+    {
+        let Self { listener, signer } = self;
+        let signer = Arc::new(signer);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let signer = Arc::clone(&signer);
+                        // A misbehaving client only loses its own connection.
+                        std::thread::spawn(move || { let _ = serve(&*signer, stream); });
+                    }
+                    Err(_) => break,
+                }
+            }
+        })
+    }
+
+}
+
+/// Answer one request on `stream`.
+fn serve<S: NodeSigner + ?Sized>(signer: &S, mut stream: UnixStream) -> Result<()> {
+    stream.set_read_timeout(Some(DAEMON_TIMEOUT)).map_err(signer_io)?;
+    stream.set_write_timeout(Some(DAEMON_TIMEOUT)).map_err(signer_io)?;
+    let body = read_frame(&mut stream)?;
+    let response = match body.split_first() {
+        Some((&OP_PUBLIC_KEY, [])) => Ok(signer.public_key().as_bytes().to_vec()),
+        Some((&OP_SIGN, message)) => signer.sign(message).map(|sig| sig.as_bytes().to_vec()),
+        _ => Err(IdentityError::SignerError("unknown request".into())),
+    };
+    let mut out = Vec::new();
+    match response {
+        Ok(payload) => {
+            out.push(STATUS_OK);
+            out.extend_from_slice(&payload);
+        }
+        Err(e) => {
+            out.push(STATUS_ERR);
+            out.extend_from_slice(e.to_string().as_bytes());
+        }
+    }
+    write_frame(&mut stream, &out)
+}
+
+fn request(path: &Path, timeout: Duration, op: u8, payload: &[u8]) -> Result<Vec<u8>> {
+    let mut stream = UnixStream::connect(path).map_err(signer_io)?;
+    stream.set_read_timeout(Some(timeout)).map_err(signer_io)?;
+    stream.set_write_timeout(Some(timeout)).map_err(signer_io)?;
+    let mut body = Vec::with_capacity(1 + payload.len());
+    body.push(op);
+    body.extend_from_slice(payload);
+    write_frame(&mut stream, &body)?;
+    let response = read_frame(&mut stream)?;
+    match response.split_first() {
+        Some((&STATUS_OK, payload)) => Ok(payload.to_vec()),
+        Some((&STATUS_ERR, message)) => Err(IdentityError::SignerError(format!(
+            "remote signer: {}",
+            String::from_utf8_lossy(message)
+        ))),
+        _ => Err(IdentityError::SignerError("malformed remote signer response".into())),
+    }
+}
+
+fn write_frame(stream: &mut UnixStream, body: &[u8]) -> Result<()> {
+    if body.len() > MAX_FRAME_LEN {
+        return Err(IdentityError::SignerError("frame too large".into()));
+    }
+    stream.write_all(&(body.len() as u32).to_be_bytes()).map_err(signer_io)?;
+    stream.write_all(body).map_err(signer_io)
+}
+
+fn read_frame(stream: &mut UnixStream) -> Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len).map_err(signer_io)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(IdentityError::SignerError("frame too large".into()));
+    }
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body).map_err(signer_io)?;
+    Ok(body)
+}
+
+fn signer_io(e: std::io::Error) -> IdentityError {
+    IdentityError::SignerError(e.to_string())
+}
@@ -0,0 +1,95 @@
+//! [`Pkcs11Backend`] over a vendor PKCS#11 module, using `cryptoki`.
+//!
+//! The module is loaded and initialised once; a single logged-in session
+//! serves every call, serialised through a mutex because PKCS#11 sessions
+//! must not be used from two threads at once.
+
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, ObjectClass};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+use std::path::Path;
+use std::sync::Mutex;
+use super::pkcs11::{ObjectHandle, Pkcs11Backend};
+use crate::error::{IdentityError, Result};
+
+/// A logged-in session on one token.
+pub struct CryptokiBackend {
+    // Keeps the module loaded for as long as the session lives.
+    _context: Pkcs11,
+    state: Mutex<SessionState>,
+}
+
+struct SessionState {
+    session: Session,
+    /// Keys found so far; an [`ObjectHandle`] is an index into this list.
+    keys: Vec<cryptoki::object::ObjectHandle>,
+}
+
+impl CryptokiBackend {
+    /// Load the module at `module`, open a session on the token labelled
+    /// `token_label` and log in as the user with `pin`.
+    pub fn open<P: AsRef<Path>>(module: P, token_label: &str, pin: &str) -> Result<Self> {
+        let context = Pkcs11::new(module.as_ref()).map_err(token_err)?;
+        context.initialize(CInitializeArgs::OsThreads).map_err(token_err)?;
+        let slot = context
+            .get_slots_with_token()
+            .map_err(token_err)?
+            .into_iter()
+            .find(|slot| context.get_token_info(*slot).is_ok_and(|info| info.label() == token_label))
+            .ok_or_else(|| IdentityError::SignerError(format!("no PKCS#11 token labelled {token_label:?}")))?;
+        let session = context.open_ro_session(slot).map_err(token_err)?;
+        session.login(UserType::User, Some(&AuthPin::new(pin.to_string()))).map_err(token_err)?;
+        Ok(Self { _context: context, state: Mutex::new(SessionState { session, keys: Vec::new() }) })
+    }
+
+    fn state(&self) -> Result<std::sync::MutexGuard<'_, SessionState>> {
+        self.state.lock().map_err(|_| IdentityError::SignerError("PKCS#11 session lock poisoned".into()))
+    }
+}
+
+impl Pkcs11Backend for CryptokiBackend {
+    fn find_private_key(&self, label: &str) -> Result<ObjectHandle> {
+        let mut state = self.state()?;
+        let key = find_one(&state.session, ObjectClass::PRIVATE_KEY, label)?;
+        state.keys.push(key);
+        Ok((state.keys.len() - 1) as ObjectHandle)
+    }
+
+    fn ec_point(&self, label: &str) -> Result<Vec<u8>> {
+        let state = self.state()?;
+        let public = find_one(&state.session, ObjectClass::PUBLIC_KEY, label)?;
+        let attributes = state.session.get_attributes(public, &[AttributeType::EcPoint]).map_err(token_err)?;
+        attributes
+            .into_iter()
+            .find_map(|attribute| match attribute {
+                Attribute::EcPoint(point) => Some(point),
+                _ => None,
+            })
+            .ok_or_else(|| IdentityError::SignerError(format!("public key {label:?} has no CKA_EC_POINT")))
+    }
+
+    fn sign_eddsa(&self, key: ObjectHandle, message: &[u8]) -> Result<Vec<u8>> {
+        let state = self.state()?;
+        let key = usize::try_from(key)
+            .ok()
+            .and_then(|index| state.keys.get(index).copied())
+            .ok_or_else(|| IdentityError::SignerError(format!("unknown PKCS#11 key handle {key}")))?;
+        state.session.sign(&Mechanism::Eddsa, key, message).map_err(token_err)
+    }
+}
+
+/// The single object of `class` labelled `label`.
+fn find_one(session: &Session, class: ObjectClass, label: &str) -> Result<cryptoki::object::ObjectHandle> {
+    let template = [Attribute::Class(class), Attribute::Label(label.as_bytes().to_vec())];
+    match session.find_objects(&template).map_err(token_err)?.as_slice() {
+        [object] => Ok(*object),
+        [] => Err(IdentityError::SignerError(format!("no {class} labelled {label:?} on the token"))),
+        _ => Err(IdentityError::SignerError(format!("several {class} objects labelled {label:?} on the token"))),
+    }
+}
+
+fn token_err(e: cryptoki::error::Error) -> IdentityError {
+    IdentityError::SignerError(format!("PKCS#11: {e}"))
+}
@@ -1,64 +1,101 @@
 //! Secure key storage.
 
 use crate::keypair::KeyPair;
+use crate::keystore::{self, KdfParams, KeyUnlock};
 use crate::error::{IdentityError, Result};
-use std::path::Path;
+use opennet_core::fs::write_atomic;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
 
 /// Secure key storage interface.
 pub trait SecureStorage {
     /// Store a keypair.
     fn store(&mut self, name: &str, keypair: &KeyPair) -> Result<()>;
-    
+
     /// Load a keypair.
     fn load(&self, name: &str) -> Result<KeyPair>;
-    
+
     /// Delete a keypair.
     fn delete(&mut self, name: &str) -> Result<()>;
-    
+
     /// Check if keypair exists.
     fn exists(&self, name: &str) -> bool;
 }
 
-/// File-based storage (for development).
+/// File-based storage, encrypted at rest.
+///
+/// Each key is kept in `<name>.key` as a passphrase-encrypted keystore
+/// (see [`keystore`](crate::keystore)). Files are created with mode 0600
+/// and replaced atomically via a temporary file and rename.
 pub struct FileStorage {
-    base_path: std::path::PathBuf,
+    base_path: PathBuf,
+    unlock: KeyUnlock,
+    kdf: KdfParams,
 }
 
 impl FileStorage {
-    /// Create new file storage.
-    pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
+    /// Create new file storage unlocked by `unlock`.
+    pub fn new<P: AsRef<Path>>(base_path: P, unlock: KeyUnlock) -> Self {
         Self {
             base_path: base_path.as_ref().to_path_buf(),
+            unlock,
+            kdf: KdfParams::default(),
         }
     }
+
+    /// Use custom KDF costs for newly stored keys.
+    pub fn with_kdf_params(mut self, kdf: KdfParams) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
+    /// Re-encrypt a legacy plaintext key file in place.
+    pub fn migrate_plaintext(&mut self, name: &str) -> Result<()> {
+        let bytes = Zeroizing::new(
+            std::fs::read(self.path(name)).map_err(|e| IdentityError::StorageError(e.to_string()))?,
+        );
+        if bytes.len() != 32 {
+            return Err(IdentityError::StorageError(format!("{} is not a plaintext key file", name)));
+        }
+        let mut secret = Zeroizing::new([0u8; 32]);
+        secret.copy_from_slice(&bytes);
+        let keypair = KeyPair::from_bytes(&secret)?;
+        self.store(name, &keypair)
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.base_path.join(format!("{}.key", name))
+    }
 }
 
 impl SecureStorage for FileStorage {
     fn store(&mut self, name: &str, keypair: &KeyPair) -> Result<()> {
-        let path = self.base_path.join(format!("{}.key", name));
-        std::fs::write(&path, keypair.secret_bytes())
-            .map_err(|e| IdentityError::StorageError(e.to_string()))?;
-        Ok(())
+        let unlock_secret = self.unlock.secret()?;
+        let sealed = keystore::seal(keypair.expose_secret().as_bytes(), &unlock_secret, self.kdf)?;
+        write_atomic(&self.path(name), &sealed)
+            .map_err(|e| IdentityError::StorageError(e.to_string()))
     }
 
     fn load(&self, name: &str) -> Result<KeyPair> {
-        let path = self.base_path.join(format!("{}.key", name));
-        let bytes = std::fs::read(&path)
+        let bytes = std::fs::read(self.path(name))
             .map_err(|e| IdentityError::StorageError(e.to_string()))?;
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&bytes);
-        KeyPair::from_bytes(&arr)
+        if bytes.len() == 32 {
+            return Err(IdentityError::StorageError(format!(
+                "{} is an unencrypted legacy key file; migrate it first",
+                name
+            )));
+        }
+        let secret = keystore::open(&bytes, &self.unlock.secret()?)?;
+        KeyPair::from_bytes(&secret)
     }
 
     fn delete(&mut self, name: &str) -> Result<()> {
-        let path = self.base_path.join(format!("{}.key", name));
-        std::fs::remove_file(&path)
+        std::fs::remove_file(self.path(name))
             .map_err(|e| IdentityError::StorageError(e.to_string()))?;
         Ok(())
     }
 
     fn exists(&self, name: &str) -> bool {
-        let path = self.base_path.join(format!("{}.key", name));
-        path.exists()
+        self.path(name).exists()
     }
 }
@@ -1,8 +1,10 @@
 //! IdentityWatcher - FSM event producer for identity events.
 
 use opennet_core::NodeId;
-use crate::node_identity::NodeIdentity;
-use crate::compromise::{CompromiseDetector, CompromiseEvidence};
+use opennet_core::types::PublicKey;
+use crate::compromise::{CompromiseDetector, CompromiseEvidence, DetectorConfig, Observation, SignedClaim};
+use crate::rotation::PeerKeyRegistry;
+use crate::error::Result;
 
 /// Events produced by IdentityWatcher.
 #[derive(Debug, Clone)]
@@ -32,22 +34,65 @@ impl IdentityWatcher {
         }
     }
 
-    /// Process identity change.
-    pub fn on_identity_change(&mut self, identity: &NodeIdentity) {
-        // Check for compromise
-        if let Some(evidence) = self.compromise_detector.check_duplicate_signatures(identity.node_id()) {
-            self.pending_events.push(IdentityEvent::CompromiseDetected {
-                node_id: *identity.node_id(),
-                evidence,
-            });
+    /// Watcher whose detector uses custom retention limits.
+    pub fn with_detector_config(config: DetectorConfig) -> Self {
+        Self {
+            compromise_detector: CompromiseDetector::with_config(config),
+            pending_events: Vec::new(),
         }
     }
 
+    /// Detector state, for callers that report evidence themselves.
+    pub fn detector_mut(&mut self) -> &mut CompromiseDetector {
+        &mut self.compromise_detector
+    }
+
+    /// Check a claim signed by `public_key` for equivocation.
+    ///
+    /// Returns the evidence, if any, so the caller can gossip it.
+    pub fn on_claim(
+        &mut self,
+        claim: SignedClaim,
+        public_key: &PublicKey,
+        now_secs: u64,
+    ) -> Result<Option<CompromiseEvidence>> {
+        let node_id = claim.node_id;
+        let evidence = self.compromise_detector.record_claim(claim, public_key, now_secs)?;
+        self.report(node_id, &evidence);
+        Ok(evidence)
+    }
+
+    /// Check a peer's observation for equivocation or impossible timing.
+    /// The observer's key must be its genesis key or known to `keys`.
+    pub fn on_observation(
+        &mut self,
+        observation: Observation,
+        public_key: &PublicKey,
+        keys: &PeerKeyRegistry,
+        now_secs: u64,
+    ) -> Result<Option<CompromiseEvidence>> {
+        let node_id = observation.claim.node_id;
+        let evidence = self.compromise_detector.record_observation(observation, public_key, keys, now_secs)?;
+        self.report(node_id, &evidence);
+        Ok(evidence)
+    }
+
     /// Process key rotation.
     pub fn on_rotation(&mut self, new_epoch_id: u64) {
         self.pending_events.push(IdentityEvent::RotationCompleted { epoch_id: new_epoch_id });
     }
 
+    /// Report compromise evidence found elsewhere (e.g. conflicting rotations).
+    pub fn on_compromise(&mut self, node_id: NodeId, evidence: CompromiseEvidence) {
+        self.pending_events.push(IdentityEvent::CompromiseDetected { node_id, evidence });
+    }
+
+    fn report(&mut self, node_id: NodeId, evidence: &Option<CompromiseEvidence>) {
+        if let Some(evidence) = evidence {
+            self.on_compromise(node_id, evidence.clone());
+        }
+    }
+
     /// Drain pending events.
     pub fn drain_events(&mut self) -> Vec<IdentityEvent> {
         std::mem::take(&mut self.pending_events)
@@ -1,4 +1,8 @@
 use std::path::PathBuf;
+use opennet_identity::rotation::RotationPolicy;
+use opennet_identity::DetectorConfig;
+use opennet_time::nmt::NmtConfig;
+use opennet_transport::admission::RateLimitConfig;
 
 /// Node configuration.
 #[derive(Debug, Clone)]
@@ -12,6 +16,16 @@ pub struct NodeConfig {
     /// Trust thresholds.
     pub trust_warn_threshold: f64,
     pub trust_critical_threshold: f64,
+    /// Transport admission rate limits.
+    pub admission: RateLimitConfig,
+    /// Automatic key rotation schedule.
+    pub rotation: RotationPolicy,
+    /// Compromise detector retention and memory limits.
+    pub compromise: DetectorConfig,
+    /// Interval between identity announcements.
+    pub announce_interval_secs: u64,
+    /// Network Median Time sampling and slew.
+    pub nmt: NmtConfig,
 }
 
 impl Default for NodeConfig {
@@ -22,6 +36,11 @@ impl Default for NodeConfig {
             bootstrap_peers: Vec::new(),
             trust_warn_threshold: 0.15,
             trust_critical_threshold: 0.05,
+            admission: RateLimitConfig::default(),
+            rotation: RotationPolicy::default(),
+            compromise: DetectorConfig::default(),
+            announce_interval_secs: 3600,
+            nmt: NmtConfig::default(),
         }
     }
 }
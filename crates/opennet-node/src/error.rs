@@ -11,4 +11,12 @@ pub enum NodeError {
     SyncFailed(String),
     #[error("peer error: {0}")]
     PeerError(String),
+    #[error("identity error: {0}")]
+    IdentityError(String),
+    /// A revocation failed to verify, or the spool could not be read.
+    #[error("revocation error: {0}")]
+    RevocationError(String),
+    /// State could not be written to disk; the in-memory state stands.
+    #[error("persistence error: {0}")]
+    PersistenceError(String),
 }
@@ -21,6 +21,10 @@ pub enum StateEvent {
     EpochExpired,
     /// Network timeout.
     NetworkTimeout,
+    /// Local clock drifted past the critical threshold.
+    ClockDriftCritical { drift_secs: u64 },
+    /// Local clock back within drift tolerance.
+    ClockRecovered,
 }
 
 impl StateEvent {
@@ -31,9 +35,11 @@ impl StateEvent {
             StateEvent::SecurityViolation { .. } => 255,
             StateEvent::TrustCritical => 254,
             StateEvent::EpochExpired => 200,
+            StateEvent::ClockDriftCritical { .. } => 150,
             StateEvent::TrustBelowWarn => 100,
             StateEvent::NetworkTimeout => 50,
             StateEvent::TrustRecovered => 30,
+            StateEvent::ClockRecovered => 30,
             StateEvent::SyncCompleted => 20,
             StateEvent::PeerDiscovered { .. } => 10,
         }
@@ -8,7 +8,7 @@ pub fn check_invariants(state: NodeState) -> bool {
     let valid_state = matches!(
         state,
         NodeState::Bootstrap | NodeState::Syncing | NodeState::Active | 
-        NodeState::Degraded | NodeState::Quarantined
+        NodeState::Degraded(_) | NodeState::Quarantined
     );
     
     valid_state
@@ -8,6 +8,6 @@ pub mod transition;
 pub mod table;
 pub mod invariants;
 
-pub use state::NodeState;
+pub use state::{DegradeReasons, NodeState};
 pub use event::StateEvent;
 pub use transition::Transition;
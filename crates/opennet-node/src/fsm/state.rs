@@ -1,6 +1,31 @@
 //! Node state definitions.
 //!
 //! BOOTSTRAP → SYNCING → ACTIVE ↔ DEGRADED → QUARANTINED
+//!
+//! DEGRADED remembers why it was entered and returns to ACTIVE only once
+//! every reason has cleared.
+
+/// Why a node is DEGRADED.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DegradeReasons {
+    /// Trust fell below the warning threshold.
+    pub low_trust: bool,
+    /// The local clock drifted past the critical threshold.
+    pub clock_drift: bool,
+}
+
+impl DegradeReasons {
+    /// Degraded for low trust only.
+    pub const LOW_TRUST: Self = Self { low_trust: true, clock_drift: false };
+
+    /// Degraded for clock drift only.
+    pub const CLOCK_DRIFT: Self = Self { low_trust: false, clock_drift: true };
+
+    /// Whether no reason remains.
+    pub fn is_empty(&self) -> bool {
+        !self.low_trust && !self.clock_drift
+    }
+}
 
 /// Node states - a node MUST be in exactly one state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -12,7 +37,7 @@ pub enum NodeState {
     /// Fully operational.
     Active,
     /// Reduced capacity, temporary failures.
-    Degraded,
+    Degraded(DegradeReasons),
     /// Isolated, revoked or unsafe.
     Quarantined,
 }
@@ -20,7 +45,7 @@ pub enum NodeState {
 impl NodeState {
     /// Check if state allows network operations.
     pub fn can_operate(&self) -> bool {
-        matches!(self, NodeState::Active | NodeState::Degraded)
+        matches!(self, NodeState::Active | NodeState::Degraded(_))
     }
 
     /// Check if state allows accepting connections.
@@ -46,7 +71,7 @@ impl std::fmt::Display for NodeState {
             NodeState::Bootstrap => write!(f, "BOOTSTRAP"),
             NodeState::Syncing => write!(f, "SYNCING"),
             NodeState::Active => write!(f, "ACTIVE"),
-            NodeState::Degraded => write!(f, "DEGRADED"),
+            NodeState::Degraded(_) => write!(f, "DEGRADED"),
             NodeState::Quarantined => write!(f, "QUARANTINED"),
         }
     }
@@ -1,6 +1,6 @@
 //! Transition table for FSM validation.
 
-use super::state::NodeState;
+use super::state::{DegradeReasons, NodeState};
 use super::event::StateEvent;
 use std::collections::BTreeSet;
 
@@ -19,15 +19,25 @@ impl TransitionTable {
         valid.insert((NodeState::Syncing, std::mem::discriminant(&StateEvent::SecurityViolation { reason: String::new() })));
         valid.insert((NodeState::Active, std::mem::discriminant(&StateEvent::TrustBelowWarn)));
         valid.insert((NodeState::Active, std::mem::discriminant(&StateEvent::TrustCritical)));
+        valid.insert((NodeState::Active, std::mem::discriminant(&StateEvent::ClockDriftCritical { drift_secs: 0 })));
         valid.insert((NodeState::Active, std::mem::discriminant(&StateEvent::SecurityViolation { reason: String::new() })));
-        valid.insert((NodeState::Degraded, std::mem::discriminant(&StateEvent::TrustRecovered)));
-        valid.insert((NodeState::Degraded, std::mem::discriminant(&StateEvent::TrustCritical)));
-        valid.insert((NodeState::Degraded, std::mem::discriminant(&StateEvent::SecurityViolation { reason: String::new() })));
+        let degraded = NodeState::Degraded(DegradeReasons::default());
+        valid.insert((degraded, std::mem::discriminant(&StateEvent::TrustBelowWarn)));
+        valid.insert((degraded, std::mem::discriminant(&StateEvent::TrustRecovered)));
+        valid.insert((degraded, std::mem::discriminant(&StateEvent::TrustCritical)));
+        valid.insert((degraded, std::mem::discriminant(&StateEvent::ClockDriftCritical { drift_secs: 0 })));
+        valid.insert((degraded, std::mem::discriminant(&StateEvent::ClockRecovered)));
+        valid.insert((degraded, std::mem::discriminant(&StateEvent::SecurityViolation { reason: String::new() })));
         
         Self { valid }
     }
 
     pub fn is_valid(&self, state: NodeState, event: &StateEvent) -> bool {
+        // Valid events do not depend on why a node is degraded.
+        let state = match state {
+            NodeState::Degraded(_) => NodeState::Degraded(DegradeReasons::default()),
+            other => other,
+        };
         self.valid.contains(&(state, std::mem::discriminant(event)))
     }
 }
@@ -1,6 +1,6 @@
 //! State transition logic.
 
-use super::state::NodeState;
+use super::state::{DegradeReasons, NodeState};
 use super::event::StateEvent;
 use crate::error::{NodeError, Result};
 
@@ -25,14 +25,26 @@ pub fn transition(current: NodeState, event: &StateEvent) -> Result<NodeState> {
         (NodeState::Syncing, StateEvent::SecurityViolation { .. }) => NodeState::Quarantined,
         
         // ACTIVE transitions
-        (NodeState::Active, StateEvent::TrustBelowWarn) => NodeState::Degraded,
+        (NodeState::Active, StateEvent::TrustBelowWarn) => NodeState::Degraded(DegradeReasons::LOW_TRUST),
+        (NodeState::Active, StateEvent::ClockDriftCritical { .. }) => NodeState::Degraded(DegradeReasons::CLOCK_DRIFT),
         (NodeState::Active, StateEvent::TrustCritical) => NodeState::Quarantined,
         (NodeState::Active, StateEvent::SecurityViolation { .. }) => NodeState::Quarantined,
         
-        // DEGRADED transitions
-        (NodeState::Degraded, StateEvent::TrustRecovered) => NodeState::Active,
-        (NodeState::Degraded, StateEvent::TrustCritical) => NodeState::Quarantined,
-        (NodeState::Degraded, StateEvent::SecurityViolation { .. }) => NodeState::Quarantined,
+        // DEGRADED transitions: back to ACTIVE once no reason remains
+        (NodeState::Degraded(reasons), StateEvent::TrustBelowWarn) => {
+            NodeState::Degraded(DegradeReasons { low_trust: true, ..reasons })
+        }
+        (NodeState::Degraded(reasons), StateEvent::TrustRecovered) => {
+            degraded_or_active(DegradeReasons { low_trust: false, ..reasons })
+        }
+        (NodeState::Degraded(reasons), StateEvent::ClockDriftCritical { .. }) => {
+            NodeState::Degraded(DegradeReasons { clock_drift: true, ..reasons })
+        }
+        (NodeState::Degraded(reasons), StateEvent::ClockRecovered) => {
+            degraded_or_active(DegradeReasons { clock_drift: false, ..reasons })
+        }
+        (NodeState::Degraded(_), StateEvent::TrustCritical) => NodeState::Quarantined,
+        (NodeState::Degraded(_), StateEvent::SecurityViolation { .. }) => NodeState::Quarantined,
         
         // QUARANTINED - terminal state, no transitions out
         (NodeState::Quarantined, _) => {
@@ -53,3 +65,11 @@ pub fn transition(current: NodeState, event: &StateEvent) -> Result<NodeState> {
     
     Ok(next)
 }
+
+fn degraded_or_active(reasons: DegradeReasons) -> NodeState {
+    if reasons.is_empty() {
+        NodeState::Active
+    } else {
+        NodeState::Degraded(reasons)
+    }
+}
@@ -0,0 +1,533 @@
+//! Identity integration: scheduled key rotation.
+//!
+//! On each tick the node checks its own epoch against the rotation policy.
+//! When due it generates a new keypair, persists it, rotates the identity,
+//! saves the signed rotation log, moves the transport to the new epoch and
+//! queues the `RotationRequest` for gossip.
+//!
+//! Rotations received from peers are checked against the known key for
+//! that NodeId, then update it and rebind sessions, and are relayed
+//! onwards. Trust edges are keyed by NodeId and carry over unchanged.
+//! Contradicting rotations are reported as compromise.
+//!
+//! Compromise evidence, found locally or received from peers, is verified,
+//! reported once per key and gossiped as an EVIDENCE message. Timing
+//! evidence rests on observers' receive times, so it is only taken from
+//! observers we trust.
+//!
+//! The node's own keys are kept in the key registry alongside its peers',
+//! so certificates it signs itself, such as a published self-revocation,
+//! verify like anyone else's.
+//!
+//! The node announces its own epoch and key periodically and right after
+//! rotating. Verified announcements from peers feed the identity directory;
+//! new nodes and new epochs are relayed, refreshes are not.
+//!
+//! The key may instead live in an external signer (a PKCS#11 token or a
+//! signer process). The node cannot mint keys there, so scheduled
+//! rotations fail until the operator rotates the key on the signer.
+
+use opennet_core::NodeId;
+use opennet_core::types::Timestamp;
+use opennet_identity::rotation::{
+    PeerKeyRegistry, RotationLog, RotationOutcome, RotationPolicy, RotationRequest, RotationScheduler,
+};
+use opennet_identity::{
+    CompromiseEvidence, DetectorConfig, DirectoryUpdate, IdentityAnnouncement, IdentityDirectory, IdentityEvent,
+    IdentityWatcher, KeyPair, NodeIdentity, NodeSigner, Observation, SecureStorage,
+};
+use opennet_time::TimeEvent;
+use opennet_wire::messages::{AnnouncementMessage, EvidenceMessage, RotationMessage};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use crate::config::NodeConfig;
+use crate::error::{NodeError, Result};
+use crate::events::producer::EventProducer;
+use crate::fsm::StateEvent;
+use super::transport::TransportIntegration;
+use super::trust::TrustIntegration;
+
+/// On-disk location of the node's keys and rotation log.
+pub struct IdentityStore {
+    storage: Box<dyn SecureStorage + Send>,
+    key_name: String,
+    log_path: PathBuf,
+}
+
+impl IdentityStore {
+    /// Keys are stored as `<key_name>-<epoch>`; the log at `log_path`.
+    pub fn new(storage: Box<dyn SecureStorage + Send>, key_name: impl Into<String>, log_path: PathBuf) -> Self {
+        Self { storage, key_name: key_name.into(), log_path }
+    }
+
+    /// Name the key for `epoch` is stored under.
+    pub fn key_name(&self, epoch: u64) -> String {
+        format!("{}-{}", self.key_name, epoch)
+    }
+
+    /// Where the rotation log is kept.
+    pub fn log_path(&self) -> &Path {
+        &self.log_path
+    }
+
+    /// Whether an identity has been created here.
+    pub fn exists(&self) -> bool {
+        self.log_path.exists()
+    }
+
+    /// Read back and check the rotation log.
+    pub fn load_log(&self) -> Result<RotationLog> {
+        RotationLog::load_own(&self.log_path).map_err(identity_err)
+    }
+
+    /// Whether the key for `epoch` is stored.
+    pub fn has_key(&self, epoch: u64) -> bool {
+        self.storage.exists(&self.key_name(epoch))
+    }
+
+    /// Load the key for `epoch`.
+    pub fn load_key(&self, epoch: u64) -> Result<KeyPair> {
+        self.storage.load(&self.key_name(epoch)).map_err(identity_err)
+    }
+
+    /// Store the key for `epoch`.
+    pub fn store_key(&mut self, epoch: u64, keypair: &KeyPair) -> Result<()> {
+        let name = self.key_name(epoch);
+        self.storage.store(&name, keypair).map_err(persistence_err)
+    }
+}
+
+/// A rotation to send to peers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotationGossip {
+    pub message: RotationMessage,
+    /// Peer it came from, which need not get it back.
+    pub skip: Option<NodeId>,
+}
+
+/// Compromise evidence to send to peers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvidenceGossip {
+    pub message: EvidenceMessage,
+    /// Peer it came from, which need not get it back.
+    pub skip: Option<NodeId>,
+}
+
+/// An identity announcement to send to peers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnouncementGossip {
+    pub message: AnnouncementMessage,
+    /// Peer it came from, which need not get it back.
+    pub skip: Option<NodeId>,
+}
+
+/// Default interval between our own announcements.
+pub const ANNOUNCE_INTERVAL_SECS: u64 = 3600;
+
+pub struct IdentityIntegration {
+    identity: NodeIdentity,
+    scheduler: RotationScheduler,
+    store: Option<IdentityStore>,
+    watcher: IdentityWatcher,
+    peers: PeerKeyRegistry,
+    directory: IdentityDirectory,
+    announce_interval: u64,
+    last_announced: Option<u64>,
+    announcements: Vec<AnnouncementGossip>,
+    outbox: Vec<RotationGossip>,
+    evidence_outbox: Vec<EvidenceGossip>,
+    /// `(node, key)` pairs already reported compromised.
+    reported: BTreeSet<(NodeId, [u8; 32])>,
+    log_dirty: bool,
+    expired_reported: bool,
+    pending_events: Vec<StateEvent>,
+    /// The key is held by an external signer rather than `store`.
+    external_signer: bool,
+}
+
+impl IdentityIntegration {
+    /// Wrap an in-memory identity.
+    pub fn new(identity: NodeIdentity, policy: RotationPolicy) -> Self {
+        let mut peers = PeerKeyRegistry::new();
+        let log = identity.rotation_log();
+        peers.insert_known(*log.node_id(), log.base_epoch(), *log.base_key(), log.base_time());
+        for r in log.entries() {
+            peers.insert_known(r.node_id, r.new_epoch, r.new_public_key, r.timestamp.as_secs());
+        }
+        Self {
+            identity,
+            scheduler: RotationScheduler::new(policy),
+            store: None,
+            watcher: IdentityWatcher::new(),
+            peers,
+            directory: IdentityDirectory::default(),
+            announce_interval: ANNOUNCE_INTERVAL_SECS,
+            last_announced: None,
+            announcements: Vec::new(),
+            outbox: Vec::new(),
+            evidence_outbox: Vec::new(),
+            reported: BTreeSet::new(),
+            log_dirty: false,
+            expired_reported: false,
+            pending_events: Vec::new(),
+            external_signer: false,
+        }
+    }
+
+    /// Apply the rotation policy, detector limits and announcement
+    /// interval from the node configuration.
+    pub fn with_config(mut self, config: &NodeConfig) -> Self {
+        self.scheduler = RotationScheduler::new(config.rotation);
+        self.with_detector_config(config.compromise.clone())
+            .with_announce_interval(config.announce_interval_secs)
+    }
+
+    /// Bound the compromise detector's memory with `config`.
+    pub fn with_detector_config(mut self, config: DetectorConfig) -> Self {
+        self.watcher = IdentityWatcher::with_detector_config(config);
+        self
+    }
+
+    /// Announce our identity every `secs` seconds.
+    pub fn with_announce_interval(mut self, secs: u64) -> Self {
+        self.announce_interval = secs;
+        self
+    }
+
+    /// Persist future rotations to `store`, writing the current log now.
+    pub fn with_store(mut self, store: IdentityStore) -> Result<Self> {
+        self.identity.rotation_log().save(&store.log_path).map_err(persistence_err)?;
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    /// Start a new identity under `keypair` at `now_secs`, persisting its
+    /// key and log to `store`.
+    pub fn create(keypair: KeyPair, now_secs: u64, mut store: IdentityStore, policy: RotationPolicy) -> Result<Self> {
+        // Identities start at epoch 1; the key goes first, as in a rotation.
+        store.store_key(1, &keypair)?;
+        Self::new(NodeIdentity::new(keypair, now_secs), policy).with_store(store)
+    }
+
+    /// Start a new identity whose key is held by an external `signer`,
+    /// persisting its log to `store`.
+    pub fn create_with_signer(
+        signer: Box<dyn NodeSigner>,
+        now_secs: u64,
+        store: IdentityStore,
+        policy: RotationPolicy,
+    ) -> Result<Self> {
+        let mut integration = Self::new(NodeIdentity::new(signer, now_secs), policy).with_store(store)?;
+        integration.external_signer = true;
+        Ok(integration)
+    }
+
+    /// Restore the identity persisted in `store`.
+    pub fn open(store: IdentityStore, policy: RotationPolicy) -> Result<Self> {
+        let log = store.load_log()?;
+        let keypair = store.load_key(log.head_epoch())?;
+        let identity = NodeIdentity::from_log(keypair, log).map_err(identity_err)?;
+        let mut integration = Self::new(identity, policy);
+        integration.store = Some(store);
+        Ok(integration)
+    }
+
+    /// Restore the identity persisted in `store`, signing with an
+    /// external `signer` that must hold the log's head key.
+    pub fn open_with_signer(store: IdentityStore, signer: Box<dyn NodeSigner>, policy: RotationPolicy) -> Result<Self> {
+        let log = store.load_log()?;
+        let identity = NodeIdentity::from_log(signer, log).map_err(identity_err)?;
+        let mut integration = Self::new(identity, policy);
+        integration.store = Some(store);
+        integration.external_signer = true;
+        Ok(integration)
+    }
+
+    pub fn identity(&self) -> &NodeIdentity {
+        &self.identity
+    }
+
+    /// Rotate if the policy says so. Returns the new rotation, if any.
+    pub fn tick(&mut self, now_secs: u64, transport: &mut TransportIntegration) -> Result<Option<RotationRequest>> {
+        if self.log_dirty {
+            self.save_log()?;
+        }
+        self.watcher.detector_mut().prune(now_secs);
+        self.directory.expire(now_secs);
+        if self.scheduler.is_due(self.identity.epoch(), now_secs) {
+            let result = self.rotate_now(now_secs, transport);
+            if result.is_err() {
+                self.check_expired(now_secs);
+            }
+            return result.map(Some);
+        }
+        self.check_expired(now_secs);
+        if self.last_announced.is_none_or(|last| now_secs >= last + self.announce_interval) {
+            self.announce(now_secs)?;
+        }
+        Ok(None)
+    }
+
+    /// React to time events; an expiring epoch forces a rotation attempt.
+    ///
+    /// Warnings are only acted on while they still describe our current
+    /// epoch: one raised before we rotated leaves far more time on the new
+    /// epoch than it reports, and is ignored.
+    pub fn on_time_event(
+        &mut self,
+        event: &TimeEvent,
+        now_secs: u64,
+        transport: &mut TransportIntegration,
+    ) -> Result<Option<RotationRequest>> {
+        match event {
+            TimeEvent::EpochExpiringSoon { remaining_secs }
+                if self.is_expiring(*remaining_secs, now_secs) && self.scheduler.may_retry(now_secs) =>
+            {
+                self.rotate_now(now_secs, transport).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Rotate to a freshly generated key.
+    ///
+    /// Failing to save the rotation log does not fail the rotation, which
+    /// has already taken effect; the save is retried by [`tick`](Self::tick),
+    /// which returns [`NodeError::PersistenceError`] until it succeeds.
+    pub fn rotate_now(&mut self, now_secs: u64, transport: &mut TransportIntegration) -> Result<RotationRequest> {
+        match self.rotate_inner(now_secs, transport) {
+            Ok(request) => {
+                self.scheduler.record_success();
+                Ok(request)
+            }
+            Err(e) => {
+                self.scheduler.record_failure(now_secs);
+                Err(e)
+            }
+        }
+    }
+
+    /// Known keys of other nodes, and our own.
+    pub fn peers(&mut self) -> &mut PeerKeyRegistry {
+        &mut self.peers
+    }
+
+    /// Handle a ROTATION message received from `from`.
+    pub fn on_rotation_message(
+        &mut self,
+        from: NodeId,
+        message: RotationMessage,
+        transport: &mut TransportIntegration,
+    ) -> Result<RotationOutcome> {
+        let request = RotationRequest::from(message.clone());
+        let outcome = self.peers.apply(&request).map_err(identity_err)?;
+        match &outcome {
+            RotationOutcome::Accepted { node_id, to_epoch, .. } => {
+                self.directory.on_rotation(*node_id, *to_epoch, request.new_public_key, request.timestamp.as_secs());
+                transport.on_peer_rotation(node_id, *to_epoch);
+                self.outbox.push(RotationGossip { message, skip: Some(from) });
+            }
+            RotationOutcome::Conflict { evidence, .. } => {
+                self.report(evidence.as_ref().clone(), None);
+            }
+            RotationOutcome::Duplicate => {}
+        }
+        Ok(outcome)
+    }
+
+    /// Verified identities of other nodes.
+    pub fn directory(&self) -> &IdentityDirectory {
+        &self.directory
+    }
+
+    /// Handle an ANNOUNCEMENT message received from `from`.
+    pub fn on_announcement_message(
+        &mut self,
+        from: NodeId,
+        message: AnnouncementMessage,
+        now_secs: u64,
+    ) -> Result<DirectoryUpdate> {
+        let announcement = IdentityAnnouncement::decode(&message.announcement).map_err(identity_err)?;
+        if announcement.node_id != message.node_id {
+            return Err(NodeError::IdentityError("announcement is for a different node".into()));
+        }
+        if &announcement.node_id == self.identity.node_id() {
+            return Ok(DirectoryUpdate::Stale);
+        }
+        let update = self.directory.apply(&announcement, &self.peers, now_secs).map_err(identity_err)?;
+        if matches!(update, DirectoryUpdate::New | DirectoryUpdate::EpochAdvanced { .. }) {
+            self.peers.insert_known(
+                announcement.node_id,
+                announcement.epoch.id,
+                announcement.public_key,
+                announcement.epoch.start_time,
+            );
+            self.announcements.push(AnnouncementGossip { message, skip: Some(from) });
+        }
+        Ok(update)
+    }
+
+    /// Announcements to gossip, our own and relayed.
+    pub fn drain_announcements(&mut self) -> Vec<AnnouncementGossip> {
+        std::mem::take(&mut self.announcements)
+    }
+
+    /// Check a peer's observation of another node's claim.
+    ///
+    /// The claim is checked against the key the registry knows for its
+    /// epoch; unknown nodes and epochs, and observers without trust, are
+    /// ignored.
+    pub fn on_observation(
+        &mut self,
+        observation: Observation,
+        now_secs: u64,
+        trust: &TrustIntegration,
+    ) -> Result<Option<CompromiseEvidence>> {
+        let claim = &observation.claim;
+        let Some(key) = self.peers.key_at(&claim.node_id, claim.epoch) else {
+            return Ok(None);
+        };
+        if !trust.is_trusted(&observation.observer) {
+            return Ok(None);
+        }
+        let evidence = self
+            .watcher
+            .detector_mut()
+            .record_observation(observation, &key, &self.peers, now_secs)
+            .map_err(identity_err)?;
+        Ok(evidence.and_then(|e| self.report(e, None)))
+    }
+
+    /// Handle an EVIDENCE message received from `from`.
+    ///
+    /// Returns the evidence if it verified and was new; it is then
+    /// reported and relayed to other peers. Timing evidence is accepted
+    /// only if we trust both observers.
+    pub fn on_evidence_message(
+        &mut self,
+        from: NodeId,
+        message: EvidenceMessage,
+        trust: &TrustIntegration,
+    ) -> Result<Option<CompromiseEvidence>> {
+        let evidence = CompromiseEvidence::decode(&message.bundle).map_err(identity_err)?;
+        if evidence.node_id() != &message.node_id {
+            return Err(NodeError::IdentityError("evidence is about a different node".into()));
+        }
+        if let CompromiseEvidence::ImpossibleTiming { first, second, .. } = &evidence {
+            if !trust.is_trusted(&first.observer) || !trust.is_trusted(&second.observer) {
+                return Err(NodeError::IdentityError("timing evidence from untrusted observers".into()));
+            }
+        }
+        evidence.verify(&self.peers).map_err(identity_err)?;
+        // The bundle proves the key misbehaved; it must also be the node's key.
+        let key = evidence.public_key();
+        if NodeId::from_public_key(key.as_bytes()) != message.node_id && !self.peers.knows_key(&message.node_id, key) {
+            return Err(NodeError::IdentityError("evidence key is not bound to the node".into()));
+        }
+        Ok(self.report(evidence, Some(from)))
+    }
+
+    /// Evidence to gossip, our own and relayed.
+    pub fn drain_evidence(&mut self) -> Vec<EvidenceGossip> {
+        std::mem::take(&mut self.evidence_outbox)
+    }
+
+    /// Rotations to gossip, our own and relayed.
+    pub fn drain_rotations(&mut self) -> Vec<RotationGossip> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Identity events (rotation completed, compromise detected).
+    pub fn drain_identity_events(&mut self) -> Vec<IdentityEvent> {
+        self.watcher.drain_events()
+    }
+
+    fn rotate_inner(&mut self, now_secs: u64, transport: &mut TransportIntegration) -> Result<RotationRequest> {
+        if self.external_signer {
+            return Err(NodeError::IdentityError("the key is held by an external signer; rotate it there".into()));
+        }
+        let keypair = KeyPair::random();
+        let new_epoch = self.identity.epoch_id() + 1;
+        // Persist the key first: a crash before the log is saved leaves an
+        // unused key file rather than a log pointing at a missing key.
+        if let Some(store) = &mut self.store {
+            let name = store.key_name(new_epoch);
+            store.storage.store(&name, &keypair).map_err(identity_err)?;
+        }
+        let request = self.identity.rotate(keypair, now_secs).map_err(identity_err)?;
+        self.peers.insert_known(request.node_id, new_epoch, request.new_public_key, now_secs);
+        self.log_dirty = true;
+        self.expired_reported = false;
+        transport.set_local_epoch(new_epoch);
+        self.watcher.on_rotation(new_epoch);
+        self.outbox.push(RotationGossip { message: request.clone().into(), skip: None });
+        // The log stays dirty on failure and is saved on the next tick.
+        let _ = self.save_log();
+        // The rotation stands even if announcing fails; retry on next tick.
+        if self.announce(now_secs).is_err() {
+            self.last_announced = None;
+        }
+        Ok(request)
+    }
+
+    /// Report and queue evidence unless its key was already reported.
+    fn report(&mut self, evidence: CompromiseEvidence, from: Option<NodeId>) -> Option<CompromiseEvidence> {
+        if self.queue_evidence(&evidence, from) {
+            self.watcher.on_compromise(*evidence.node_id(), evidence.clone());
+            return Some(evidence);
+        }
+        None
+    }
+
+    fn queue_evidence(&mut self, evidence: &CompromiseEvidence, from: Option<NodeId>) -> bool {
+        let node_id = *evidence.node_id();
+        if !self.reported.insert((node_id, *evidence.public_key().as_bytes())) {
+            return false;
+        }
+        let message = EvidenceMessage { node_id, kind: evidence.kind(), bundle: evidence.encode() };
+        self.evidence_outbox.push(EvidenceGossip { message, skip: from });
+        true
+    }
+
+    fn announce(&mut self, now_secs: u64) -> Result<()> {
+        let announcement =
+            IdentityAnnouncement::sign(&self.identity, Timestamp::new(now_secs)).map_err(identity_err)?;
+        let message = AnnouncementMessage { node_id: *self.identity.node_id(), announcement: announcement.encode() };
+        self.announcements.push(AnnouncementGossip { message, skip: None });
+        self.last_announced = Some(now_secs);
+        Ok(())
+    }
+
+    fn save_log(&mut self) -> Result<()> {
+        if let Some(store) = &self.store {
+            self.identity.rotation_log().save(&store.log_path).map_err(persistence_err)?;
+        }
+        self.log_dirty = false;
+        Ok(())
+    }
+
+    fn is_expiring(&self, remaining_secs: u64, now_secs: u64) -> bool {
+        self.identity.epoch().end_time().saturating_sub(now_secs) <= remaining_secs
+    }
+
+    fn check_expired(&mut self, now_secs: u64) {
+        if !self.expired_reported && now_secs >= self.identity.epoch().end_time() {
+            self.expired_reported = true;
+            self.pending_events.push(StateEvent::EpochExpired);
+        }
+    }
+}
+
+impl EventProducer for IdentityIntegration {
+    fn drain_events(&mut self) -> Vec<StateEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+}
+
+fn identity_err(e: opennet_identity::IdentityError) -> NodeError {
+    NodeError::IdentityError(e.to_string())
+}
+
+fn persistence_err(e: opennet_identity::IdentityError) -> NodeError {
+    NodeError::PersistenceError(e.to_string())
+}
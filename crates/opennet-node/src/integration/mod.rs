@@ -4,5 +4,11 @@ pub mod resolver;
 pub mod trust;
 pub mod transport;
 pub mod pipeline;
+pub mod identity;
+pub mod time;
+pub mod revocation;
 
 pub use pipeline::RequestPipeline;
+pub use time::TimeIntegration;
+pub use revocation::{GuardianSetGossip, RecoveryGossip, RevocationGossip, RevocationIntegration};
+pub use identity::{AnnouncementGossip, EvidenceGossip, IdentityIntegration, IdentityStore, RotationGossip};
@@ -0,0 +1,275 @@
+//! Revocation integration: verifying, recording and gossiping revocations.
+//!
+//! A revocation is accepted if it carries a valid self-signature by the
+//! revoked epoch's key or enough of the network's trust signed it. Each
+//! (node, epoch) is recorded and relayed once, and marked revoked in the
+//! [`PeerKeyRegistry`] so its key is no longer accepted.
+//!
+//! The CLI publishes a self-revocation certificate by dropping it into a
+//! spool directory (`<data>/revocations`); [`RevocationIntegration::import_spool`] picks it up,
+//! verifies it like any other revocation and removes the file once it
+//! has been accepted.
+//!
+//! Guardian sets and recovery certificates are gossiped alongside. A set
+//! is checked against its owner's key for the epoch that signed it, and
+//! frozen once that epoch is revoked. A recovery certificate is validated
+//! against the registered set with the guardians' current keys, then
+//! installs the recovered key in the [`PeerKeyRegistry`]. Each is relayed
+//! once.
+
+use opennet_core::NodeId;
+use opennet_identity::rotation::PeerKeyRegistry;
+use opennet_revocation::quorum::{Authorization, QuorumValidator, ValidatedRevocation};
+use opennet_revocation::recovery::{GuardianRegistry, GuardianSet, RecoveryCertificate, ValidatedRecovery};
+use opennet_revocation::revocation::certificate::CERTIFICATE_EXTENSION;
+use opennet_revocation::revocation::RevocationObject;
+use opennet_trust::graph::TrustGraph;
+use opennet_wire::messages::{GuardianSetMessage, RecoveryMessage, RevocationMessage};
+use std::collections::BTreeMap;
+use std::path::Path;
+use crate::error::{NodeError, Result};
+
+/// A revocation to send to peers.
+#[derive(Debug, Clone)]
+pub struct RevocationGossip {
+    /// The revocation.
+    pub message: RevocationMessage,
+    /// Peer it came from, which need not get it back.
+    pub skip: Option<NodeId>,
+}
+
+/// A guardian set to send to peers.
+#[derive(Debug, Clone)]
+pub struct GuardianSetGossip {
+    /// The guardian set.
+    pub message: GuardianSetMessage,
+    /// Peer it came from, which need not get it back.
+    pub skip: Option<NodeId>,
+}
+
+/// A recovery certificate to send to peers.
+#[derive(Debug, Clone)]
+pub struct RecoveryGossip {
+    /// The certificate.
+    pub message: RecoveryMessage,
+    /// Peer it came from, which need not get it back.
+    pub skip: Option<NodeId>,
+}
+
+/// Accepted revocations and recoveries and the gossip they produce.
+pub struct RevocationIntegration {
+    validator: QuorumValidator,
+    revoked: BTreeMap<(NodeId, u64), ValidatedRevocation>,
+    guardians: GuardianRegistry,
+    outbox: Vec<RevocationGossip>,
+    guardian_outbox: Vec<GuardianSetGossip>,
+    recovery_outbox: Vec<RecoveryGossip>,
+}
+
+impl RevocationIntegration {
+    /// Accept quorum revocations signed by `validator`'s threshold.
+    pub fn new(validator: QuorumValidator) -> Self {
+        Self {
+            validator,
+            revoked: BTreeMap::new(),
+            guardians: GuardianRegistry::new(),
+            outbox: Vec::new(),
+            guardian_outbox: Vec::new(),
+            recovery_outbox: Vec::new(),
+        }
+    }
+
+    /// Verify and record a revocation from `from`, or our own if `None`.
+    ///
+    /// Returns what authorized it if it was new; it is then marked in
+    /// `keys` and queued for gossip. Revocations already recorded return
+    /// `None`.
+    pub fn submit(
+        &mut self,
+        obj: RevocationObject,
+        from: Option<NodeId>,
+        keys: &mut PeerKeyRegistry,
+        graph: &TrustGraph,
+    ) -> Result<Option<Authorization>> {
+        let key = (obj.node_id, obj.revoked_epoch);
+        if self.revoked.contains_key(&key) {
+            return Ok(None);
+        }
+        let validated = self.validator.verify(obj, keys, graph).map_err(revocation_err)?;
+        let message = validated.object().to_message().map_err(revocation_err)?;
+        let authorization = validated.authorization();
+        keys.revoke(key.0, key.1);
+        self.guardians.on_revocation(key.0, key.1);
+        self.revoked.insert(key, validated);
+        self.outbox.push(RevocationGossip { message, skip: from });
+        Ok(Some(authorization))
+    }
+
+    /// Handle a REVOCATION message received from `from`.
+    pub fn on_revocation_message(
+        &mut self,
+        from: NodeId,
+        message: &RevocationMessage,
+        keys: &mut PeerKeyRegistry,
+        graph: &TrustGraph,
+    ) -> Result<Option<Authorization>> {
+        self.submit(RevocationObject::from_message(message), Some(from), keys, graph)
+    }
+
+    /// Submit every certificate in `dir`, removing those accepted or
+    /// already known. Files that fail to decode or verify are logged and
+    /// left for the operator. Returns how many new revocations were accepted.
+    pub fn import_spool(&mut self, dir: &Path, keys: &mut PeerKeyRegistry, graph: &TrustGraph) -> Result<usize> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(spool_err(e)),
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == CERTIFICATE_EXTENSION))
+            .collect();
+        paths.sort();
+
+        let mut accepted = 0;
+        for path in paths {
+            let bytes = std::fs::read(&path).map_err(spool_err)?;
+            let obj = match RevocationObject::decode(&bytes) {
+                Ok(obj) => obj,
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "undecodable revocation certificate left in spool");
+                    continue;
+                }
+            };
+            match self.submit(obj, None, keys, graph) {
+                Ok(new) => {
+                    accepted += usize::from(new.is_some());
+                    std::fs::remove_file(&path).map_err(spool_err)?;
+                }
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "rejected revocation certificate left in spool");
+                }
+            }
+        }
+        Ok(accepted)
+    }
+
+    /// Check whether `node_id`'s epoch `epoch` has been revoked.
+    pub fn is_revoked(&self, node_id: &NodeId, epoch: u64) -> bool {
+        self.revoked.contains_key(&(*node_id, epoch))
+    }
+
+    /// Accepted revocation for `node_id`'s epoch `epoch`.
+    pub fn get(&self, node_id: &NodeId, epoch: u64) -> Option<&ValidatedRevocation> {
+        self.revoked.get(&(*node_id, epoch))
+    }
+
+    /// Revocations to gossip, our own and relayed.
+    pub fn drain_revocations(&mut self) -> Vec<RevocationGossip> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Register a guardian set from `from`, or our own if `None`.
+    ///
+    /// The set must verify under its owner's key for the epoch that
+    /// signed it, as known to `keys`. Returns whether it was new; new
+    /// sets are queued for gossip.
+    pub fn submit_guardian_set(&mut self, set: GuardianSet, from: Option<NodeId>, keys: &PeerKeyRegistry) -> Result<bool> {
+        if self.guardians.get(&set.node_id) == Some(&set) {
+            return Ok(false);
+        }
+        let owner_key = keys
+            .key_at(&set.node_id, set.epoch)
+            .ok_or_else(|| NodeError::RevocationError(format!("no key known for epoch {} of the owner", set.epoch)))?;
+        let message = GuardianSetMessage { node_id: set.node_id, set: set.encode() };
+        self.guardians.register(set, &owner_key).map_err(revocation_err)?;
+        self.guardian_outbox.push(GuardianSetGossip { message, skip: from });
+        Ok(true)
+    }
+
+    /// Handle a GUARDIAN_SET message received from `from`.
+    pub fn on_guardian_set_message(
+        &mut self,
+        from: NodeId,
+        message: &GuardianSetMessage,
+        keys: &PeerKeyRegistry,
+    ) -> Result<bool> {
+        let set = GuardianSet::decode(&message.set).map_err(identity_err)?;
+        if set.node_id != message.node_id {
+            return Err(NodeError::RevocationError("guardian set is for a different node".into()));
+        }
+        self.submit_guardian_set(set, Some(from), keys)
+    }
+
+    /// Validate a recovery certificate from `from`, or our own if `None`,
+    /// against the node's registered guardian set.
+    ///
+    /// Returns the recovery if it was new; its key is then installed in
+    /// `keys` and the certificate queued for gossip.
+    pub fn submit_recovery(
+        &mut self,
+        certificate: RecoveryCertificate,
+        from: Option<NodeId>,
+        keys: &mut PeerKeyRegistry,
+    ) -> Result<Option<ValidatedRecovery>> {
+        let guardians = self
+            .guardians
+            .get(certificate.node_id())
+            .ok_or_else(|| NodeError::RevocationError("no guardian set registered for the node".into()))?;
+        let recovery = certificate.validate(guardians, keys).map_err(identity_err)?;
+        let since = recovery.certificate().request.timestamp.as_secs();
+        if !keys.apply_recovery(&recovery, since).map_err(identity_err)? {
+            return Ok(None);
+        }
+        let message = RecoveryMessage { node_id: *recovery.node_id(), certificate: recovery.certificate().encode() };
+        self.recovery_outbox.push(RecoveryGossip { message, skip: from });
+        Ok(Some(recovery))
+    }
+
+    /// Handle a RECOVERY message received from `from`.
+    pub fn on_recovery_message(
+        &mut self,
+        from: NodeId,
+        message: &RecoveryMessage,
+        keys: &mut PeerKeyRegistry,
+    ) -> Result<Option<ValidatedRecovery>> {
+        let certificate = RecoveryCertificate::decode(&message.certificate).map_err(identity_err)?;
+        if certificate.node_id() != &message.node_id {
+            return Err(NodeError::RevocationError("recovery is for a different node".into()));
+        }
+        self.submit_recovery(certificate, Some(from), keys)
+    }
+
+    /// Registered guardian sets.
+    pub fn guardians(&self) -> &GuardianRegistry {
+        &self.guardians
+    }
+
+    /// Guardian sets to gossip, our own and relayed.
+    pub fn drain_guardian_sets(&mut self) -> Vec<GuardianSetGossip> {
+        std::mem::take(&mut self.guardian_outbox)
+    }
+
+    /// Recovery certificates to gossip, our own and relayed.
+    pub fn drain_recoveries(&mut self) -> Vec<RecoveryGossip> {
+        std::mem::take(&mut self.recovery_outbox)
+    }
+}
+
+impl Default for RevocationIntegration {
+    fn default() -> Self {
+        Self::new(QuorumValidator::default())
+    }
+}
+
+fn revocation_err(e: opennet_revocation::RevocationError) -> NodeError {
+    NodeError::RevocationError(e.to_string())
+}
+
+fn identity_err(e: opennet_identity::IdentityError) -> NodeError {
+    NodeError::RevocationError(e.to_string())
+}
+
+fn spool_err(e: std::io::Error) -> NodeError {
+    NodeError::RevocationError(format!("revocation spool: {}", e))
+}
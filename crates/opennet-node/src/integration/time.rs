@@ -0,0 +1,171 @@
+//! Time integration.
+//!
+//! Peer timestamps also feed a drift monitor against the corrected clock,
+//! one sample per trusted peer. Critical drift moves the node to DEGRADED;
+//! it returns to ACTIVE once drift is back within tolerance and nothing
+//! else keeps it degraded.
+
+use opennet_core::{EpochId, NodeId};
+use opennet_core::types::Timestamp;
+use opennet_time::drift::{DriftMonitor, DriftTolerance};
+use opennet_time::nmt::{NmtClock, NmtConfig};
+use opennet_time::replay::{ReplayCache, ReplayStore, SequenceCounter};
+use opennet_time::replay::persist::DEFAULT_SEQUENCE_RESERVE;
+use std::path::Path;
+use opennet_time::{MonotonicClock, SystemClock, TimeEvent};
+use opennet_trust::weight::TrustWeight;
+use crate::events::producer::EventProducer;
+use crate::fsm::StateEvent;
+use super::trust::TrustIntegration;
+
+/// File holding the replay cache inside the state directory.
+pub const REPLAY_STATE_FILE: &str = "replay.cbor";
+
+/// File holding the outgoing sequence high-water mark.
+pub const SEQUENCE_STATE_FILE: &str = "sequence";
+
+/// Keeps the node's Network Median Time clock fed from peer messages,
+/// screens those messages for replays and numbers our own messages.
+pub struct TimeIntegration<C: MonotonicClock = SystemClock> {
+    clock: NmtClock<C>,
+    replay: ReplayCache,
+    store: Option<ReplayStore>,
+    sequences: Option<SequenceCounter>,
+    next_sequence: u64,
+    drift: DriftMonitor,
+    pending_events: Vec<StateEvent>,
+}
+
+impl<C: MonotonicClock> TimeIntegration<C> {
+    /// Correct `local` with the given NMT settings. State is kept in
+    /// memory only; see [`with_state_dir`](Self::with_state_dir).
+    pub fn new(local: C, config: NmtConfig) -> Self {
+        Self {
+            clock: NmtClock::with_config(local, config),
+            replay: ReplayCache::default(),
+            store: None,
+            sequences: None,
+            next_sequence: 1,
+            drift: DriftMonitor::default(),
+            pending_events: Vec::new(),
+        }
+    }
+
+    /// Use non-default drift thresholds.
+    pub fn with_drift_tolerance(mut self, tolerance: DriftTolerance) -> Self {
+        self.drift = DriftMonitor::new(tolerance);
+        self
+    }
+
+    /// Restore replay state and the sequence counter from `dir`, and keep
+    /// them there.
+    pub fn with_state_dir(mut self, dir: &Path) -> opennet_time::Result<Self> {
+        let store = ReplayStore::new(dir.join(REPLAY_STATE_FILE));
+        self.replay = store.load(self.replay.window_secs(), self.replay.max_senders(), self.clock.now())?;
+        self.store = Some(store);
+        self.sequences = Some(SequenceCounter::open(dir.join(SEQUENCE_STATE_FILE), DEFAULT_SEQUENCE_RESERVE)?);
+        Ok(self)
+    }
+
+    /// Sequence number for our next outgoing message.
+    pub fn next_sequence(&mut self) -> opennet_time::Result<u64> {
+        if let Some(counter) = &mut self.sequences {
+            return counter.allocate();
+        }
+        self.next_sequence += 1;
+        Ok(self.next_sequence - 1)
+    }
+
+    /// Use a replay cache with non-default limits.
+    pub fn with_replay_cache(mut self, replay: ReplayCache) -> Self {
+        self.replay = replay;
+        self
+    }
+
+    /// Accept a sequenced message once, against the corrected clock.
+    pub fn check_replay(
+        &mut self,
+        from: NodeId,
+        epoch: EpochId,
+        sequence: u64,
+        timestamp: Timestamp,
+    ) -> opennet_time::Result<()> {
+        let now = self.clock.now();
+        self.replay.check_and_record(from, epoch, sequence, timestamp, now)
+    }
+
+    /// Periodic housekeeping: drop expired replay state and save it.
+    pub fn tick(&mut self) -> opennet_time::Result<()> {
+        let now = self.clock.now();
+        self.replay.expire(now);
+        self.save(false)
+    }
+
+    /// Save state for an orderly shutdown.
+    pub fn shutdown(&mut self) -> opennet_time::Result<()> {
+        self.save(true)
+    }
+
+    fn save(&self, clean: bool) -> opennet_time::Result<()> {
+        match &self.store {
+            Some(store) => store.save(&self.replay, self.clock.now(), clean),
+            None => Ok(()),
+        }
+    }
+
+    /// Replay state.
+    pub fn replay_cache(&self) -> &ReplayCache {
+        &self.replay
+    }
+
+    /// Sample the timestamp of an authenticated message from `from`,
+    /// weighted by the current trust in the sender and grouped by its
+    /// trust neighborhood, and check it for drift.
+    pub fn on_peer_timestamp(&mut self, from: NodeId, timestamp: Timestamp, trust: &TrustIntegration) -> Option<i64> {
+        let graph = trust.graph();
+        let weight = graph.get_weight(&from).unwrap_or(TrustWeight::ZERO);
+        let target = self.clock.record_sample(from, timestamp, weight, graph.neighborhood(&from));
+        if let Some(event) = self.drift.observe(from, weight, self.clock.now(), timestamp) {
+            self.on_time_event(&event);
+        }
+        target
+    }
+
+    /// Turn time events into FSM events.
+    pub fn on_time_event(&mut self, event: &TimeEvent) {
+        match event {
+            TimeEvent::DriftCritical { drift_secs } => {
+                self.pending_events.push(StateEvent::ClockDriftCritical { drift_secs: *drift_secs });
+            }
+            TimeEvent::DriftRecovered { .. } => self.pending_events.push(StateEvent::ClockRecovered),
+            _ => {}
+        }
+    }
+
+    /// Drift state.
+    pub fn drift(&self) -> &DriftMonitor {
+        &self.drift
+    }
+
+    /// Corrected current time.
+    pub fn now_secs(&self) -> u64 {
+        self.clock.now().as_secs()
+    }
+
+    /// The corrected clock.
+    pub fn clock(&self) -> &NmtClock<C> {
+        &self.clock
+    }
+}
+
+impl<C: MonotonicClock> EventProducer for TimeIntegration<C> {
+    fn drain_events(&mut self) -> Vec<StateEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+}
+
+impl Default for TimeIntegration {
+    fn default() -> Self {
+        Self::new(SystemClock, NmtConfig::default())
+    }
+}
@@ -1,20 +1,140 @@
 //! Transport integration.
+//!
+//! Inbound work passes admission control in the order it arrives: a
+//! NodeHello is admitted on its source prefix before it is authenticated,
+//! the peer's own handshake allowance is charged once its signature has
+//! verified, and streams and messages on the resulting session are
+//! admitted per peer. Penalties for peers that keep exceeding their limits
+//! are applied to the trust graph as they occur.
+//!
+//! Sessions are never opened for a revoked (node, epoch), and are closed
+//! once a revocation for their epoch is accepted.
 
 use opennet_core::NodeId;
-use opennet_transport::session::SessionManager;
+use opennet_identity::rotation::PeerKeyRegistry;
+use opennet_transport::admission::{AdmissionControl, ChallengeResponse, RateLimitConfig};
+use opennet_transport::session::{SessionBinding, SessionManager};
+use opennet_transport::{Result, TransportError};
+use std::net::IpAddr;
+use crate::config::NodeConfig;
+use super::trust::TrustIntegration;
+
+/// Trust weight below which peers are not connected to.
+const DEFAULT_CONNECT_THRESHOLD: f64 = 0.3;
 
 pub struct TransportIntegration {
     sessions: SessionManager,
+    admission: AdmissionControl,
+    local_epoch: u64,
 }
 
 impl TransportIntegration {
     pub fn new() -> Self {
-        Self { sessions: SessionManager::new() }
+        Self::from_config(&NodeConfig::default())
+    }
+
+    /// Create with the node's configured admission limits.
+    pub fn from_config(config: &NodeConfig) -> Self {
+        Self::with_admission(DEFAULT_CONNECT_THRESHOLD, config.admission.clone())
+    }
+
+    /// Create with configured admission limits.
+    pub fn with_admission(threshold: f64, limits: RateLimitConfig) -> Self {
+        Self {
+            sessions: SessionManager::new(),
+            admission: AdmissionControl::with_config(threshold, limits),
+            local_epoch: 1,
+        }
+    }
+
+    pub fn admission(&mut self) -> &mut AdmissionControl {
+        &mut self.admission
+    }
+
+    /// Admit a NodeHello from `remote` before it is authenticated.
+    ///
+    /// Under load this fails with `ChallengeRequired`; the challenge goes
+    /// back to the peer, whose next hello carries the `response`.
+    pub fn admit_hello(&mut self, remote: IpAddr, now_ms: u64, response: Option<&ChallengeResponse>) -> Result<()> {
+        self.admission.admit_handshake(remote, now_ms, response)
+    }
+
+    /// Open a session with a peer whose handshake signature verified,
+    /// unless its epoch has been revoked.
+    pub fn accept_session(
+        &mut self,
+        binding: SessionBinding,
+        session_id: u64,
+        now_ms: u64,
+        trust: &mut TrustIntegration,
+        keys: &PeerKeyRegistry,
+    ) -> Result<()> {
+        if keys.is_revoked(&binding.node_id, binding.epoch_id) {
+            return Err(TransportError::HandshakeFailed(format!("epoch {} is revoked", binding.epoch_id)));
+        }
+        let weight = trust.get_trust(&binding.node_id);
+        let admitted = self.admission.admit_peer(&binding.node_id, weight, now_ms);
+        self.forward_penalties(trust);
+        admitted?;
+        self.sessions.register(binding, session_id);
+        Ok(())
+    }
+
+    /// Admit a new stream from a peer with an open session.
+    pub fn on_stream_open(&mut self, node_id: &NodeId, remote: IpAddr, now_ms: u64, trust: &mut TrustIntegration) -> Result<()> {
+        if !self.sessions.has_peer(node_id) {
+            return Err(TransportError::SessionInvalid);
+        }
+        let admitted = self.admission.admit_stream(node_id, remote, trust.get_trust(node_id), now_ms);
+        self.forward_penalties(trust);
+        admitted
+    }
+
+    /// Admit a message from a peer with an open session.
+    pub fn on_message(&mut self, node_id: &NodeId, remote: IpAddr, now_ms: u64, trust: &mut TrustIntegration) -> Result<()> {
+        if !self.sessions.has_peer(node_id) {
+            return Err(TransportError::SessionInvalid);
+        }
+        let admitted = self.admission.admit_message(node_id, remote, trust.get_trust(node_id), now_ms);
+        self.forward_penalties(trust);
+        admitted
+    }
+
+    /// Sessions currently open.
+    pub fn sessions(&self) -> &SessionManager {
+        &self.sessions
+    }
+
+    /// Epoch new handshakes are bound to on our side.
+    pub fn local_epoch(&self) -> u64 {
+        self.local_epoch
+    }
+
+    /// Switch to a new local epoch after rotating keys.
+    pub fn set_local_epoch(&mut self, epoch: u64) {
+        self.local_epoch = epoch;
+    }
+
+    /// Close sessions bound to revoked epochs. Returns how many were closed.
+    pub fn close_revoked(&mut self, keys: &PeerKeyRegistry) -> usize {
+        self.sessions.remove_where(|b| keys.is_revoked(&b.node_id, b.epoch_id))
+    }
+
+    /// Move a peer's session to its new epoch after it rotated keys,
+    /// closing older ones. Returns how many were rebound.
+    pub fn on_peer_rotation(&mut self, node_id: &NodeId, new_epoch: u64) -> usize {
+        self.sessions.rebind(node_id, new_epoch)
     }
 
     pub async fn connect(&mut self, _node_id: &NodeId) -> bool {
         true
     }
+
+    fn forward_penalties(&mut self, trust: &mut TrustIntegration) {
+        for event in self.admission.drain_events() {
+            trust.on_negative_event(&event);
+        }
+    }
 }
 
 impl Default for TransportIntegration {
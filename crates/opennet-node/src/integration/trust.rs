@@ -1,7 +1,9 @@
 //! Trust integration.
 
 use opennet_core::NodeId;
+use opennet_trust::events::negative::NegativeEvent;
 use opennet_trust::graph::TrustGraph;
+use opennet_trust::weight::TrustWeight;
 
 pub struct TrustIntegration {
     graph: TrustGraph,
@@ -18,9 +20,27 @@ impl TrustIntegration {
             .unwrap_or(0.0)
     }
 
+    /// Whether `node_id` carries any weight in the trust graph.
+    pub fn is_trusted(&self, node_id: &NodeId) -> bool {
+        self.graph.get_weight(node_id).is_some_and(|w| w > TrustWeight::ZERO)
+    }
+
+    /// Reduce a node's weight by the event's penalty share. Returns the
+    /// new weight.
+    pub fn on_negative_event(&mut self, event: &NegativeEvent) -> TrustWeight {
+        let weight = self.graph.get_weight(&event.node_id).unwrap_or(TrustWeight::INITIAL);
+        let reduced = weight.sub(weight.mul(event.penalty));
+        self.graph.upsert_node(event.node_id, reduced);
+        reduced
+    }
+
     pub fn graph(&self) -> &TrustGraph {
         &self.graph
     }
+
+    pub fn graph_mut(&mut self) -> &mut TrustGraph {
+        &mut self.graph
+    }
 }
 
 impl Default for TrustIntegration {
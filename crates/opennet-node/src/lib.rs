@@ -12,8 +12,10 @@ pub mod integration;
 pub mod peer;
 pub mod sync;
 pub mod error;
+pub mod runtime;
 
 mod config;
 
 pub use config::NodeConfig;
 pub use error::{NodeError, Result};
+pub use runtime::NodeRuntime;
@@ -1,4 +1,5 @@
 use opennet_core::NodeId;
+use opennet_transport::nat::Reachability;
 use std::collections::BTreeMap;
 
 pub struct PeerManager {
@@ -8,6 +9,8 @@ pub struct PeerManager {
 #[derive(Debug)]
 pub struct PeerInfo {
     pub endpoint: String,
+    /// Whether `endpoint` can be dialed directly or needs hole punching.
+    pub reachability: Reachability,
     pub trust: f64,
     pub last_seen: u64,
 }
@@ -0,0 +1,220 @@
+//! Node runtime: the integrations a running node owns and the periodic
+//! work that drives them.
+//!
+//! The daemon calls [`NodeRuntime::tick`] on a fixed interval with the
+//! current time. Each tick rotates the identity key once the configured
+//! [`RotationPolicy`](opennet_identity::rotation::RotationPolicy) says it
+//! is due, retries unsaved rotation logs and announces the identity. It
+//! also expires and saves the replay state, which lives in the data
+//! directory alongside the identity; [`NodeRuntime::shutdown`] saves it
+//! one last time.
+//!
+//! Each session accepted through [`NodeRuntime::accept_session`] samples
+//! the peer's handshake timestamp, which feeds the Network Median Time
+//! clock and the drift monitor.
+//!
+//! Revocation certificates published with the CLI land in the spool under
+//! the data directory and are picked up by
+//! [`NodeRuntime::import_revocations`]. Accepted revocations mark their
+//! epoch revoked for the key registry and close its sessions.
+//!
+//! Guardian sets and recovery certificates received from peers go through
+//! the revocation integration; a validated recovery installs the
+//! recovered key in the key registry.
+
+use opennet_core::NodeId;
+use opennet_core::types::Timestamp;
+use opennet_identity::rotation::RotationRequest;
+use opennet_identity::{KeyPair, NodeSigner, SecureStorage};
+use opennet_revocation::quorum::Authorization;
+use opennet_revocation::recovery::ValidatedRecovery;
+use opennet_revocation::revocation::certificate::CERTIFICATE_SPOOL_DIR;
+use opennet_time::{SystemClock, TimeEvent};
+use opennet_transport::session::SessionBinding;
+use opennet_wire::messages::{GuardianSetMessage, RecoveryMessage, RevocationMessage};
+use crate::config::NodeConfig;
+use crate::error::{NodeError, Result};
+use crate::integration::transport::TransportIntegration;
+use crate::integration::trust::TrustIntegration;
+use crate::integration::{
+    GuardianSetGossip, IdentityIntegration, IdentityStore, RecoveryGossip, RevocationGossip, RevocationIntegration,
+    TimeIntegration,
+};
+
+/// Name the identity keys are stored under, as `<name>-<epoch>`.
+pub const IDENTITY_KEY_NAME: &str = "identity";
+
+/// File holding the signed rotation log inside the data directory.
+pub const ROTATION_LOG_FILE: &str = "rotation.log";
+
+/// Default interval between ticks.
+pub const TICK_INTERVAL_SECS: u64 = 60;
+
+/// A node's integrations, built from its configuration.
+pub struct NodeRuntime {
+    config: NodeConfig,
+    identity: IdentityIntegration,
+    transport: TransportIntegration,
+    trust: TrustIntegration,
+    revocation: RevocationIntegration,
+    time: TimeIntegration<SystemClock>,
+}
+
+impl NodeRuntime {
+    /// Open the identity and replay state persisted in `config.data_dir`,
+    /// creating an identity at `now_secs` on first start.
+    pub fn open(config: NodeConfig, storage: Box<dyn SecureStorage + Send>, now_secs: u64) -> Result<Self> {
+        Self::open_inner(config, storage, None, now_secs)
+    }
+
+    /// As [`open`](Self::open), but the identity key is held by an
+    /// external `signer` (a PKCS#11 token or a signer process) instead of
+    /// `storage`. The node does not rotate such a key itself.
+    pub fn open_with_signer(
+        config: NodeConfig,
+        storage: Box<dyn SecureStorage + Send>,
+        signer: Box<dyn NodeSigner>,
+        now_secs: u64,
+    ) -> Result<Self> {
+        Self::open_inner(config, storage, Some(signer), now_secs)
+    }
+
+    fn open_inner(
+        config: NodeConfig,
+        storage: Box<dyn SecureStorage + Send>,
+        signer: Option<Box<dyn NodeSigner>>,
+        now_secs: u64,
+    ) -> Result<Self> {
+        let time = TimeIntegration::new(SystemClock, config.nmt.clone())
+            .with_state_dir(&config.data_dir)
+            .map_err(time_err)?;
+        let store = IdentityStore::new(storage, IDENTITY_KEY_NAME, config.data_dir.join(ROTATION_LOG_FILE));
+        let identity = match (store.exists(), signer) {
+            (true, None) => IdentityIntegration::open(store, config.rotation)?,
+            (false, None) => IdentityIntegration::create(KeyPair::random(), now_secs, store, config.rotation)?,
+            (true, Some(signer)) => IdentityIntegration::open_with_signer(store, signer, config.rotation)?,
+            (false, Some(signer)) => IdentityIntegration::create_with_signer(signer, now_secs, store, config.rotation)?,
+        };
+        Ok(Self {
+            identity: identity.with_config(&config),
+            transport: TransportIntegration::from_config(&config),
+            trust: TrustIntegration::new(),
+            revocation: RevocationIntegration::default(),
+            time,
+            config,
+        })
+    }
+
+    /// Periodic work. Returns our rotation if one happened.
+    ///
+    /// The replay state is saved even if the identity tick fails, and a
+    /// failed save does not hold back the rotation.
+    pub fn tick(&mut self, now_secs: u64) -> Result<Option<RotationRequest>> {
+        let saved = self.time.tick().map_err(time_err);
+        let rotation = self.identity.tick(now_secs, &mut self.transport)?;
+        saved.map(|()| rotation)
+    }
+
+    /// Save state for an orderly shutdown.
+    pub fn shutdown(&mut self) -> Result<()> {
+        self.time.shutdown().map_err(time_err)
+    }
+
+    /// Hand a time event to the layers that react to it.
+    pub fn on_time_event(&mut self, event: &TimeEvent, now_secs: u64) -> Result<Option<RotationRequest>> {
+        self.time.on_time_event(event);
+        self.identity.on_time_event(event, now_secs, &mut self.transport)
+    }
+
+    /// Open a session with a peer whose handshake verified, and sample
+    /// the `timestamp` it signed into the handshake transcript.
+    pub fn accept_session(
+        &mut self,
+        binding: SessionBinding,
+        session_id: u64,
+        timestamp: Timestamp,
+        now_ms: u64,
+    ) -> Result<()> {
+        let node_id = binding.node_id;
+        self.transport
+            .accept_session(binding, session_id, now_ms, &mut self.trust, self.identity.peers())
+            .map_err(|e| NodeError::PeerError(e.to_string()))?;
+        self.time.on_peer_timestamp(node_id, timestamp, &self.trust);
+        Ok(())
+    }
+
+    /// Submit the certificates waiting in the revocation spool. Returns
+    /// how many new revocations were accepted.
+    pub fn import_revocations(&mut self) -> Result<usize> {
+        let spool = self.config.data_dir.join(CERTIFICATE_SPOOL_DIR);
+        let accepted = self.revocation.import_spool(&spool, self.identity.peers(), self.trust.graph())?;
+        self.transport.close_revoked(self.identity.peers());
+        Ok(accepted)
+    }
+
+    /// Handle a REVOCATION message received from `from`.
+    pub fn on_revocation_message(&mut self, from: NodeId, message: &RevocationMessage) -> Result<Option<Authorization>> {
+        let accepted = self.revocation.on_revocation_message(from, message, self.identity.peers(), self.trust.graph())?;
+        self.transport.close_revoked(self.identity.peers());
+        Ok(accepted)
+    }
+
+    /// Revocations to gossip, our own and relayed.
+    pub fn drain_revocations(&mut self) -> Vec<RevocationGossip> {
+        self.revocation.drain_revocations()
+    }
+
+    /// Handle a GUARDIAN_SET message received from `from`.
+    pub fn on_guardian_set_message(&mut self, from: NodeId, message: &GuardianSetMessage) -> Result<bool> {
+        self.revocation.on_guardian_set_message(from, message, self.identity.peers())
+    }
+
+    /// Handle a RECOVERY message received from `from`.
+    pub fn on_recovery_message(&mut self, from: NodeId, message: &RecoveryMessage) -> Result<Option<ValidatedRecovery>> {
+        self.revocation.on_recovery_message(from, message, self.identity.peers())
+    }
+
+    /// Guardian sets to gossip, our own and relayed.
+    pub fn drain_guardian_sets(&mut self) -> Vec<GuardianSetGossip> {
+        self.revocation.drain_guardian_sets()
+    }
+
+    /// Recovery certificates to gossip, our own and relayed.
+    pub fn drain_recoveries(&mut self) -> Vec<RecoveryGossip> {
+        self.revocation.drain_recoveries()
+    }
+
+    /// Configuration the node was opened with.
+    pub fn config(&self) -> &NodeConfig {
+        &self.config
+    }
+
+    /// The identity integration.
+    pub fn identity(&mut self) -> &mut IdentityIntegration {
+        &mut self.identity
+    }
+
+    /// The transport integration.
+    pub fn transport(&mut self) -> &mut TransportIntegration {
+        &mut self.transport
+    }
+
+    /// The trust integration.
+    pub fn trust(&mut self) -> &mut TrustIntegration {
+        &mut self.trust
+    }
+
+    /// The revocation integration.
+    pub fn revocation(&mut self) -> &mut RevocationIntegration {
+        &mut self.revocation
+    }
+
+    /// The time integration.
+    pub fn time(&mut self) -> &mut TimeIntegration<SystemClock> {
+        &mut self.time
+    }
+}
+
+fn time_err(e: opennet_time::TimeError) -> NodeError {
+    NodeError::PersistenceError(e.to_string())
+}
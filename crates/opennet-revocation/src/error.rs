@@ -9,4 +9,6 @@ pub enum RevocationError {
     QuorumNotMet,
     #[error("recovery failed: {0}")]
     RecoveryFailed(String),
+    #[error("signing failed: {0}")]
+    Signing(#[from] opennet_identity::IdentityError),
 }
@@ -1,3 +1,4 @@
 pub mod validator;
 pub mod weight;
-pub use validator::QuorumValidator;
+pub use validator::{Authorization, QuorumValidator, ValidatedRevocation};
+pub use weight::{calculate_quorum_weight, QuorumWeight};
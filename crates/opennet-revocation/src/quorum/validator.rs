@@ -1,11 +1,103 @@
+use opennet_core::NodeId;
+use opennet_identity::rotation::PeerKeyRegistry;
+use opennet_identity::verify_signature;
+use opennet_trust::graph::TrustGraph;
+use std::collections::BTreeSet;
+use crate::revocation::certificate::verify_self_revocation;
 use crate::revocation::object::RevocationObject;
-use crate::error::Result;
+use crate::revocation::validation;
+use crate::error::{RevocationError, Result};
+use super::weight::{calculate_quorum_weight, QuorumWeight};
 
-pub struct QuorumValidator { threshold: f64 }
+/// Why a revocation was accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authorization {
+    /// Signed by the revoked epoch's own key.
+    SelfSigned,
+    /// Signed by enough of the network's trust.
+    Quorum(QuorumWeight),
+}
+
+/// A revocation that passed [`QuorumValidator::verify`], with what
+/// authorized it. Only the validator creates one.
+#[derive(Debug, Clone)]
+pub struct ValidatedRevocation {
+    object: RevocationObject,
+    authorization: Authorization,
+}
+
+impl ValidatedRevocation {
+    /// The revocation.
+    pub fn object(&self) -> &RevocationObject {
+        &self.object
+    }
+
+    /// What authorized it.
+    pub fn authorization(&self) -> Authorization {
+        self.authorization
+    }
+
+    /// Take the revocation back.
+    pub fn into_object(self) -> RevocationObject {
+        self.object
+    }
+}
+
+/// Checks that a revocation is signed by enough of the network's trust,
+/// or by the revoked node itself.
+///
+/// A valid self-signature with the revoked epoch's key is enough on its
+/// own. Otherwise each signature must name the signer's current epoch,
+/// which must not be revoked, and verify under that epoch's key; keys a
+/// signer has rotated away from no longer vote. Signatures that do not
+/// verify, repeat a signer, or come from the revoked node itself are
+/// ignored; the weight of the remaining signers must reach the threshold
+/// share of the graph's total weight.
+pub struct QuorumValidator { threshold_ppm: u64 }
 
 impl QuorumValidator {
-    pub fn new(threshold: f64) -> Self { Self { threshold } }
-    pub fn validate(&self, _obj: &RevocationObject) -> Result<bool> { Ok(true) }
+    /// Require `threshold` (0.0 to 1.0) of the total trust weight.
+    pub fn new(threshold: f64) -> Self {
+        Self { threshold_ppm: (threshold.clamp(0.0, 1.0) * 1_000_000.0).round() as u64 }
+    }
+
+    /// Validate `obj`, returning what authorized it.
+    pub fn validate(&self, obj: &RevocationObject, keys: &PeerKeyRegistry, graph: &TrustGraph) -> Result<Authorization> {
+        validation::validate(obj)?;
+        if obj.self_signature().is_some() && verify_self_revocation(obj, keys).is_ok() {
+            return Ok(Authorization::SelfSigned);
+        }
+        let signers = Self::valid_signers(obj, keys);
+        let weight = calculate_quorum_weight(&signers, graph, &obj.node_id);
+        if !weight.meets(self.threshold_ppm) {
+            return Err(RevocationError::QuorumNotMet);
+        }
+        Ok(Authorization::Quorum(weight))
+    }
+
+    /// Validate `obj` and keep it together with what authorized it.
+    pub fn verify(&self, obj: RevocationObject, keys: &PeerKeyRegistry, graph: &TrustGraph) -> Result<ValidatedRevocation> {
+        let authorization = self.validate(&obj, keys, graph)?;
+        Ok(ValidatedRevocation { object: obj, authorization })
+    }
+
+    /// Distinct signers other than the subject whose signatures verify
+    /// under their current, unrevoked key.
+    pub fn valid_signers(obj: &RevocationObject, keys: &PeerKeyRegistry) -> BTreeSet<NodeId> {
+        let message = obj.signing_bytes();
+        obj.signatures
+            .iter()
+            .filter(|s| s.signer != obj.node_id)
+            .filter(|s| {
+                keys.current(&s.signer).is_some_and(|(epoch, key)| {
+                    epoch == s.epoch
+                        && !keys.is_revoked(&s.signer, epoch)
+                        && verify_signature(&key, &message, &s.signature).is_ok()
+                })
+            })
+            .map(|s| s.signer)
+            .collect()
+    }
 }
 
 impl Default for QuorumValidator {
@@ -1,2 +1,38 @@
 use opennet_core::NodeId;
-pub fn calculate_quorum_weight(_signers: &[NodeId]) -> f64 { 0.6 }
+use opennet_trust::graph::TrustGraph;
+use std::collections::BTreeSet;
+
+/// Trust behind a set of signers, out of the trust of every node that
+/// could have signed. Sums are of raw fixed-point weights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuorumWeight {
+    /// Weight of the valid signers.
+    pub signed: u64,
+    /// Weight of every node but the subject.
+    pub total: u64,
+}
+
+impl QuorumWeight {
+    /// Share of the total weight that signed.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.signed as f64 / self.total as f64
+    }
+
+    /// Check the signed share reaches `threshold_ppm` parts per million.
+    pub fn meets(&self, threshold_ppm: u64) -> bool {
+        self.signed > 0 && u128::from(self.signed) * 1_000_000 >= u128::from(threshold_ppm) * u128::from(self.total)
+    }
+}
+
+/// Sum the graph weights of `signers` against the weight of every node
+/// in `graph`. The `subject` of the vote counts for neither.
+pub fn calculate_quorum_weight(signers: &BTreeSet<NodeId>, graph: &TrustGraph, subject: &NodeId) -> QuorumWeight {
+    let weight = |id: &NodeId| graph.get_weight(id).map_or(0, |w| w.raw().max(0) as u64);
+    let sum = |ids: &mut dyn Iterator<Item = &NodeId>| {
+        ids.filter(|id| *id != subject).fold(0u64, |acc, id| acc.saturating_add(weight(id)))
+    };
+    QuorumWeight { signed: sum(&mut signers.iter()), total: sum(&mut graph.nodes()) }
+}
@@ -0,0 +1,68 @@
+//! Guardian sets tracked across revocations.
+
+use opennet_core::NodeId;
+use opennet_core::types::PublicKey;
+use opennet_identity::recovery::GuardianSet;
+use std::collections::BTreeMap;
+use crate::error::{RevocationError, Result};
+
+/// Guardian sets known for other nodes.
+///
+/// Once a node is revoked its set is frozen: only sets signed by a key
+/// newer than the revoked epoch are accepted, so a stolen key cannot
+/// swap in its own guardians.
+#[derive(Debug, Default)]
+pub struct GuardianRegistry {
+    sets: BTreeMap<NodeId, GuardianSet>,
+    /// Highest revoked epoch per node.
+    revoked: BTreeMap<NodeId, u64>,
+}
+
+impl GuardianRegistry {
+    /// Empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a set signed by `owner_key`, the node's key for `set.epoch`.
+    pub fn register(&mut self, set: GuardianSet, owner_key: &PublicKey) -> Result<()> {
+        set.verify(owner_key).map_err(recovery_err)?;
+        if self.revoked.get(&set.node_id).is_some_and(|revoked| set.epoch <= *revoked) {
+            return Err(RevocationError::RecoveryFailed("guardian set signed by a revoked key".into()));
+        }
+        if self.sets.get(&set.node_id).is_some_and(|known| known.version >= set.version) {
+            return Err(RevocationError::RecoveryFailed("stale guardian set".into()));
+        }
+        self.sets.insert(set.node_id, set);
+        Ok(())
+    }
+
+    /// Freeze a node's guardians after its `epoch` key was revoked.
+    pub fn on_revocation(&mut self, node_id: NodeId, epoch: u64) {
+        let revoked = self.revoked.entry(node_id).or_insert(epoch);
+        *revoked = (*revoked).max(epoch);
+    }
+
+    /// Current guardian set of `node_id`.
+    pub fn get(&self, node_id: &NodeId) -> Option<&GuardianSet> {
+        self.sets.get(node_id)
+    }
+
+    /// Number of nodes with a registered set.
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    /// Check if no sets are registered.
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+}
+
+/// Keep the reason of identity-side recovery failures.
+pub(crate) fn recovery_err(e: opennet_identity::IdentityError) -> RevocationError {
+    match e {
+        opennet_identity::IdentityError::RecoveryFailed(reason) => RevocationError::RecoveryFailed(reason),
+        e => e.into(),
+    }
+}
@@ -1,4 +1,12 @@
-pub mod request;
-pub mod attestation;
+//! Social recovery after a revocation.
+//!
+//! The recovery objects live in [`opennet_identity::recovery`], where the
+//! key registry and rotation log accept a validated certificate; this
+//! module tracks guardian sets across revocations and builds certificates
+//! for a validated revocation.
+
+pub mod guardians;
 pub mod process;
-pub use request::RecoveryRequest;
+pub use opennet_identity::recovery::{GuardianSet, PeerAttestation, RecoveryCertificate, RecoveryRequest, ValidatedRecovery};
+pub use guardians::GuardianRegistry;
+pub use process::process_recovery;
@@ -1,3 +1,28 @@
-use super::request::RecoveryRequest;
-use crate::error::Result;
-pub fn process_recovery(_req: &RecoveryRequest) -> Result<()> { Ok(()) }
+//! Building recovery certificates for a revoked identity.
+
+use opennet_identity::recovery::{GuardianSet, PeerAttestation, RecoveryCertificate, RecoveryRequest, ValidatedRecovery};
+use opennet_identity::rotation::PeerKeyRegistry;
+use crate::quorum::ValidatedRevocation;
+use crate::error::{RevocationError, Result};
+use super::guardians::recovery_err;
+
+/// Check a recovery request against a validated revocation and the node's
+/// pre-registered guardians, whose current keys are taken from `keys`.
+///
+/// Invalid or duplicate attestations and attestations from non-guardians
+/// are dropped; recovery succeeds if the rest meet the threshold.
+pub fn process_recovery(
+    request: &RecoveryRequest,
+    attestations: &[PeerAttestation],
+    guardians: &GuardianSet,
+    revocation: &ValidatedRevocation,
+    keys: &PeerKeyRegistry,
+) -> Result<ValidatedRecovery> {
+    let revocation = revocation.object();
+    if revocation.node_id != request.node_id || revocation.revoked_epoch != request.revoked_epoch {
+        return Err(RevocationError::RecoveryFailed("request does not match revocation".into()));
+    }
+    RecoveryCertificate { request: request.clone(), attestations: attestations.to_vec() }
+        .validate(guardians, keys)
+        .map_err(recovery_err)
+}
@@ -0,0 +1,62 @@
+//! Self-revocation certificates.
+//!
+//! A node signs a revocation of its own epoch when the key is created
+//! and stores it offline, like a PGP revocation certificate. If the key
+//! is later lost or stolen, publishing the certificate revokes the epoch
+//! without waiting for a quorum: only the epoch's own key could have
+//! produced it, and whoever holds that key can already impersonate the
+//! node, so letting them also revoke it gives nothing away.
+
+use opennet_core::NodeId;
+use opennet_core::types::Timestamp;
+use opennet_identity::rotation::PeerKeyRegistry;
+use opennet_identity::{verify_signature, NodeSigner};
+use super::object::RevocationObject;
+use super::trigger::RevocationTrigger;
+use super::validation;
+use crate::error::{RevocationError, Result};
+
+/// File extension for stored certificates.
+pub const CERTIFICATE_EXTENSION: &str = "rev";
+
+/// Directory under the node's data directory where certificates are
+/// dropped for the running node to publish.
+pub const CERTIFICATE_SPOOL_DIR: &str = "revocations";
+
+/// Sign a revocation of `node_id`'s epoch `epoch` with that epoch's key.
+pub fn generate_self_revocation(
+    node_id: NodeId,
+    epoch: u64,
+    trigger: RevocationTrigger,
+    timestamp: Timestamp,
+    signer: &dyn NodeSigner,
+) -> Result<RevocationObject> {
+    let mut obj = RevocationObject {
+        node_id,
+        revoked_epoch: epoch,
+        reason: trigger.reason(),
+        timestamp,
+        signatures: Vec::new(),
+    };
+    obj.add_signature(node_id, epoch, signer)?;
+    validation::validate(&obj)?;
+    Ok(obj)
+}
+
+/// Check `obj` carries a valid signature by the revoked epoch's own key.
+pub fn verify_self_revocation(obj: &RevocationObject, keys: &PeerKeyRegistry) -> Result<()> {
+    validation::validate(obj)?;
+    let sig = obj
+        .self_signature()
+        .ok_or_else(|| RevocationError::Invalid("not signed by the revoked node".into()))?;
+    let key = keys
+        .key_at(&obj.node_id, obj.revoked_epoch)
+        .ok_or_else(|| RevocationError::Invalid(format!("no key for epoch {}", obj.revoked_epoch)))?;
+    verify_signature(&key, &obj.signing_bytes(), &sig.signature)
+        .map_err(|_| RevocationError::Invalid("self-signature does not verify".into()))
+}
+
+/// File name for a stored certificate: `<node-hex>-<epoch>.rev`.
+pub fn certificate_file_name(obj: &RevocationObject) -> String {
+    format!("{}-{}.{}", obj.node_id.to_hex(), obj.revoked_epoch, CERTIFICATE_EXTENSION)
+}
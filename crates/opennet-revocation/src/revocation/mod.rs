@@ -2,4 +2,6 @@ pub mod object;
 pub mod trigger;
 pub mod propagation;
 pub mod validation;
+pub mod certificate;
 pub use object::RevocationObject;
+pub use certificate::{generate_self_revocation, verify_self_revocation};
@@ -1,6 +1,12 @@
 use opennet_core::NodeId;
 use opennet_core::types::{Signature, Timestamp};
+use opennet_identity::{NodeSigner, SigningContext};
+use opennet_wire::cbor::{CborDecoder, CborEncoder};
+use opennet_wire::messages::revocation::{RevocationReason, RevocationSignature};
+use opennet_wire::messages::RevocationMessage;
 use serde::{Deserialize, Serialize};
+use crate::error::{RevocationError, Result};
+use super::trigger::RevocationTrigger;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RevocationObject {
@@ -17,3 +23,122 @@ pub struct QuorumSignature {
     pub epoch: u64,
     pub signature: Signature,
 }
+
+impl RevocationObject {
+    /// Bytes each quorum member signs.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        SigningContext::Revocation.signing_bytes(|enc| {
+            enc.encode_array_header(4)
+                .encode_bytes(self.node_id.as_bytes())
+                .encode_uint(self.revoked_epoch)
+                .encode_uint(u64::from(self.reason))
+                .encode_timestamp(self.timestamp);
+        })
+    }
+
+    /// Add this node's quorum signature.
+    pub fn add_signature(&mut self, signer_id: NodeId, epoch: u64, signer: &dyn NodeSigner) -> Result<()> {
+        let signature = signer.sign(&self.signing_bytes())?;
+        self.signatures.push(QuorumSignature { signer: signer_id, epoch, signature });
+        Ok(())
+    }
+
+    /// Signature by the revoked node itself with the revoked epoch's key.
+    pub fn self_signature(&self) -> Option<&QuorumSignature> {
+        self.signatures
+            .iter()
+            .find(|s| s.signer == self.node_id && s.epoch == self.revoked_epoch)
+    }
+
+    /// Canonical CBOR for storage and gossip:
+    /// `[node_id, revoked_epoch, reason, timestamp, [[signer, epoch, signature]...]]`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = CborEncoder::new();
+        enc.encode_array_header(5)
+            .encode_bytes(self.node_id.as_bytes())
+            .encode_uint(self.revoked_epoch)
+            .encode_uint(u64::from(self.reason))
+            .encode_timestamp(self.timestamp)
+            .encode_array_header(self.signatures.len());
+        for s in &self.signatures {
+            enc.encode_array_header(3)
+                .encode_bytes(s.signer.as_bytes())
+                .encode_uint(s.epoch)
+                .encode_bytes(s.signature.as_bytes());
+        }
+        enc.into_bytes()
+    }
+
+    /// Decode a revocation. Does not verify it.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut dec = CborDecoder::new(bytes);
+        if dec.decode_array_header().map_err(malformed)? != 5 {
+            return Err(malformed("expected 5-element revocation"));
+        }
+        let node_id = NodeId::from_bytes(fixed(&mut dec)?);
+        let revoked_epoch = dec.decode_uint().map_err(malformed)?;
+        let reason = u8::try_from(dec.decode_uint().map_err(malformed)?).map_err(malformed)?;
+        let timestamp = dec.decode_timestamp().map_err(malformed)?;
+        let count = dec.decode_array_header().map_err(malformed)?;
+        let mut signatures = Vec::with_capacity(count.min(64));
+        for _ in 0..count {
+            if dec.decode_array_header().map_err(malformed)? != 3 {
+                return Err(malformed("expected 3-element signature"));
+            }
+            let signer = NodeId::from_bytes(fixed(&mut dec)?);
+            let epoch = dec.decode_uint().map_err(malformed)?;
+            let signature = Signature::from_bytes(fixed(&mut dec)?);
+            signatures.push(QuorumSignature { signer, epoch, signature });
+        }
+        if !dec.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(Self { node_id, revoked_epoch, reason, timestamp, signatures })
+    }
+
+    /// REVOCATION message carrying this object.
+    pub fn to_message(&self) -> Result<RevocationMessage> {
+        let reason = match RevocationTrigger::from_reason(self.reason) {
+            Some(RevocationTrigger::KeyCompromise) => RevocationReason::KeyCompromise,
+            Some(RevocationTrigger::ForcedDisclosure) => RevocationReason::ForcedDisclosure,
+            Some(RevocationTrigger::UnauthorizedUsage) => RevocationReason::UnauthorizedUsage,
+            Some(RevocationTrigger::Voluntary) => RevocationReason::Voluntary,
+            None => return Err(RevocationError::Invalid(format!("unknown reason {}", self.reason))),
+        };
+        Ok(RevocationMessage {
+            node_id: self.node_id,
+            revoked_epoch: self.revoked_epoch,
+            reason,
+            timestamp: self.timestamp,
+            signatures: self
+                .signatures
+                .iter()
+                .map(|s| RevocationSignature { signer: s.signer, epoch_id: s.epoch, signature: s.signature })
+                .collect(),
+        })
+    }
+
+    /// Object carried by a REVOCATION message. Does not verify it.
+    pub fn from_message(message: &RevocationMessage) -> Self {
+        Self {
+            node_id: message.node_id,
+            revoked_epoch: message.revoked_epoch,
+            reason: message.reason as u8,
+            timestamp: message.timestamp,
+            signatures: message
+                .signatures
+                .iter()
+                .map(|s| QuorumSignature { signer: s.signer, epoch: s.epoch_id, signature: s.signature })
+                .collect(),
+        }
+    }
+}
+
+fn fixed<const N: usize>(dec: &mut CborDecoder) -> Result<[u8; N]> {
+    let bytes = dec.decode_bytes().map_err(malformed)?;
+    bytes.as_slice().try_into().map_err(|_| malformed(format!("expected {} bytes, got {}", N, bytes.len())))
+}
+
+fn malformed(e: impl std::fmt::Display) -> RevocationError {
+    RevocationError::Invalid(format!("malformed revocation: {}", e))
+}
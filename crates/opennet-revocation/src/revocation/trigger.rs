@@ -1,2 +1,20 @@
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RevocationTrigger { KeyCompromise, ForcedDisclosure, UnauthorizedUsage, Voluntary }
+
+impl RevocationTrigger {
+    /// Trigger for a `RevocationObject::reason` code.
+    pub fn from_reason(reason: u8) -> Option<Self> {
+        match reason {
+            0 => Some(Self::KeyCompromise),
+            1 => Some(Self::ForcedDisclosure),
+            2 => Some(Self::UnauthorizedUsage),
+            3 => Some(Self::Voluntary),
+            _ => None,
+        }
+    }
+
+    /// Reason code carried in a `RevocationObject`.
+    pub fn reason(self) -> u8 {
+        self as u8
+    }
+}
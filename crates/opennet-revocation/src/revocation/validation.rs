@@ -1,3 +1,17 @@
 use super::object::RevocationObject;
+use super::trigger::RevocationTrigger;
 use crate::error::{RevocationError, Result};
-pub fn validate(_obj: &RevocationObject) -> Result<()> { Ok(()) }
+
+/// Check a revocation is well formed before any signature is verified.
+pub fn validate(obj: &RevocationObject) -> Result<()> {
+    if obj.revoked_epoch == 0 {
+        return Err(RevocationError::Invalid("epoch 0 cannot be revoked".into()));
+    }
+    if RevocationTrigger::from_reason(obj.reason).is_none() {
+        return Err(RevocationError::Invalid(format!("unknown reason {}", obj.reason)));
+    }
+    if obj.signatures.is_empty() {
+        return Err(RevocationError::Invalid("no signatures".into()));
+    }
+    Ok(())
+}
@@ -1,3 +1,564 @@
+use opennet_identity::{verify_signature, FileStorage, KdfParams, KeyPair, KeyUnlock, NodeIdentity, NodeSigner, SecureStorage};
+
 pub fn test_ed25519_signatures() -> bool { true }
 pub fn test_sha256_hashing() -> bool { true }
 pub fn test_node_id_derivation() -> bool { true }
+
+/// Keys round-trip through the encrypted keystore, only with the right
+/// passphrase, and truncated files are rejected without panicking.
+pub fn test_encrypted_keystore() -> bool {
+    let dir = std::env::temp_dir().join(format!("opennet-keystore-{}", std::process::id()));
+    if std::fs::create_dir_all(&dir).is_err() {
+        return false;
+    }
+    let cheap = KdfParams { m_cost_kib: 64, t_cost: 1, p_cost: 1 };
+    let keypair = KeyPair::generate(&[7u8; 32]);
+
+    let mut storage = FileStorage::new(&dir, KeyUnlock::passphrase("correct horse")).with_kdf_params(cheap);
+    let wrong = FileStorage::new(&dir, KeyUnlock::passphrase("battery staple"));
+    let ok = storage.store("node", &keypair).is_ok()
+        && storage.load("node").map(|k| k.public_key() == keypair.public_key()).unwrap_or(false)
+        && wrong.load("node").is_err()
+        && std::fs::read(dir.join("node.key")).map(|b| !b.windows(32).any(|w| w == keypair.expose_secret().as_bytes())).unwrap_or(false)
+        && private_mode(&dir.join("node.key"))
+        && std::fs::write(dir.join("short.key"), [1u8; 10]).is_ok()
+        && storage.load("short").is_err();
+
+    let _ = std::fs::remove_dir_all(&dir);
+    ok
+}
+
+/// Secrets never show up in `Debug` output, and equal keys compare equal.
+pub fn test_secret_hygiene() -> bool {
+    let keypair = KeyPair::generate(&[9u8; 32]);
+    let secret = keypair.expose_secret();
+    let hex: String = secret.as_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+    let debug = format!("{:?} {:?}", keypair, secret);
+
+    let mut identity = NodeIdentity::new(KeyPair::generate(&[9u8; 32]), 0);
+    !debug.contains(&hex)
+        && !debug.contains(&format!("{:?}", secret.as_bytes()))
+        && keypair == KeyPair::generate(&[9u8; 32])
+        && keypair != KeyPair::generate(&[10u8; 32])
+        && identity.rotate(KeyPair::generate(&[9u8; 32]), 1).is_err()
+}
+
+/// A rotation log exported by one node imports and verifies elsewhere,
+/// restores the identity, and rejects tampering and version 1 logs with
+/// second-precision timestamps.
+pub fn test_rotation_log() -> bool {
+    use opennet_core::types::{AnyPublicKey, SignatureAlgorithm};
+    use opennet_core::NodeId;
+    use opennet_identity::rotation::RotationLog;
+    use opennet_wire::cbor::CborEncoder;
+
+    let mut identity = NodeIdentity::new(KeyPair::generate(&[1u8; 32]), 100);
+    if identity.rotate(KeyPair::generate(&[2u8; 32]), 200).is_err()
+        || identity.rotate(KeyPair::generate(&[3u8; 32]), 300).is_err()
+    {
+        return false;
+    }
+    let exported = identity.rotation_log().export();
+    let imported = match RotationLog::import(&exported) {
+        Ok(log) => log,
+        Err(_) => return false,
+    };
+
+    let mut tampered = exported.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+
+    let mut v1 = CborEncoder::new();
+    v1.encode_array_header(4)
+        .encode_uint(1)
+        .encode_bytes(KeyPair::generate(&[1u8; 32]).public_key().as_bytes())
+        .encode_uint(100)
+        .encode_array_header(0);
+
+    let path = std::env::temp_dir().join(format!("opennet-rotlog-{}", std::process::id()));
+    let persisted = imported.save(&path).is_ok()
+        && RotationLog::load(&path).map(|log| log == imported).unwrap_or(false);
+    let _ = std::fs::remove_file(&path);
+
+    let restored = NodeIdentity::from_log(KeyPair::generate(&[3u8; 32]), imported.clone())
+        .map(|id| id.epoch_id() == 3 && id.node_id() == identity.node_id())
+        .unwrap_or(false);
+
+    // A hybrid genesis key derives the NodeId from the full key and is
+    // carried through export so the binding still verifies on import.
+    let genesis = KeyPair::generate(&[4u8; 32]).public_key();
+    let mut hybrid_bytes = genesis.as_bytes().to_vec();
+    hybrid_bytes.resize(SignatureAlgorithm::HybridEd25519MlDsa65.public_key_len(), 0xab);
+    let hybrid = AnyPublicKey::new(SignatureAlgorithm::HybridEd25519MlDsa65, hybrid_bytes)
+        .map(|key| RotationLog::new(genesis, 100).with_genesis_key(key.clone()).node_id() == &NodeId::from_any_public_key(&key)
+            && RotationLog::import(&RotationLog::new(genesis, 100).with_genesis_key(key).export()).is_ok_and(|log| log.verify().is_ok()))
+        .unwrap_or(false);
+
+    imported.head_epoch() == 3
+        && imported.verify().map(|key| key == identity.public_key()).unwrap_or(false)
+        && imported.key_at(2) == Some(&KeyPair::generate(&[2u8; 32]).public_key())
+        && RotationLog::import(&tampered).is_err()
+        && RotationLog::import(&v1.into_bytes()).is_err()
+        && NodeIdentity::from_log(KeyPair::generate(&[2u8; 32]), imported).is_err()
+        && persisted
+        && restored
+        && hybrid
+}
+
+/// Equivocation and impossible-timing evidence is detected, survives a
+/// CBOR round trip, verifies, and fails to verify once tampered with.
+/// Timing evidence needs two distinct claims received close together by
+/// observers whose keys are bound to their NodeIds.
+pub fn test_compromise_evidence() -> bool {
+    use opennet_core::types::Timestamp;
+    use opennet_identity::rotation::PeerKeyRegistry;
+    use opennet_identity::{CompromiseDetector, CompromiseEvidence, Observation, SignedClaim, MIN_RELOCATION_MS};
+    use std::net::IpAddr;
+
+    let node = KeyPair::generate(&[1u8; 32]);
+    let node_id = *NodeIdentity::new(KeyPair::generate(&[1u8; 32]), 0).node_id();
+    let key = node.public_key();
+    let claim = |seq: u64, ts: u64, payload: &[u8]| SignedClaim::sign(node_id, 1, seq, Timestamp::new(ts), payload, &node);
+    let (Ok(a), Ok(a_again), Ok(b), Ok(c), Ok(d)) =
+        (claim(1, 100, b"a"), claim(1, 100, b"a"), claim(1, 100, b"b"), claim(2, 110, b"c"), claim(3, 110, b"d"))
+    else {
+        return false;
+    };
+
+    let mut detector = CompromiseDetector::new();
+    let first = detector.record_claim(a, &key, 1_000);
+    let retransmit = detector.record_claim(a_again, &key, 1_000);
+    let equivocation = match detector.record_claim(b, &key, 1_000) {
+        Ok(Some(evidence)) => evidence,
+        _ => return false,
+    };
+
+    let observe = |claim: &SignedClaim, seed: u8, source: [u8; 4], received_at_ms: u64| {
+        let observer = KeyPair::generate(&[seed; 32]);
+        let observer_id = *NodeIdentity::new(KeyPair::generate(&[seed; 32]), 0).node_id();
+        Observation::sign(claim.clone(), observer_id, IpAddr::from(source), received_at_ms, &observer)
+    };
+    let late_ms = 1_000 + MIN_RELOCATION_MS;
+    let (Ok(here), Ok(nearby), Ok(late), Ok(far), Ok(relayed)) = (
+        observe(&c, 2, [10, 1, 0, 1], 1_000),
+        observe(&d, 3, [10, 1, 9, 9], 1_000),
+        observe(&d, 5, [198, 51, 100, 1], late_ms),
+        observe(&d, 4, [192, 0, 2, 1], 1_000),
+        observe(&c, 6, [203, 0, 113, 1], 1_000),
+    ) else {
+        return false;
+    };
+    // Signed by a key that is not the claimed observer's.
+    let impostor = KeyPair::generate(&[7u8; 32]);
+    let Ok(unbound) = Observation::sign(c.clone(), here.observer, IpAddr::from([172, 16, 0, 1]), 1_000, &impostor) else {
+        return false;
+    };
+    let keys = PeerKeyRegistry::new();
+    let same_claim = CompromiseEvidence::ImpossibleTiming {
+        public_key: key,
+        first: Box::new(here.clone()),
+        second: Box::new(relayed),
+    };
+    let seen_here = detector.record_observation(here, &key, &keys, 1_000);
+    let seen_nearby = detector.record_observation(nearby, &key, &keys, 1_000);
+    let seen_late = detector.record_observation(late, &key, &keys, 1_000);
+    let rejected_unbound = detector.record_observation(unbound, &key, &keys, 1_000).is_err();
+    let timing = match detector.record_observation(far, &key, &keys, 1_000) {
+        Ok(Some(evidence)) => evidence,
+        _ => return false,
+    };
+
+    let roundtrips = [&equivocation, &timing].iter().all(|evidence| {
+        CompromiseEvidence::decode(&evidence.encode()).map(|d| &d == *evidence && d.verify(&keys).is_ok()).unwrap_or(false)
+    });
+    let mut tampered = timing.encode();
+    let mid = tampered.len() / 2;
+    tampered[mid] ^= 1;
+    let wrong_key = match &equivocation {
+        CompromiseEvidence::Equivocation { first, second, .. } => CompromiseEvidence::Equivocation {
+            public_key: KeyPair::generate(&[9u8; 32]).public_key(),
+            first: first.clone(),
+            second: second.clone(),
+        },
+        _ => return false,
+    };
+
+    matches!(first, Ok(None))
+        && matches!(retransmit, Ok(None))
+        && matches!(seen_here, Ok(None))
+        && matches!(seen_nearby, Ok(None))
+        && matches!(seen_late, Ok(None))
+        && rejected_unbound
+        && matches!(timing, CompromiseEvidence::ImpossibleTiming { .. })
+        && same_claim.verify(&keys).is_err()
+        && roundtrips
+        && CompromiseEvidence::decode(&tampered).map(|d| d.verify(&keys).is_err()).unwrap_or(true)
+        && wrong_key.verify(&keys).is_err()
+}
+
+/// The detector stays within its node and claim caps, proves equivocation
+/// inside the window, and only counts suspicion once detail is summarised.
+pub fn test_bounded_compromise_detector() -> bool {
+    use opennet_core::types::Timestamp;
+    use opennet_identity::{CompromiseDetector, DetectorConfig, SignedClaim};
+
+    let config = DetectorConfig {
+        window_secs: 60,
+        summary_secs: 600,
+        max_nodes: 4,
+        max_claims_per_node: 8,
+        max_observations_per_node: 4,
+        bloom_bits: 1 << 14,
+        bloom_hashes: 5,
+        sketch_width: 256,
+        sketch_depth: 4,
+    };
+    let mut detector = CompromiseDetector::with_config(config);
+    let keys: Vec<KeyPair> = (1..=12u8).map(|seed| KeyPair::generate(&[seed; 32])).collect();
+    let ids: Vec<_> = (1..=12u8).map(|seed| *NodeIdentity::new(KeyPair::generate(&[seed; 32]), 0).node_id()).collect();
+
+    let mut peak = 0;
+    for (key, id) in keys.iter().zip(&ids) {
+        for seq in 0..16u64 {
+            let Ok(claim) = SignedClaim::sign(*id, 1, seq, Timestamp::new(seq), b"honest", key) else {
+                return false;
+            };
+            if !matches!(detector.record_claim(claim, &key.public_key(), seq), Ok(None)) {
+                return false;
+            }
+        }
+        peak = peak.max(detector.memory_usage());
+    }
+    let bounded = detector.tracked_nodes() <= 4;
+    let baseline = detector.memory_usage();
+
+    // Node 12 is still tracked: the contradiction is provable.
+    let (key, id) = (&keys[11], ids[11]);
+    let proven = SignedClaim::sign(id, 1, 15, Timestamp::new(15), b"forked", key)
+        .map(|c| matches!(detector.record_claim(c, &key.public_key(), 20), Ok(Some(_))))
+        .unwrap_or(false);
+
+    // After the window, node 12's detail is folded into the summaries.
+    detector.prune(200);
+    let forgotten = detector.tracked_nodes() == 0;
+    let late = SignedClaim::sign(id, 1, 14, Timestamp::new(14), b"forked", key)
+        .map(|c| matches!(detector.record_claim(c, &key.public_key(), 200), Ok(None)))
+        .unwrap_or(false);
+
+    bounded
+        && baseline <= peak
+        && proven
+        && forgotten
+        && late
+        && detector.suspicion(&id) >= 1
+        && detector.suspicion(&ids[0]) == 0
+}
+
+/// A key split 3-of-5 restores from any three mnemonic shares, and short,
+/// mixed or mistyped share sets are rejected.
+pub fn test_seed_backup() -> bool {
+    use opennet_identity::backup::{combine_shares, split_secret, SeedShare};
+
+    let keypair = KeyPair::generate(&[7u8; 32]);
+    let secret = keypair.expose_secret();
+    let Ok(shares) = split_secret(secret.as_bytes(), 3, 5) else {
+        return false;
+    };
+    let words: Vec<_> = shares.iter().map(|s| s.to_mnemonic()).collect();
+    let decoded: Vec<SeedShare> = words.iter().filter_map(|w| SeedShare::from_mnemonic(w).ok()).collect();
+
+    let restores = |picked: &[usize]| {
+        let subset: Vec<SeedShare> = picked.iter().map(|&i| decoded[i].clone()).collect();
+        combine_shares(&subset)
+            .ok()
+            .and_then(|s| KeyPair::from_bytes(&s).ok())
+            .map(|k| k.public_key() == keypair.public_key())
+            .unwrap_or(false)
+    };
+
+    let Ok(other) = split_secret(KeyPair::generate(&[8u8; 32]).expose_secret().as_bytes(), 3, 5) else {
+        return false;
+    };
+    let mixed = [decoded[0].clone(), decoded[1].clone(), other[2].clone()];
+
+    let mut typo: Vec<&str> = words[0].split(' ').collect();
+    typo[3] = if typo[3] == "abandon" { "ability" } else { "abandon" };
+
+    decoded.len() == 5
+        && words[0].split(' ').count() == 30
+        && restores(&[0, 1, 2])
+        && restores(&[4, 2, 0])
+        && restores(&[1, 3, 4])
+        && combine_shares(&decoded[..2]).is_err()
+        && combine_shares(&[decoded[0].clone(), decoded[0].clone(), decoded[1].clone()]).is_err()
+        && combine_shares(&mixed).is_err()
+        && SeedShare::from_mnemonic(&typo.join(" ")).is_err()
+        && split_secret(secret.as_bytes(), 4, 3).is_err()
+}
+
+/// Signing bytes follow `[context, context version, body]`, and a
+/// signature made in one context never verifies in another, even over an
+/// identical body.
+pub fn test_signing_contexts() -> bool {
+    use opennet_core::types::Timestamp;
+    use opennet_identity::rotation::RotationRequest;
+    use opennet_identity::{IdentityAnnouncement, SigningContext};
+    use opennet_transport::handshake::verification::{sign_handshake, verify_handshake};
+
+    // Known answer: handshake context v1 over an empty transcript.
+    let mut expected = vec![0x83, 0x78, 0x1b];
+    expected.extend_from_slice(b"opennet/transport/handshake");
+    expected.extend_from_slice(&[0x01, 0x40]);
+    let vector = SigningContext::Handshake.signing_bytes(|enc| {
+        enc.encode_bytes(&[]);
+    });
+
+    let keypair = KeyPair::generate(&[9u8; 32]);
+    let body = |enc: &mut opennet_wire::cbor::CborEncoder| {
+        enc.encode_array_header(1).encode_uint(7);
+    };
+    let cross_rejected = SigningContext::ALL.iter().all(|&signed_as| {
+        let signature = keypair.sign(&signed_as.signing_bytes(body));
+        SigningContext::ALL.iter().all(|&checked_as| {
+            let ok = verify_signature(&keypair.public_key(), &checked_as.signing_bytes(body), &signature).is_ok();
+            ok == (signed_as == checked_as)
+        })
+    });
+    let labels_unique = SigningContext::ALL
+        .iter()
+        .map(|c| c.label())
+        .collect::<std::collections::BTreeSet<_>>()
+        .len()
+        == SigningContext::ALL.len();
+
+    // Real objects: the old-key rotation signature is not a new-key one,
+    // and an announcement signature is not a handshake signature.
+    let identity = NodeIdentity::new(KeyPair::generate(&[10u8; 32]), 0);
+    let next = KeyPair::generate(&[11u8; 32]);
+    let Ok(rotation) = RotationRequest::create(*identity.node_id(), 1, Timestamp::new(100), identity.signer(), &next)
+    else {
+        return false;
+    };
+    let Ok(announcement) = IdentityAnnouncement::sign(&identity, Timestamp::new(100)) else {
+        return false;
+    };
+    let Ok(handshake) = sign_handshake(identity.signer(), &announcement.signing_bytes()) else {
+        return false;
+    };
+    let key = identity.public_key();
+
+    vector == expected
+        && cross_rejected
+        && labels_unique
+        && verify_signature(&key, &rotation.old_key_signing_bytes(), &rotation.old_key_signature).is_ok()
+        && verify_signature(&key, &rotation.new_key_signing_bytes(), &rotation.old_key_signature).is_err()
+        && announcement.verify().is_ok()
+        && !verify_handshake(&key, &announcement.signing_bytes(), &announcement.signature).unwrap_or(true)
+        && verify_handshake(&key, &announcement.signing_bytes(), &handshake).unwrap_or(false)
+        && verify_signature(&key, &announcement.signing_bytes(), &handshake).is_err()
+}
+
+/// Fixed vectors in `test-vectors/signing`, one per context: the signing
+/// bytes of a byte-string body and its Ed25519 signature match exactly,
+/// and the signature is rejected under every other context's bytes.
+pub fn test_signing_context_vectors() -> bool {
+    use opennet_core::types::{PublicKey, Signature};
+    use opennet_identity::SigningContext;
+
+    const VECTORS: [&str; 10] = [
+        include_str!("../../../../test-vectors/signing/identity_announcement.json"),
+        include_str!("../../../../test-vectors/signing/identity_rotation_old_key.json"),
+        include_str!("../../../../test-vectors/signing/identity_rotation_new_key.json"),
+        include_str!("../../../../test-vectors/signing/identity_claim.json"),
+        include_str!("../../../../test-vectors/signing/identity_observation.json"),
+        include_str!("../../../../test-vectors/signing/revocation_revocation.json"),
+        include_str!("../../../../test-vectors/signing/revocation_recovery_request.json"),
+        include_str!("../../../../test-vectors/signing/revocation_recovery_attestation.json"),
+        include_str!("../../../../test-vectors/signing/revocation_guardian_set.json"),
+        include_str!("../../../../test-vectors/signing/transport_handshake.json"),
+    ];
+    let context = |label: &serde_json::Value| SigningContext::ALL.into_iter().find(|c| Some(c.label()) == label.as_str());
+    let field = |v: &serde_json::Value, name: &str| v[name].as_str().and_then(from_hex);
+
+    let check = |text: &str| -> Option<(SigningContext, bool)> {
+        let v: serde_json::Value = serde_json::from_str(text).ok()?;
+        let ctx = context(&v["context"])?;
+        let seed: [u8; 32] = field(&v, "seed")?.try_into().ok()?;
+        let public_key = PublicKey::from_bytes(field(&v, "public_key")?.try_into().ok()?);
+        let signature = Signature::from_bytes(field(&v, "signature")?.try_into().ok()?);
+        let body = field(&v, "body")?;
+        let signing_bytes = field(&v, "signing_bytes")?;
+        let keypair = KeyPair::generate(&seed);
+
+        let rejected = v["rejected_as"].as_array()?;
+        let all_rejected = rejected.len() == SigningContext::ALL.len() - 1
+            && rejected.iter().all(|r| {
+                let (Some(other), Some(bytes)) = (context(&r["context"]), field(r, "signing_bytes")) else {
+                    return false;
+                };
+                other != ctx
+                    && bytes == other.signing_bytes(|enc| {
+                        enc.encode_bytes(&body);
+                    })
+                    && verify_signature(&public_key, &bytes, &signature).is_err()
+            });
+        let ok = v["version"].as_u64() == Some(ctx.version())
+            && keypair.public_key() == public_key
+            && signing_bytes
+                == ctx.signing_bytes(|enc| {
+                    enc.encode_bytes(&body);
+                })
+            && keypair.sign(&signing_bytes) == signature
+            && verify_signature(&public_key, &signing_bytes, &signature).is_ok()
+            && all_rejected;
+        Some((ctx, ok))
+    };
+
+    let results: Vec<_> = VECTORS.iter().map(|text| check(text)).collect();
+    let covered = results.iter().flatten().map(|(ctx, _)| *ctx).collect::<std::collections::BTreeSet<_>>();
+    covered.len() == SigningContext::ALL.len() && results.iter().all(|r| matches!(r, Some((_, true))))
+}
+
+/// Algorithm-tagged keys and signatures round-trip and refuse the wrong
+/// length however they are decoded, Ed25519 NodeIds are unchanged, other algorithms derive a domain-separated NodeId, and the
+/// hybrid scheme is used only when both peers advertise it.
+pub fn test_algorithm_agility() -> bool {
+    use opennet_core::types::{AnyPublicKey, AnySignature, SignatureAlgorithm};
+    use opennet_core::{NodeId, FEATURE_HYBRID_SIGNATURES};
+    use opennet_identity::algorithm::{negotiate_features, negotiated_algorithm, supported_features, verify_any};
+
+    let keypair = KeyPair::generate(&[12u8; 32]);
+    let key = AnyPublicKey::from(keypair.public_key());
+    let Ok(signature) = keypair.sign_any(b"message") else {
+        return false;
+    };
+    let round_trip = AnyPublicKey::from_bytes(&key.to_bytes()).ok() == Some(key.clone())
+        && AnySignature::from_bytes(&signature.to_bytes()).ok() == Some(signature.clone())
+        && key.to_bytes()[0] == SignatureAlgorithm::Ed25519.id();
+    // Decoding goes through the same length checks as `new`.
+    let decoded = serde_json::to_string(&signature).ok().and_then(|j| serde_json::from_str::<AnySignature>(&j).ok());
+    let short_signature_rejected = decoded == Some(signature.clone())
+        && serde_json::from_str::<AnySignature>(r#"{"algorithm":"Ed25519","bytes":[1,2,3]}"#).is_err()
+        && serde_json::from_str::<AnyPublicKey>(r#"{"algorithm":"HybridEd25519MlDsa65","bytes":[1]}"#).is_err();
+
+    // A hybrid key whose Ed25519 half is this node's key.
+    let mut hybrid_bytes = keypair.public_key().as_bytes().to_vec();
+    hybrid_bytes.resize(SignatureAlgorithm::HybridEd25519MlDsa65.public_key_len(), 0xab);
+    let Ok(hybrid) = AnyPublicKey::new(SignatureAlgorithm::HybridEd25519MlDsa65, hybrid_bytes) else {
+        return false;
+    };
+
+    let local = supported_features();
+    let hybrid_only_if_both = negotiated_algorithm(negotiate_features(FEATURE_HYBRID_SIGNATURES, 0))
+        == SignatureAlgorithm::Ed25519
+        && negotiated_algorithm(negotiate_features(FEATURE_HYBRID_SIGNATURES, FEATURE_HYBRID_SIGNATURES))
+            == SignatureAlgorithm::HybridEd25519MlDsa65
+        && (local & FEATURE_HYBRID_SIGNATURES != 0) == cfg!(feature = "pq-hybrid");
+
+    round_trip
+        && hybrid_only_if_both
+        && NodeId::from_any_public_key(&key) == NodeId::from_public_key(keypair.public_key().as_bytes())
+        && NodeId::from_any_public_key(&hybrid) != NodeId::from_any_public_key(&key)
+        && hybrid.ed25519().is_ok_and(|k| k == keypair.public_key())
+        && AnyPublicKey::new(SignatureAlgorithm::Ed25519, vec![0; 33]).is_err()
+        && AnySignature::from_bytes(&[9u8; 65]).is_err()
+        && short_signature_rejected
+        && verify_any(&key, b"message", &signature).is_ok()
+        && verify_any(&key, b"other", &signature).is_err()
+        && verify_any(&hybrid, b"message", &signature).is_err()
+        && test_hybrid_signatures()
+}
+
+#[cfg(feature = "pq-hybrid")]
+fn test_hybrid_signatures() -> bool {
+    use opennet_core::types::AnySignature;
+    use opennet_core::NodeId;
+    use opennet_identity::algorithm::{verify_any, HybridKeyPair};
+
+    let keypair = HybridKeyPair::generate(&[13u8; 32]);
+    let key = keypair.public_key_any();
+    let Ok(signature) = keypair.sign_any(b"message") else {
+        return false;
+    };
+    // The Ed25519 half alone verifies neither as hybrid nor as plain Ed25519.
+    let (Ok(classic_sig), Ok(classic_key)) = (signature.ed25519(), key.ed25519()) else {
+        return false;
+    };
+    let stripped = AnySignature::from(classic_sig);
+    let classic = classic_key.into();
+
+    verify_any(&key, b"message", &signature).is_ok()
+        && verify_any(&key, b"other", &signature).is_err()
+        && verify_any(&key, b"message", &stripped).is_err()
+        && verify_any(&classic, b"message", &stripped).is_err()
+        && NodeId::from_any_public_key(&key) != NodeId::from_public_key(classic_key.as_bytes())
+}
+
+#[cfg(not(feature = "pq-hybrid"))]
+fn test_hybrid_signatures() -> bool {
+    true
+}
+
+/// A node backed by the remote signer daemon signs, rotates and verifies
+/// exactly as one holding the key in memory. The socket is private to its
+/// owner from the start, and a stalled client does not hold up others.
+#[cfg(unix)]
+pub fn test_remote_signer() -> bool {
+    use opennet_identity::signer::{RemoteSigner, SignerDaemon};
+    use std::time::Duration;
+
+    let dir = std::env::temp_dir().join(format!("opennet-signer-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    if std::fs::create_dir_all(&dir).is_err() {
+        return false;
+    }
+    let path = dir.join("signer.sock");
+    let local = KeyPair::generate(&[3u8; 32]);
+    let daemon = match SignerDaemon::bind(&path, KeyPair::generate(&[3u8; 32])) {
+        Ok(daemon) => daemon,
+        Err(_) => return false,
+    };
+    let private = private_mode(&path)
+        && std::fs::read_dir(&dir).map(|d| d.count() == 1).unwrap_or(false);
+    daemon.spawn();
+
+    // Connects and never sends a request.
+    let _stalled = std::os::unix::net::UnixStream::connect(&path);
+    let remote = match RemoteSigner::with_timeout(&path, Duration::from_secs(2)) {
+        Ok(remote) => remote,
+        Err(_) => return false,
+    };
+    let same_key = remote.public_key() == local.public_key();
+    let mut identity = NodeIdentity::new(remote, 0);
+    let signed = identity
+        .sign(b"hello")
+        .map(|sig| verify_signature(&local.public_key(), b"hello", &sig).is_ok())
+        .unwrap_or(false);
+    let rotated = identity
+        .rotate(KeyPair::generate(&[4u8; 32]), 1)
+        .map(|req| {
+            verify_signature(&local.public_key(), &req.old_key_signing_bytes(), &req.old_key_signature).is_ok()
+        })
+        .unwrap_or(false);
+
+    let _ = std::fs::remove_dir_all(&dir);
+    private && same_key && signed && rotated
+}
+
+#[cfg(unix)]
+fn private_mode(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).map(|m| m.permissions().mode() & 0o777 == 0o600).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn private_mode(_path: &std::path::Path) -> bool {
+    true
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
@@ -3,3 +3,5 @@ pub mod crypto;
 pub mod resolver;
 pub mod trust;
 pub mod fsm;
+pub mod time;
+pub mod transport;
@@ -0,0 +1,137 @@
+//! Transport admission compliance.
+
+use opennet_core::NodeId;
+use opennet_identity::rotation::PeerKeyRegistry;
+use opennet_transport::admission::{
+    AdmissionControl, ChallengeResponse, CookieIssuer, IpPrefix, KindLimits, RateLimit, RateLimitConfig, TokenBucket,
+};
+use opennet_node::integration::transport::TransportIntegration;
+use opennet_node::integration::trust::TrustIntegration;
+use opennet_transport::session::{SessionBinding, SessionManager};
+use opennet_transport::TransportError;
+use opennet_trust::weight::TrustWeight;
+use std::net::IpAddr;
+
+fn limits() -> RateLimitConfig {
+    let kind = |node, prefix, global| KindLimits {
+        per_node: RateLimit::new(node, 1),
+        per_prefix: RateLimit::new(prefix, 1),
+        global: RateLimit::new(global, 1),
+    };
+    RateLimitConfig {
+        handshake: kind(2, 3, 8),
+        stream: kind(2, 4, 100),
+        message: kind(2, 4, 100),
+        trust_multiplier: 0,
+        challenge_threshold_permille: 250,
+        challenge_difficulty: 4,
+        challenge_window_ms: 1_000,
+        violation_threshold: 2,
+        ..RateLimitConfig::default()
+    }
+}
+
+fn addr(s: &str) -> IpAddr {
+    s.parse().expect("address")
+}
+
+/// Buckets refill with time, prefixes share one allowance, handshake
+/// NodeIds are charged only after authentication, challenges are bound to
+/// the prefix, accepted once and expire, and repeated violations emit a
+/// penalty. Sessions follow a peer's rotation one at a time.
+pub fn test_admission_control() -> bool {
+    let mut bucket = TokenBucket::new(2, 1, 0);
+    let refill = bucket.try_take(2, 0)
+        && !bucket.try_take(1, 999)
+        && bucket.try_take(1, 1_000)
+        && bucket.available(10_000) == 2;
+
+    // Two hosts in one /24 share its three handshakes; another /24 is separate.
+    let mut ac = AdmissionControl::with_cookie_secret(0.3, limits(), [1; 32]);
+    let same_prefix = ac.admit_handshake(addr("10.0.0.1"), 0, None).is_ok()
+        && ac.admit_handshake(addr("10.0.0.2"), 0, None).is_ok()
+        && ac.admit_handshake(addr("10.0.0.3"), 0, None).is_ok()
+        && matches!(ac.admit_handshake(addr("10.0.0.4"), 0, None), Err(TransportError::RateLimited))
+        && ac.admit_handshake(addr("10.0.1.1"), 0, None).is_ok();
+
+    // Claiming a NodeId costs it nothing until the peer is authenticated.
+    let victim = NodeId::from_bytes([9; 32]);
+    let per_node = ac.admit_peer(&victim, 0.0, 0).is_ok()
+        && ac.admit_peer(&victim, 0.0, 0).is_ok()
+        && matches!(ac.admit_peer(&victim, 0.0, 0), Err(TransportError::RateLimited))
+        && ac.admit_peer(&victim, 0.0, 1_000).is_ok();
+
+    // Drain the global allowance until handshakes need a challenge.
+    let mut ac = AdmissionControl::with_cookie_secret(0.3, limits(), [1; 32]);
+    for i in 0..7u8 {
+        let _ = ac.admit_handshake(IpAddr::from([10, i, 0, 1]), 0, None);
+    }
+    let src = addr("192.0.2.1");
+    let challenge = match ac.admit_handshake(src, 0, None) {
+        Err(TransportError::ChallengeRequired(c)) => c,
+        _ => return false,
+    };
+    let solved = ChallengeResponse::solve(&challenge);
+    let wrong_cookie = ChallengeResponse { cookie: [0; 32], nonce: solved.nonce };
+    let challenged = matches!(ac.admit_handshake(src, 0, Some(&wrong_cookie)), Err(TransportError::InvalidChallenge))
+        && ac.admit_handshake(src, 0, Some(&solved)).is_ok()
+        && matches!(ac.admit_handshake(src, 0, Some(&solved)), Err(TransportError::InvalidChallenge));
+
+    let prefix = |s| IpPrefix::from_addr(addr(s), 24, 48);
+    let mut issuer = CookieIssuer::new([2; 32], 1_000, 4);
+    let c = issuer.issue(&prefix("192.0.2.1"), 500);
+    let first = ChallengeResponse::solve(&c);
+    let second = ChallengeResponse::solve_from(&c, first.nonce + 1);
+    let third = ChallengeResponse::solve_from(&c, second.nonce + 1);
+    let cookies = !issuer.verify(&prefix("198.51.100.1"), &first, 500)
+        && issuer.verify(&prefix("192.0.2.9"), &first, 500)
+        && !issuer.verify(&prefix("192.0.2.9"), &first, 1_500)
+        && issuer.verify(&prefix("192.0.2.1"), &second, 1_999)
+        && !issuer.verify(&prefix("192.0.2.1"), &third, 2_000)
+        && issuer.spent_count() == 0;
+
+    // Authenticated peers that keep exceeding limits are penalized.
+    let mut ac = AdmissionControl::with_cookie_secret(0.3, limits(), [1; 32]);
+    let peer = NodeId::from_bytes([3; 32]);
+    let rejected = (0..6).filter(|_| ac.admit_stream(&peer, addr("10.0.0.1"), 0.0, 0).is_err()).count();
+    let events = ac.drain_events();
+    let penalty = rejected == 4 && events.len() == 2 && events.iter().all(|e| e.node_id == peer);
+
+    // The node applies them to the peer's trust weight.
+    let mut transport = TransportIntegration::with_admission(0.3, limits());
+    let mut trust = TrustIntegration::new();
+    trust.graph_mut().upsert_node(peer, TrustWeight::from_raw(500_000));
+    let no_session = matches!(
+        transport.on_message(&peer, addr("10.0.0.1"), 0, &mut trust),
+        Err(TransportError::SessionInvalid)
+    );
+    let mut keys = PeerKeyRegistry::new();
+    let opened = transport.accept_session(SessionBinding::new(peer, 1), 7, 0, &mut trust, &keys).is_ok();
+    let flooded = (0..4).filter(|_| transport.on_message(&peer, addr("10.0.0.1"), 0, &mut trust).is_err()).count();
+    let node = no_session && opened && flooded == 2 && trust.get_trust(&peer) == 0.4;
+
+    // Revoked epochs get no new sessions and lose the ones they had.
+    keys.revoke(peer, 1);
+    let refused = matches!(
+        transport.accept_session(SessionBinding::new(peer, 1), 8, 0, &mut trust, &keys),
+        Err(TransportError::HandshakeFailed(_))
+    );
+    let closed = transport.close_revoked(&keys) == 1 && !transport.sessions().has_peer(&peer);
+    let revoked = refused && closed;
+
+    // A rotation moves the newest session to the new epoch and closes the
+    // rest, but never replaces a session already open there.
+    let at = |epoch| SessionBinding::new(peer, epoch);
+    let mut sessions = SessionManager::new();
+    sessions.register(at(1), 1);
+    sessions.register(at(2), 2);
+    let moved = sessions.rebind(&peer, 3) == 1
+        && sessions.get(&at(3)) == Some(2)
+        && sessions.get(&at(1)).is_none()
+        && sessions.get(&at(2)).is_none();
+    sessions.register(at(2), 4);
+    let kept = sessions.rebind(&peer, 3) == 0 && sessions.get(&at(3)) == Some(2) && sessions.get(&at(2)).is_none();
+    let rebound = moved && kept;
+
+    refill && same_prefix && per_node && challenged && cookies && penalty && node && revoked && rebound
+}
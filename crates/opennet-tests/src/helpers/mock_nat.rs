@@ -0,0 +1,104 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::{IpAddr, SocketAddr};
+
+/// NAT mapping and filtering behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NatBehavior {
+    /// One public port per private socket; inbound only from addresses
+    /// the private socket has sent to.
+    PortRestricted,
+    /// One public port per (private socket, destination); inbound only
+    /// from that destination.
+    Symmetric,
+}
+
+struct SimulatedNat {
+    behavior: NatBehavior,
+    public_ip: IpAddr,
+    next_port: u16,
+    /// (private, destination if symmetric) -> public port.
+    mappings: BTreeMap<(SocketAddr, Option<SocketAddr>), u16>,
+    /// public port -> (private, remotes allowed in).
+    ports: BTreeMap<u16, (SocketAddr, BTreeSet<SocketAddr>)>,
+}
+
+impl SimulatedNat {
+    fn outbound(&mut self, private: SocketAddr, dst: SocketAddr) -> SocketAddr {
+        let key = match self.behavior {
+            NatBehavior::PortRestricted => (private, None),
+            NatBehavior::Symmetric => (private, Some(dst)),
+        };
+        let port = match self.mappings.get(&key) {
+            Some(port) => *port,
+            None => {
+                let port = self.next_port;
+                self.next_port += 1;
+                self.mappings.insert(key, port);
+                self.ports.insert(port, (private, BTreeSet::new()));
+                port
+            }
+        };
+        if let Some((_, allowed)) = self.ports.get_mut(&port) {
+            allowed.insert(dst);
+        }
+        SocketAddr::new(self.public_ip, port)
+    }
+
+    fn inbound(&self, port: u16, from: SocketAddr) -> Option<SocketAddr> {
+        let (private, allowed) = self.ports.get(&port)?;
+        allowed.contains(&from).then_some(*private)
+    }
+}
+
+/// In-process UDP network with simulated NATs.
+pub struct NatNetwork {
+    nats: Vec<SimulatedNat>,
+    /// private address -> NAT index (absent for public hosts).
+    hosts: BTreeMap<SocketAddr, usize>,
+}
+
+impl NatNetwork {
+    pub fn new() -> Self {
+        Self { nats: Vec::new(), hosts: BTreeMap::new() }
+    }
+
+    /// Add a NAT with the given public IP, returning its index.
+    pub fn add_nat(&mut self, public_ip: IpAddr, behavior: NatBehavior) -> usize {
+        self.nats.push(SimulatedNat {
+            behavior,
+            public_ip,
+            next_port: 40000,
+            mappings: BTreeMap::new(),
+            ports: BTreeMap::new(),
+        });
+        self.nats.len() - 1
+    }
+
+    /// Place a private host behind a NAT.
+    pub fn add_host(&mut self, private: SocketAddr, nat: usize) {
+        self.hosts.insert(private, nat);
+    }
+
+    /// Send a datagram from `from` (a local socket) to `to` (as addressed).
+    ///
+    /// Returns the receiving local socket and the source address it sees,
+    /// or `None` if a NAT dropped the packet.
+    pub fn send(&mut self, from: SocketAddr, to: SocketAddr) -> Option<(SocketAddr, SocketAddr)> {
+        // Private addresses are only reachable from behind the same NAT.
+        if let Some(&dst_nat) = self.hosts.get(&to) {
+            return (self.hosts.get(&from) == Some(&dst_nat)).then_some((to, from));
+        }
+        let seen_from = match self.hosts.get(&from) {
+            Some(&nat) => self.nats[nat].outbound(from, to),
+            None => from,
+        };
+        match self.nats.iter().find(|nat| nat.public_ip == to.ip()) {
+            Some(nat) => nat.inbound(to.port(), seen_from).map(|private| (private, seen_from)),
+            None => Some((to, seen_from)),
+        }
+    }
+}
+
+impl Default for NatNetwork {
+    fn default() -> Self { Self::new() }
+}
@@ -1,3 +1,4 @@
 pub mod mock_time;
 pub mod mock_network;
+pub mod mock_nat;
 pub mod test_node;
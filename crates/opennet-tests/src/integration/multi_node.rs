@@ -1,3 +1,784 @@
+use opennet_core::NodeId;
+use opennet_transport::nat::{coordinate, HolePunch, PunchAction, PunchConfig, RelayTable};
+use opennet_wire::messages::RelayData;
+use crate::helpers::mock_nat::{NatBehavior, NatNetwork};
+use std::net::SocketAddr;
+
 pub async fn test_peer_discovery() -> bool { true }
 pub async fn test_trust_exchange() -> bool { true }
 pub async fn test_revocation_propagation() -> bool { true }
+
+/// Two port-restricted NATs connect via a rendezvous.
+pub fn test_nat_hole_punching() -> bool {
+    simulate_punch(NatBehavior::PortRestricted, NatBehavior::PortRestricted) == PunchOutcome::Connected
+}
+
+/// Two symmetric NATs cannot be punched and fall back to relay.
+pub fn test_symmetric_nat_relay_fallback() -> bool {
+    simulate_punch(NatBehavior::Symmetric, NatBehavior::Symmetric) == PunchOutcome::Relayed
+}
+
+/// The rendezvous opens a circuit only for a punch it coordinated, asked
+/// for by the initiator itself, and forwards only between its two ends
+/// with the circuit nonce, up to the byte budget. Every punch and relay
+/// message type passes wire validation.
+pub fn test_relay_circuits() -> bool {
+    use opennet_core::types::Timestamp;
+    use opennet_wire::messages::{MessageType, PunchRequest, RelayRequest};
+    use opennet_wire::validate_message_type;
+
+    let a = NodeId::from_bytes([1u8; 32]);
+    let b = NodeId::from_bytes([2u8; 32]);
+    let m = NodeId::from_bytes([4u8; 32]);
+    let punch = PunchRequest { initiator: a, target: b, initiator_addrs: Vec::new(), nonce: 7, timestamp: Timestamp::ZERO };
+    let request = RelayRequest { initiator: a, target: b, nonce: 7 };
+    let data = |peer, nonce, len| RelayData { peer, nonce, payload: vec![0u8; len] };
+
+    let mut relay = RelayTable::new(4).with_byte_limit(100);
+    let uncoordinated = relay.open(&a, &request, true).is_err();
+    relay.on_punch_request(&punch);
+    let spoofed = relay.open(&m, &request, true).is_err();
+    let wrong_nonce = relay.open(&a, &RelayRequest { nonce: 8, ..request.clone() }, true).is_err();
+    let target_gone = relay.open(&a, &request, false).is_err();
+    let opened = relay.open(&a, &request, true).is_ok();
+
+    let forwarded = matches!(relay.forward(&a, &data(b, 7, 60)), Ok((to, out)) if to == b && out == data(a, 7, 60));
+    let back = matches!(relay.forward(&b, &data(a, 7, 30)), Ok((to, _)) if to == a);
+    let outsider = relay.forward(&m, &data(b, 7, 1)).is_err();
+    let bad_nonce = relay.forward(&a, &data(b, 8, 1)).is_err();
+    let over_budget = relay.forward(&a, &data(b, 7, 11)).is_err() && relay.is_empty();
+
+    let known = [MessageType::PunchRequest, MessageType::PunchConnect, MessageType::RelayRequest, MessageType::RelayData]
+        .iter()
+        .all(|t| validate_message_type(*t as u16).is_ok())
+        && validate_message_type(0x0044).is_err();
+
+    uncoordinated && spoofed && wrong_nonce && target_gone && opened
+        && forwarded && back && outsider && bad_nonce && over_budget && known
+}
+
+/// The responder reflects the address it saw the initiator connect from;
+/// two peers agreeing on it confirm the initiator's public address. Each
+/// side signs the handshake transcript, which covers both full keys, and
+/// a key is accepted only if it derives the sender's NodeId or is the key
+/// known for its epoch. Once hybrid signatures are negotiated an Ed25519
+/// key is refused rather than used instead.
+pub fn test_observed_addr_reflection() -> bool {
+    use opennet_core::types::{AnyPublicKey, SignatureAlgorithm, Timestamp};
+    use opennet_core::{Epoch, FEATURE_HYBRID_SIGNATURES};
+    use opennet_identity::KeyPair;
+    use opennet_transport::handshake::initiator::HandshakeInitiator;
+    use opennet_transport::handshake::responder::HandshakeResponder;
+    use opennet_transport::handshake::verification::{transcript, verify_handshake_any};
+    use opennet_transport::nat::ObservedAddrs;
+    use opennet_wire::messages::{NodeHello, NodeWelcome};
+
+    let epoch = Epoch { id: 1, start_time: 0, max_duration: opennet_core::MAX_EPOCH_DURATION, key_hash: [0; 32] };
+    let initiator_keys = KeyPair::generate(&[1; 32]);
+    let initiator_key = AnyPublicKey::from(initiator_keys.public_key());
+    let initiator = HandshakeInitiator::new(NodeId::from_any_public_key(&initiator_key), epoch.clone(), initiator_key);
+    let public: SocketAddr = "198.51.100.1:40000".parse().unwrap();
+    let hello = initiator.hello(Timestamp::new(10));
+
+    let mut observed = ObservedAddrs::default();
+    let mut features = Vec::new();
+    let mut rejected = true;
+    for seed in [2u8, 3] {
+        let keys = KeyPair::generate(&[seed; 32]);
+        let key = AnyPublicKey::from(keys.public_key());
+        // The second responder has rotated: its key no longer derives its
+        // NodeId and is only accepted as the key known for its epoch.
+        let (node_id, known) = match seed {
+            2 => (NodeId::from_any_public_key(&key), None),
+            _ => (NodeId::from_bytes([seed; 32]), Some(keys.public_key())),
+        };
+        let responder = HandshakeResponder::new(node_id, epoch.clone(), key.clone());
+        let Ok(welcome) = responder.welcome(&hello, public, Timestamp::new(10), &keys) else {
+            return false;
+        };
+
+        // A tampered welcome, or one presenting a hybrid key with the same
+        // Ed25519 half, does not verify and records nothing.
+        let mut untouched = ObservedAddrs::default();
+        let tampered = NodeWelcome { timestamp: Timestamp::new(11), ..welcome.clone() };
+        let mut hybrid_bytes = keys.public_key().as_bytes().to_vec();
+        hybrid_bytes.resize(SignatureAlgorithm::HybridEd25519MlDsa65.public_key_len(), 0xab);
+        let Ok(hybrid) = AnyPublicKey::new(SignatureAlgorithm::HybridEd25519MlDsa65, hybrid_bytes) else {
+            return false;
+        };
+        let swapped = NodeWelcome { public_key: hybrid, ..welcome.clone() };
+        rejected &= initiator.on_welcome(&hello, &tampered, known.as_ref(), &mut untouched).is_err()
+            && initiator.on_welcome(&hello, &swapped, known.as_ref(), &mut untouched).is_err()
+            && untouched.public_addr().is_none();
+        if known.is_some() {
+            rejected &= initiator.on_welcome(&hello, &welcome, None, &mut untouched).is_err();
+        }
+
+        match initiator.on_welcome(&hello, &welcome, known.as_ref(), &mut observed) {
+            Ok(accepted) => features.push(accepted),
+            Err(_) => return false,
+        }
+
+        // The initiator proves its key over the same transcript, and
+        // cannot present someone else's.
+        let Ok(signature) = initiator.sign(&hello, &welcome, &initiator_keys) else {
+            return false;
+        };
+        let impostor = NodeHello { public_key: key.clone(), ..hello.clone() };
+        rejected &= responder.verify_initiator(&hello, &welcome, None, &signature).is_ok()
+            && responder.verify_initiator(&hello, &tampered, None, &signature).is_err()
+            && responder.verify_initiator(&impostor, &welcome, Some(&keys.public_key()), &signature).is_err();
+    }
+
+    // Both sides forcing hybrid signatures refuse to fall back to Ed25519.
+    let keys = KeyPair::generate(&[4; 32]);
+    let key = AnyPublicKey::from(keys.public_key());
+    let forced = HandshakeResponder::new(NodeId::from_any_public_key(&key), epoch.clone(), key.clone())
+        .with_features(FEATURE_HYBRID_SIGNATURES);
+    let forced_hello = NodeHello { features: FEATURE_HYBRID_SIGNATURES, ..hello.clone() };
+    let Ok(plain) = HandshakeResponder::new(NodeId::from_any_public_key(&key), epoch, key.clone())
+        .welcome(&hello, public, Timestamp::new(10), &keys)
+    else {
+        return false;
+    };
+    let signature = plain.signature.clone();
+    let no_downgrade = forced.welcome(&forced_hello, public, Timestamp::new(10), &keys).is_err()
+        && signature.is_some_and(|sig| {
+            let t = transcript(&hello, &plain);
+            verify_handshake_any(&key, SignatureAlgorithm::Ed25519, &t, &sig).is_ok_and(|ok| ok)
+                && verify_handshake_any(&key, SignatureAlgorithm::HybridEd25519MlDsa65, &t, &sig).is_ok_and(|ok| !ok)
+        });
+
+    observed.public_addr() == Some(public) && features == [0, 0] && rejected && no_downgrade
+}
+
+/// A rotation gossips A -> B -> C, is not echoed back, leaves B's trust
+/// edges to A in place, and a second rotation signed by A's old key is
+/// flagged.
+pub fn test_rotation_gossip() -> bool {
+    use opennet_core::types::Timestamp;
+    use opennet_identity::rotation::{RotationOutcome, RotationPolicy, RotationRequest};
+    use opennet_identity::{IdentityEvent, KeyPair, NodeIdentity};
+    use opennet_node::integration::IdentityIntegration;
+    use opennet_node::integration::transport::TransportIntegration;
+    use opennet_node::integration::trust::TrustIntegration;
+    use opennet_trust::graph::TrustEdge;
+    use opennet_trust::weight::TrustWeight;
+
+    let node = |seed: u8| {
+        IdentityIntegration::new(NodeIdentity::new(KeyPair::generate(&[seed; 32]), 0), RotationPolicy::default())
+    };
+    let (mut a, mut b, mut c) = (node(1), node(2), node(3));
+    let a_id = *a.identity().node_id();
+    let b_id = *b.identity().node_id();
+    let c_id = *c.identity().node_id();
+    let a_key = a.identity().public_key();
+    b.peers().insert_known(a_id, 1, a_key, 0);
+    c.peers().insert_known(a_id, 1, a_key, 0);
+
+    let mut transport = TransportIntegration::new();
+    let mut b_trust = TrustIntegration::new();
+    b_trust.graph_mut().upsert_edge(TrustEdge::new(b_id, a_id, TrustWeight::from_f64(0.5), 1));
+
+    if a.rotate_now(100, &mut transport).is_err() {
+        return false;
+    }
+    let from_a = a.drain_rotations().remove(0);
+    let at_b = b.on_rotation_message(a_id, from_a.message.clone(), &mut transport);
+    let relayed = b.drain_rotations();
+    let at_c = c.on_rotation_message(b_id, relayed[0].message.clone(), &mut transport);
+    let echo = b.on_rotation_message(c_id, relayed[0].message.clone(), &mut transport);
+
+    let forged = RotationRequest::create(
+        a_id,
+        1,
+        Timestamp::new(150),
+        &KeyPair::generate(&[1u8; 32]),
+        &KeyPair::generate(&[9u8; 32]),
+    );
+    let conflict = forged.map(|f| b.on_rotation_message(c_id, f.into(), &mut transport));
+    let kept = b_trust
+        .graph()
+        .get_edges(&b_id)
+        .and_then(|edges| edges.get(&a_id))
+        .map(|edge| edge.last_updated == 1)
+        .unwrap_or(false);
+
+    matches!(at_b, Ok(RotationOutcome::Accepted { to_epoch: 2, .. }))
+        && relayed.len() == 1
+        && relayed[0].skip == Some(a_id)
+        && matches!(at_c, Ok(RotationOutcome::Accepted { .. }))
+        && matches!(echo, Ok(RotationOutcome::Duplicate))
+        && b.drain_rotations().is_empty()
+        && matches!(conflict, Ok(Ok(RotationOutcome::Conflict { .. })))
+        && matches!(b.drain_identity_events().as_slice(), [IdentityEvent::CompromiseDetected { .. }])
+        && b.peers().current(&a_id).map(|(epoch, _)| epoch) == Some(2)
+        && kept
+}
+
+/// Evidence of A's old key signing two rotations is gossiped B -> C,
+/// reported once, not re-relayed, and a bundle about the wrong node is
+/// rejected.
+pub fn test_evidence_gossip() -> bool {
+    use opennet_core::types::Timestamp;
+    use opennet_identity::rotation::{RotationPolicy, RotationRequest};
+    use opennet_identity::{CompromiseEvidence, IdentityEvent, KeyPair, NodeIdentity};
+    use opennet_node::integration::IdentityIntegration;
+    use opennet_node::integration::transport::TransportIntegration;
+    use opennet_node::integration::trust::TrustIntegration;
+
+    let node = |seed: u8| {
+        IdentityIntegration::new(NodeIdentity::new(KeyPair::generate(&[seed; 32]), 0), RotationPolicy::default())
+    };
+    let (mut b, mut c) = (node(2), node(3));
+    let a_id = *NodeIdentity::new(KeyPair::generate(&[1u8; 32]), 0).node_id();
+    let b_id = *b.identity().node_id();
+    let a_key = KeyPair::generate(&[1u8; 32]).public_key();
+    b.peers().insert_known(a_id, 1, a_key, 0);
+
+    let rotation = |seed: u8| {
+        RotationRequest::create(a_id, 1, Timestamp::new(100), &KeyPair::generate(&[1u8; 32]), &KeyPair::generate(&[seed; 32]))
+    };
+    let (Ok(first), Ok(second)) = (rotation(7), rotation(8)) else {
+        return false;
+    };
+    let mut transport = TransportIntegration::new();
+    let accepted = b.on_rotation_message(a_id, first.into(), &mut transport).is_ok();
+    let conflict = b.on_rotation_message(a_id, second.into(), &mut transport).is_ok();
+    let gossip = b.drain_evidence();
+    let Some(outgoing) = gossip.first() else {
+        return false;
+    };
+
+    // C has never heard of A; the evidence binds to A's genesis key.
+    let trust = TrustIntegration::new();
+    let received = c.on_evidence_message(b_id, outgoing.message.clone(), &trust);
+    let relayed = c.drain_evidence();
+    let again = c.on_evidence_message(b_id, outgoing.message.clone(), &trust);
+    let mut misattributed = outgoing.message.clone();
+    misattributed.node_id = b_id;
+
+    accepted
+        && conflict
+        && gossip.len() == 1
+        && outgoing.skip.is_none()
+        && matches!(received, Ok(Some(CompromiseEvidence::ConflictingEpoch { .. })))
+        && relayed.len() == 1
+        && relayed[0].skip == Some(b_id)
+        && matches!(again, Ok(None))
+        && c.drain_evidence().is_empty()
+        && c.on_evidence_message(b_id, misattributed, &trust).is_err()
+        && matches!(c.drain_identity_events().as_slice(), [IdentityEvent::CompromiseDetected { .. }])
+}
+
+/// Observations only count from trusted observers: a timing conflict is
+/// found once a trusted observer sees a second claim, and the resulting
+/// evidence is refused by a node that trusts neither observer.
+pub fn test_observation_trust() -> bool {
+    use opennet_core::types::Timestamp;
+    use opennet_identity::rotation::RotationPolicy;
+    use opennet_identity::{CompromiseEvidence, KeyPair, NodeIdentity, Observation, SignedClaim};
+    use opennet_node::integration::IdentityIntegration;
+    use opennet_node::integration::trust::TrustIntegration;
+    use opennet_trust::weight::TrustWeight;
+    use opennet_wire::messages::EvidenceMessage;
+    use std::net::IpAddr;
+
+    let node = |seed: u8| {
+        IdentityIntegration::new(NodeIdentity::new(KeyPair::generate(&[seed; 32]), 0), RotationPolicy::default())
+    };
+    let (mut b, mut c) = (node(2), node(3));
+    let a = KeyPair::generate(&[1u8; 32]);
+    let a_id = *NodeIdentity::new(KeyPair::generate(&[1u8; 32]), 0).node_id();
+    b.peers().insert_known(a_id, 1, a.public_key(), 0);
+    c.peers().insert_known(a_id, 1, a.public_key(), 0);
+
+    let observe = |seq: u64, seed: u8, source: [u8; 4]| {
+        let claim = SignedClaim::sign(a_id, 1, seq, Timestamp::new(100), &[seed], &a).ok()?;
+        let observer_id = *NodeIdentity::new(KeyPair::generate(&[seed; 32]), 0).node_id();
+        Observation::sign(claim, observer_id, IpAddr::from(source), 5_000, &KeyPair::generate(&[seed; 32])).ok()
+    };
+    let (Some(first), Some(untrusted), Some(trusted)) =
+        (observe(1, 10, [10, 0, 0, 1]), observe(2, 11, [192, 0, 2, 1]), observe(3, 12, [198, 51, 100, 1]))
+    else {
+        return false;
+    };
+    let mut trust = TrustIntegration::new();
+    for observer in [first.observer, trusted.observer] {
+        trust.graph_mut().upsert_node(observer, TrustWeight::from_f64(0.5));
+    }
+
+    let seen_first = b.on_observation(first, 10, &trust);
+    let ignored = b.on_observation(untrusted, 10, &trust);
+    let evidence = match b.on_observation(trusted, 10, &trust) {
+        Ok(Some(evidence)) => evidence,
+        _ => return false,
+    };
+    let message = EvidenceMessage { node_id: a_id, kind: evidence.kind(), bundle: evidence.encode() };
+    let b_id = *b.identity().node_id();
+
+    matches!(seen_first, Ok(None))
+        && matches!(ignored, Ok(None))
+        && matches!(evidence, CompromiseEvidence::ImpossibleTiming { .. })
+        && c.on_evidence_message(b_id, message.clone(), &TrustIntegration::new()).is_err()
+        && matches!(c.on_evidence_message(b_id, message, &trust), Ok(Some(_)))
+}
+
+/// A node that lost its epoch-2 key is revoked, two of its three
+/// pre-registered guardians vouch for a new key, and peers accept the
+/// recovered epoch 3 for the same NodeId while ignoring the stolen key.
+/// Guardian sets and certificates travel as gossip; attestations verify
+/// only under the guardian's current key, and the recovered rotation log
+/// verifies only together with its certificate.
+pub fn test_social_recovery() -> bool {
+    use opennet_core::types::Timestamp;
+    use opennet_identity::rotation::{PeerKeyRegistry, RotationLog, RotationRequest};
+    use opennet_identity::{KeyPair, NodeIdentity};
+    use opennet_node::integration::RevocationIntegration;
+    use opennet_revocation::quorum::QuorumValidator;
+    use opennet_revocation::recovery::{process_recovery, GuardianSet, PeerAttestation, RecoveryCertificate, RecoveryRequest};
+    use opennet_revocation::revocation::certificate::generate_self_revocation;
+    use opennet_revocation::revocation::trigger::RevocationTrigger;
+    use opennet_revocation::RevocationError;
+    use opennet_trust::graph::TrustGraph;
+    use opennet_wire::messages::{GuardianSetMessage, RecoveryMessage};
+
+    let mut owner = NodeIdentity::new(KeyPair::generate(&[1u8; 32]), 0);
+    let node_id = *owner.node_id();
+    let relay = NodeId::from_bytes([9u8; 32]);
+    let mut peers = PeerKeyRegistry::new();
+    peers.insert_known(node_id, 1, owner.public_key(), 0);
+    let mut guardians = Vec::new();
+    for seed in [11u8, 12, 13] {
+        let identity = NodeIdentity::new(KeyPair::generate(&[seed; 32]), 0);
+        peers.insert_known(*identity.node_id(), 1, identity.public_key(), 0);
+        guardians.push(identity);
+    }
+    let Ok(set) = GuardianSet::create(node_id, 1, 1, 2, guardians.iter().map(|g| *g.node_id()), owner.signer()) else {
+        return false;
+    };
+    let decoded = GuardianSet::decode(&set.encode()).is_ok_and(|d| d == set);
+    let mut network = RevocationIntegration::default();
+    let message = GuardianSetMessage { node_id, set: set.encode() };
+    let registered = matches!(network.on_guardian_set_message(relay, &message, &peers), Ok(true))
+        && matches!(network.on_guardian_set_message(relay, &message, &peers), Ok(false))
+        && network.drain_guardian_sets().len() == 1;
+
+    // A guardian rotates after the set was registered.
+    let old_guardian_key = KeyPair::generate(&[11u8; 32]);
+    let Ok(guardian_rotation) = guardians[0].rotate(KeyPair::generate(&[21u8; 32]), 50) else {
+        return false;
+    };
+
+    // The epoch-2 key is stolen; peers have followed the rotation.
+    let stolen = KeyPair::generate(&[2u8; 32]);
+    let Ok(rotation) = owner.rotate(KeyPair::generate(&[2u8; 32]), 100) else {
+        return false;
+    };
+    let followed = peers.apply(&guardian_rotation).is_ok() && peers.apply(&rotation).is_ok();
+
+    // The owner's pre-signed certificate revokes the stolen key, which
+    // freezes the guardian set.
+    let Ok(revocation_obj) = generate_self_revocation(node_id, 2, RevocationTrigger::KeyCompromise, Timestamp::new(200), &stolen)
+    else {
+        return false;
+    };
+    let Ok(revocation) = QuorumValidator::default().verify(revocation_obj.clone(), &peers, &TrustGraph::new()) else {
+        return false;
+    };
+    let revoked = matches!(network.submit(revocation_obj, Some(relay), &mut peers, &TrustGraph::new()), Ok(Some(_)));
+    let hijack = GuardianSet::create(node_id, 2, 2, 1, [NodeId::from_bytes([99u8; 32])], &stolen)
+        .map(|set| network.submit_guardian_set(set, Some(relay), &peers).is_err())
+        .unwrap_or(false);
+
+    let recovered_key = KeyPair::generate(&[3u8; 32]);
+    let Ok(request) = RecoveryRequest::create(node_id, 2, Timestamp::new(300), &recovered_key) else {
+        return false;
+    };
+    let attest = |g: &NodeIdentity| PeerAttestation::sign(&request, *g.node_id(), g.signer());
+    let outsider = NodeIdentity::new(KeyPair::generate(&[50u8; 32]), 0);
+    let (Ok(a), Ok(b), Ok(stale), Ok(forged)) = (
+        attest(&guardians[0]),
+        attest(&guardians[1]),
+        PeerAttestation::sign(&request, *guardians[0].node_id(), &old_guardian_key),
+        attest(&outsider),
+    ) else {
+        return false;
+    };
+    let Some(set) = network.guardians().get(&node_id) else {
+        return false;
+    };
+    let duplicate = process_recovery(&request, &[a.clone(), a.clone()], set, &revocation, &peers);
+    let rotated_away = process_recovery(&request, &[stale, b.clone()], set, &revocation, &peers);
+    let with_outsider = process_recovery(&request, &[a.clone(), forged], set, &revocation, &peers);
+    let recovery = match process_recovery(&request, &[a, b], set, &revocation, &peers) {
+        Ok(recovery) => recovery,
+        Err(_) => return false,
+    };
+    let encoded = recovery.certificate().encode();
+    let round_trip = RecoveryCertificate::decode(&encoded).is_ok_and(|c| &c == recovery.certificate());
+
+    // Only a registry that saw the revocation takes the recovered key.
+    let unrevoked = PeerKeyRegistry::new().apply_recovery(&recovery, 300).is_err();
+    let message = RecoveryMessage { node_id, certificate: encoded };
+    let gossiped = matches!(network.on_recovery_message(relay, &message, &mut peers), Ok(Some(_)))
+        && matches!(network.on_recovery_message(relay, &message, &mut peers), Ok(None))
+        && network.drain_recoveries().len() == 1;
+
+    let wrong_signer = NodeIdentity::recover(KeyPair::generate(&[4u8; 32]), &recovery, 300).is_err();
+    let Ok(restored) = NodeIdentity::recover(recovered_key, &recovery, 300) else {
+        return false;
+    };
+    let log = restored.rotation_log();
+    let log_checked = log.verify().is_err()
+        && log.verify_recovered(&recovery).is_ok_and(|key| key == restored.public_key())
+        && RotationLog::import(&log.export()).is_err()
+        && RotationLog::import_own(&log.export()).is_ok_and(|own| &own == log);
+    let stolen_rotation = RotationRequest::create(node_id, 3, Timestamp::new(400), &stolen, &KeyPair::generate(&[4u8; 32]));
+    let stolen_rejected = stolen_rotation.map(|r| peers.apply(&r).is_err()).unwrap_or(false);
+
+    decoded
+        && registered
+        && followed
+        && revoked
+        && hijack
+        && matches!(duplicate, Err(RevocationError::RecoveryFailed(_)))
+        && matches!(rotated_away, Err(RevocationError::RecoveryFailed(_)))
+        && matches!(with_outsider, Err(RevocationError::RecoveryFailed(_)))
+        && recovery.certificate().attestations.len() == 2
+        && round_trip
+        && unrevoked
+        && gossiped
+        && wrong_signer
+        && restored.node_id() == &node_id
+        && restored.epoch_id() == 3
+        && log_checked
+        && peers.current(&node_id) == Some((3, restored.public_key()))
+        && stolen_rejected
+}
+
+/// Announcements are sent periodically, verified into the directory and
+/// relayed once; refreshes are not relayed, and a later epoch's key is
+/// only accepted once the rotation proving it is known.
+pub fn test_identity_announcements() -> bool {
+    use opennet_identity::rotation::{RotationOutcome, RotationPolicy};
+    use opennet_identity::{DirectoryUpdate, IdentityAnnouncement, KeyPair, NodeIdentity};
+    use opennet_node::integration::IdentityIntegration;
+    use opennet_node::integration::transport::TransportIntegration;
+
+    let node = |seed: u8| {
+        IdentityIntegration::new(NodeIdentity::new(KeyPair::generate(&[seed; 32]), 0), RotationPolicy::default())
+    };
+    let (mut a, mut b, mut c) = (node(1), node(2), node(3));
+    let a_id = *a.identity().node_id();
+    let b_id = *b.identity().node_id();
+    let mut transport = TransportIntegration::new();
+
+    if a.tick(10, &mut transport).is_err() {
+        return false;
+    }
+    let first = a.drain_announcements();
+    let quiet = a.tick(20, &mut transport).is_ok() && a.drain_announcements().is_empty();
+    let Some(announced) = first.first() else {
+        return false;
+    };
+    let roundtrip = IdentityAnnouncement::decode(&announced.message.announcement)
+        .map(|ann| ann.verify().is_ok() && ann.encode() == announced.message.announcement)
+        .unwrap_or(false);
+
+    let at_b = b.on_announcement_message(a_id, announced.message.clone(), 10);
+    let relayed = b.drain_announcements();
+    let at_c = c.on_announcement_message(b_id, relayed[0].message.clone(), 11);
+    let replay = b.on_announcement_message(a_id, announced.message.clone(), 12);
+
+    let mut tampered = announced.message.clone();
+    let last = tampered.announcement.len() - 1;
+    tampered.announcement[last] ^= 1;
+    let forged = c.on_announcement_message(b_id, tampered, 13);
+
+    let refreshed = a.tick(3610, &mut transport).is_ok()
+        && a.drain_announcements()
+            .first()
+            .map(|g| matches!(b.on_announcement_message(a_id, g.message.clone(), 3610), Ok(DirectoryUpdate::Refreshed)))
+            .unwrap_or(false)
+        && b.drain_announcements().is_empty();
+
+    // After rotating, the epoch-2 announcement needs the rotation first.
+    if a.rotate_now(4000, &mut transport).is_err() {
+        return false;
+    }
+    let rotation = a.drain_rotations().remove(0);
+    let epoch2 = a.drain_announcements().remove(0);
+    let early = c.on_announcement_message(a_id, epoch2.message.clone(), 4000);
+    let rotated = c.on_rotation_message(a_id, rotation.message, &mut transport);
+    let new_key = a.identity().public_key();
+
+    first.len() == 1
+        && quiet
+        && roundtrip
+        && matches!(at_b, Ok(DirectoryUpdate::New))
+        && relayed.len() == 1
+        && relayed[0].skip == Some(a_id)
+        && matches!(at_c, Ok(DirectoryUpdate::New))
+        && matches!(replay, Ok(DirectoryUpdate::Stale))
+        && forged.is_err()
+        && refreshed
+        && early.is_err()
+        && matches!(rotated, Ok(RotationOutcome::Accepted { to_epoch: 2, .. }))
+        && c.directory().current_key(&a_id) == Some((2, new_key))
+        && b.directory().current_key(&a_id).map(|(epoch, _)| epoch) == Some(1)
+}
+
+/// A revocation passes only with enough trust behind it: signatures must
+/// verify under the signer's current, unrevoked key, each signer counts
+/// once, the subject's weight does not count, and the rest must reach the
+/// threshold share of the graph's weight. The subject's own signature
+/// authorizes the revocation by itself.
+pub fn test_quorum_revocation() -> bool {
+    use opennet_core::types::Timestamp;
+    use opennet_identity::rotation::PeerKeyRegistry;
+    use opennet_identity::{KeyPair, NodeIdentity};
+    use opennet_revocation::quorum::{Authorization, QuorumValidator};
+    use opennet_revocation::revocation::RevocationObject;
+    use opennet_revocation::RevocationError;
+    use opennet_trust::graph::TrustGraph;
+    use opennet_trust::weight::TrustWeight;
+
+    let voter = |seed: u8| {
+        let key = KeyPair::generate(&[seed; 32]);
+        (*NodeIdentity::new(KeyPair::generate(&[seed; 32]), 0).node_id(), key)
+    };
+    let subject = voter(1);
+    let voters = [voter(21), voter(22), voter(23), voter(24)];
+    let mut keys = PeerKeyRegistry::new();
+    let mut graph = TrustGraph::new();
+    graph.upsert_node(subject.0, TrustWeight::from_raw(500_000));
+    keys.insert_known(subject.0, 1, subject.1.public_key(), 0);
+    for ((id, key), weight) in voters.iter().zip([400_000, 300_000, 200_000, 100_000]) {
+        keys.insert_known(*id, 1, key.public_key(), 0);
+        graph.upsert_node(*id, TrustWeight::from_raw(weight));
+    }
+
+    let unsigned = RevocationObject {
+        node_id: subject.0,
+        revoked_epoch: 1,
+        reason: 0,
+        timestamp: Timestamp::new(1_000),
+        signatures: Vec::new(),
+    };
+    let signed_by = |signers: &[(&(opennet_core::NodeId, KeyPair), u64)]| {
+        let mut obj = unsigned.clone();
+        for ((id, key), epoch) in signers {
+            obj.add_signature(*id, *epoch, key).ok()?;
+        }
+        Some(obj)
+    };
+    let validator = QuorumValidator::default();
+    let check = |obj: Option<RevocationObject>| obj.map(|o| validator.validate(&o, &keys, &graph));
+
+    let empty = matches!(validator.validate(&unsigned, &keys, &graph), Err(RevocationError::Invalid(_)));
+    let mut unknown_reason = unsigned.clone();
+    unknown_reason.reason = 9;
+    let bad_reason = matches!(validator.validate(&unknown_reason, &keys, &graph), Err(RevocationError::Invalid(_)));
+
+    let a = &voters[0];
+    let b = &voters[1];
+    let alone = check(signed_by(&[(a, 1)]));
+    let repeated = check(signed_by(&[(a, 1), (a, 1), (a, 1)]));
+    // B's id with another key, and B under an epoch it never held.
+    let impostor = (b.0, KeyPair::generate(&[99u8; 32]));
+    let forged = check(signed_by(&[(a, 1), (&impostor, 1)]));
+    let wrong_epoch = check(signed_by(&[(a, 1), (b, 2)]));
+    let with_subject = check(signed_by(&[(a, 1), (&subject, 1)]));
+    let quorum = check(signed_by(&[(a, 1), (b, 1)]));
+
+    let not_met = |r: &Option<Result<_, RevocationError>>| matches!(r, Some(Err(RevocationError::QuorumNotMet)));
+
+    // C rotates away from epoch 1, and A's epoch 1 is revoked.
+    let c = &voters[2];
+    let c_rotated = (c.0, KeyPair::generate(&[33u8; 32]));
+    keys.insert_known(c.0, 2, c_rotated.1.public_key(), 10);
+    keys.revoke(a.0, 1);
+    let check = |obj: Option<RevocationObject>| obj.map(|o| validator.validate(&o, &keys, &graph));
+    let d = &voters[3];
+    let stale_key = check(signed_by(&[(b, 1), (c, 1), (d, 1)]));
+    let current_key = check(signed_by(&[(b, 1), (&c_rotated, 2), (d, 1)]));
+    let revoked_key = check(signed_by(&[(a, 1), (&c_rotated, 2)]));
+
+    empty
+        && bad_reason
+        && not_met(&alone)
+        && not_met(&repeated)
+        && not_met(&forged)
+        && not_met(&wrong_epoch)
+        && matches!(with_subject, Some(Ok(Authorization::SelfSigned)))
+        && matches!(quorum, Some(Ok(Authorization::Quorum(w))) if w.signed == 700_000 && w.total == 1_000_000)
+        && not_met(&stale_key)
+        && matches!(current_key, Some(Ok(Authorization::Quorum(w))) if w.signed == 600_000)
+        && not_met(&revoked_key)
+}
+
+/// A certificate signed by the revoked epoch's own key revokes it with no
+/// quorum, survives the file round trip, and is published by dropping it
+/// into the node's spool. Certificates from another key or naming an epoch
+/// the key never held are refused, and left in the spool for the operator.
+pub fn test_self_revocation() -> bool {
+    use opennet_core::types::Timestamp;
+    use opennet_identity::rotation::PeerKeyRegistry;
+    use opennet_identity::{KeyPair, NodeIdentity};
+    use opennet_node::integration::RevocationIntegration;
+    use opennet_revocation::quorum::Authorization;
+    use opennet_revocation::revocation::certificate::{
+        certificate_file_name, generate_self_revocation, verify_self_revocation,
+    };
+    use opennet_revocation::revocation::trigger::RevocationTrigger;
+    use opennet_revocation::revocation::RevocationObject;
+    use opennet_trust::graph::TrustGraph;
+    use opennet_trust::weight::TrustWeight;
+
+    let key = KeyPair::generate(&[31u8; 32]);
+    let id = *NodeIdentity::new(KeyPair::generate(&[31u8; 32]), 0).node_id();
+    let thief = KeyPair::generate(&[32u8; 32]);
+    let mut keys = PeerKeyRegistry::new();
+    keys.insert_known(id, 1, key.public_key(), 0);
+    let mut graph = TrustGraph::new();
+    graph.upsert_node(id, TrustWeight::from_raw(100_000));
+    graph.upsert_node(NodeId::from_bytes([7; 32]), TrustWeight::from_raw(900_000));
+
+    let at = Timestamp::from_millis(1_000_123);
+    let Ok(cert) = generate_self_revocation(id, 1, RevocationTrigger::KeyCompromise, at, &key) else {
+        return false;
+    };
+    let Ok(decoded) = RevocationObject::decode(&cert.encode()) else {
+        return false;
+    };
+    let round_trip = decoded.encode() == cert.encode()
+        && decoded.timestamp == at
+        && RevocationObject::decode(&cert.encode()[1..]).is_err();
+    let verifies = verify_self_revocation(&decoded, &keys).is_ok();
+    let forged = generate_self_revocation(id, 1, RevocationTrigger::Voluntary, at, &thief)
+        .is_ok_and(|c| verify_self_revocation(&c, &keys).is_err());
+    let wrong_epoch = generate_self_revocation(id, 2, RevocationTrigger::Voluntary, at, &key)
+        .is_ok_and(|c| verify_self_revocation(&c, &keys).is_err());
+    let Ok(message) = cert.to_message() else {
+        return false;
+    };
+
+    let spool = std::env::temp_dir().join(format!("opennet-self-revocation-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&spool);
+    if std::fs::create_dir_all(&spool).is_err() {
+        return false;
+    }
+    let rejected = generate_self_revocation(id, 2, RevocationTrigger::Voluntary, at, &thief)
+        .map(|c| (spool.join("unknown-epoch.rev"), c.encode()));
+    let written = rejected.is_ok_and(|(path, bytes)| std::fs::write(path, bytes).is_ok())
+        && std::fs::write(spool.join(certificate_file_name(&cert)), cert.encode()).is_ok();
+
+    let mut peer_keys = PeerKeyRegistry::new();
+    peer_keys.insert_known(id, 1, key.public_key(), 0);
+    let mut node = RevocationIntegration::default();
+    let imported = node.import_spool(&spool, &mut keys, &graph);
+    let left: Vec<_> = std::fs::read_dir(&spool)
+        .map(|d| d.filter_map(|e| e.ok().map(|e| e.file_name())).collect())
+        .unwrap_or_default();
+    let gossip = node.drain_revocations();
+    // A peer hears it over gossip and accepts it once.
+    let mut peer = RevocationIntegration::default();
+    let from = NodeId::from_bytes([7; 32]);
+    let first = peer.on_revocation_message(from, &message, &mut peer_keys, &graph);
+    let again = peer.on_revocation_message(from, &message, &mut peer_keys, &graph);
+    // The revoked key cannot be re-announced.
+    keys.insert_known(id, 1, thief.public_key(), 5);
+    let marked = keys.is_revoked(&id, 1)
+        && peer_keys.is_revoked(&id, 1)
+        && keys.key_at(&id, 1) == Some(key.public_key());
+    let _ = std::fs::remove_dir_all(&spool);
+
+    round_trip
+        && verifies
+        && forged
+        && wrong_epoch
+        && written
+        && matches!(imported, Ok(1))
+        && left == ["unknown-epoch.rev"]
+        && node.is_revoked(&id, 1)
+        && !node.is_revoked(&id, 2)
+        && gossip.len() == 1
+        && gossip[0].skip.is_none()
+        && matches!(first, Ok(Some(Authorization::SelfSigned)))
+        && matches!(again, Ok(None))
+        && peer.drain_revocations().iter().all(|g| g.skip == Some(from))
+        && marked
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PunchOutcome {
+    Connected,
+    Relayed,
+    Stuck,
+}
+
+fn simulate_punch(a_behavior: NatBehavior, b_behavior: NatBehavior) -> PunchOutcome {
+    let a_local: SocketAddr = "10.0.0.2:9000".parse().unwrap();
+    let b_local: SocketAddr = "192.168.1.2:9000".parse().unwrap();
+    let rendezvous: SocketAddr = "203.0.113.1:9000".parse().unwrap();
+
+    let mut net = NatNetwork::new();
+    let a_nat = net.add_nat("198.51.100.1".parse().unwrap(), a_behavior);
+    let b_nat = net.add_nat("198.51.100.2".parse().unwrap(), b_behavior);
+    net.add_host(a_local, a_nat);
+    net.add_host(b_local, b_nat);
+
+    // Both sides hold sessions with the rendezvous, which sees their mapped addresses.
+    let (Some((_, a_seen)), Some((_, b_seen))) = (net.send(a_local, rendezvous), net.send(b_local, rendezvous)) else {
+        return PunchOutcome::Stuck;
+    };
+
+    let a_id = NodeId::from_bytes([1u8; 32]);
+    let b_id = NodeId::from_bytes([2u8; 32]);
+    let r_id = NodeId::from_bytes([3u8; 32]);
+    let config = PunchConfig::default();
+
+    let (mut a, request) = HolePunch::initiate(a_id, b_id, r_id, vec![a_local], 7, 0, config);
+    let (to_a, to_b) = coordinate(&request, a_seen, b_seen, &config);
+    let mut circuits = RelayTable::default();
+    circuits.on_punch_request(&request);
+    a.on_connect(&to_a, 0);
+    let mut b = HolePunch::respond(b_id, r_id, &to_b, 0, config);
+
+    let mut relayed = false;
+    for now in (0..10_000).step_by(10) {
+        let mut queue: Vec<(bool, PunchAction)> = a.poll(now).into_iter().map(|x| (true, x))
+            .chain(b.poll(now).into_iter().map(|x| (false, x)))
+            .collect();
+        while let Some((from_a, action)) = queue.pop() {
+            match action {
+                PunchAction::SendProbe { to, packet } => {
+                    let from = if from_a { a_local } else { b_local };
+                    match net.send(from, to) {
+                        Some((dst, seen)) if dst == a_local => {
+                            queue.extend(a.on_probe(seen, &packet).into_iter().map(|x| (true, x)));
+                        }
+                        Some((dst, seen)) if dst == b_local => {
+                            queue.extend(b.on_probe(seen, &packet).into_iter().map(|x| (false, x)));
+                        }
+                        _ => {}
+                    }
+                }
+                PunchAction::Connected { .. } => {}
+                PunchAction::FallbackRelay { request, .. } => {
+                    let data = RelayData { peer: b_id, nonce: request.nonce, payload: b"hi".to_vec() };
+                    relayed = circuits.open(&a_id, &request, true).is_ok()
+                        && matches!(circuits.forward(&a_id, &data), Ok((to, out)) if to == b_id && out.peer == a_id);
+                }
+            }
+        }
+        if a.connected_addr().is_some() && b.connected_addr().is_some() {
+            return PunchOutcome::Connected;
+        }
+        if relayed {
+            return PunchOutcome::Relayed;
+        }
+    }
+    PunchOutcome::Stuck
+}
@@ -1,3 +1,198 @@
 pub async fn test_node_bootstrap() -> bool { true }
 pub async fn test_node_sync() -> bool { true }
 pub async fn test_node_active() -> bool { true }
+
+/// A node rotates its key once the configured fraction of the epoch has
+/// passed, persists the new key and log, and restores from them.
+pub fn test_scheduled_rotation() -> bool {
+    use opennet_identity::rotation::RotationPolicy;
+    use opennet_identity::{FileStorage, KdfParams, KeyPair, KeyUnlock, NodeIdentity};
+    use opennet_node::integration::{IdentityIntegration, IdentityStore};
+    use opennet_node::integration::transport::TransportIntegration;
+
+    let dir = std::env::temp_dir().join(format!("opennet-rotation-{}", std::process::id()));
+    if std::fs::create_dir_all(&dir).is_err() {
+        return false;
+    }
+    let store = || {
+        let storage = FileStorage::new(&dir, KeyUnlock::passphrase("pw"))
+            .with_kdf_params(KdfParams { m_cost_kib: 64, t_cost: 1, p_cost: 1 });
+        IdentityStore::new(Box::new(storage), "node", dir.join("rotation.log"))
+    };
+
+    let policy = RotationPolicy { rotate_at_permille: 500, retry_secs: 60 };
+    let due = opennet_core::MAX_EPOCH_DURATION / 2;
+    let mut transport = TransportIntegration::new();
+    let identity = NodeIdentity::new(KeyPair::generate(&[5u8; 32]), 0);
+    let node_id = *identity.node_id();
+
+    let ok = (|| {
+        let mut node = IdentityIntegration::new(identity, policy).with_store(store()).ok()?;
+        let early = node.tick(due - 1, &mut transport).ok()?;
+        let rotated = node.tick(due, &mut transport).ok()??;
+        let gossip = node.drain_rotations();
+        let reopened = IdentityIntegration::open(store(), policy).ok()?;
+        Some(
+            early.is_none()
+                && rotated.new_epoch == 2
+                && gossip.len() == 1 && gossip[0].message == rotated.clone().into()
+                && transport.local_epoch() == 2
+                && node.identity().epoch_id() == 2
+                && reopened.identity().epoch_id() == 2
+                && reopened.identity().node_id() == &node_id
+                && reopened.identity().public_key() == rotated.new_public_key,
+        )
+    })()
+    .unwrap_or(false);
+
+    let _ = std::fs::remove_dir_all(&dir);
+    ok
+}
+
+/// A node opened from its configuration creates and persists an identity
+/// on first start, rotates on the configured schedule, and ignores an
+/// epoch-expiry warning raised before that rotation. Revocation
+/// certificates in its spool, a peer's or its own, are imported. Replay
+/// state is kept in the data directory, and accepted sessions sample the
+/// peer's clock.
+pub fn test_runtime_rotation() -> bool {
+    use opennet_identity::rotation::RotationPolicy;
+    use opennet_core::types::Timestamp;
+    use opennet_identity::{FileStorage, KdfParams, KeyPair, KeyUnlock, SecureStorage};
+    use opennet_node::integration::time::REPLAY_STATE_FILE;
+    use opennet_node::{NodeConfig, NodeRuntime};
+    use opennet_revocation::revocation::certificate::{
+        certificate_file_name, generate_self_revocation, CERTIFICATE_SPOOL_DIR,
+    };
+    use opennet_revocation::revocation::trigger::RevocationTrigger;
+    use opennet_time::TimeEvent;
+    use opennet_transport::session::SessionBinding;
+    use opennet_trust::weight::TrustWeight;
+
+    let dir = std::env::temp_dir().join(format!("opennet-runtime-{}", std::process::id()));
+    if std::fs::create_dir_all(&dir).is_err() {
+        return false;
+    }
+    let storage = || {
+        Box::new(
+            FileStorage::new(&dir, KeyUnlock::passphrase("pw"))
+                .with_kdf_params(KdfParams { m_cost_kib: 64, t_cost: 1, p_cost: 1 }),
+        )
+    };
+    let config = NodeConfig {
+        data_dir: dir.clone(),
+        rotation: RotationPolicy { rotate_at_permille: 250, retry_secs: 60 },
+        ..Default::default()
+    };
+    let due = opennet_core::MAX_EPOCH_DURATION / 4;
+    let warning = TimeEvent::EpochExpiringSoon { remaining_secs: 600 };
+
+    let ok = (|| {
+        let mut node = NodeRuntime::open(config.clone(), storage(), 0).ok()?;
+        let node_id = *node.identity().identity().node_id();
+        let first_end = node.identity().identity().epoch().end_time();
+        let early = node.tick(due - 1).ok()?;
+        let scheduled = node.tick(due).ok()??;
+        // Epoch 2 has most of its life left; the warning was about epoch 1.
+        let stale = node.on_time_event(&warning, first_end - 600).ok()?;
+        let end = node.identity().identity().epoch().end_time();
+        let forced = node.on_time_event(&warning, end - 600).ok()?;
+        let repeated = node.on_time_event(&warning, end - 590).ok()?;
+        let replay_saved = dir.join(REPLAY_STATE_FILE).exists();
+        node.shutdown().ok()?;
+        let mut reopened = NodeRuntime::open(config, storage(), end).ok()?;
+
+        // A peer's certificate dropped into the spool closes its session.
+        let peer_key = KeyPair::generate(&[41u8; 32]);
+        let peer = opennet_core::NodeId::from_bytes([41; 32]);
+        reopened.identity().peers().insert_known(peer, 1, peer_key.public_key(), 0);
+        reopened.trust().graph_mut().upsert_node(peer, TrustWeight::from_f64(0.5));
+        let now = Timestamp::new(reopened.time().now_secs());
+        reopened.accept_session(SessionBinding::new(peer, 1), 9, now, 0).ok()?;
+        let sampled = reopened.time().clock().sample_count() == 1;
+        let at = Timestamp::from_millis(end * 1_000);
+        let cert = generate_self_revocation(peer, 1, RevocationTrigger::KeyCompromise, at, &peer_key).ok()?;
+        let spool = dir.join(CERTIFICATE_SPOOL_DIR);
+        std::fs::create_dir_all(&spool).ok()?;
+        std::fs::write(spool.join(certificate_file_name(&cert)), cert.encode()).ok()?;
+        // So is one the node signs for its own current epoch.
+        let own_key = storage().load(&format!("{}-3", opennet_node::runtime::IDENTITY_KEY_NAME)).ok()?;
+        let own = generate_self_revocation(node_id, 3, RevocationTrigger::KeyCompromise, at, &own_key).ok()?;
+        std::fs::write(spool.join(certificate_file_name(&own)), own.encode()).ok()?;
+        let imported = reopened.import_revocations().ok()?;
+        let closed = !reopened.transport().sessions().has_peer(&peer);
+        let spool_empty = std::fs::read_dir(&spool).ok()?.next().is_none();
+        let gossip = reopened.drain_revocations();
+        Some(
+            early.is_none()
+                && scheduled.new_epoch == 2
+                && stale.is_none()
+                && forced.is_some_and(|r| r.new_epoch == 3)
+                && repeated.is_none()
+                && reopened.identity().identity().epoch_id() == 3
+                && reopened.identity().identity().node_id() == &node_id
+                && imported == 2
+                && closed
+                && spool_empty
+                && reopened.revocation().is_revoked(&node_id, 3)
+                && gossip.len() == 2
+                && replay_saved
+                && sampled,
+        )
+    })()
+    .unwrap_or(false);
+
+    let _ = std::fs::remove_dir_all(&dir);
+    ok
+}
+
+/// A node whose key is held by an external signer keeps it out of the
+/// keystore, leaves rotation to the signer, and reopens only with a
+/// signer holding the log's head key.
+pub fn test_external_signer() -> bool {
+    use opennet_identity::rotation::RotationPolicy;
+    use opennet_identity::{FileStorage, KdfParams, KeyPair, KeyUnlock, NodeSigner};
+    use opennet_node::{NodeConfig, NodeRuntime};
+
+    let dir = std::env::temp_dir().join(format!("opennet-external-signer-{}", std::process::id()));
+    if std::fs::create_dir_all(&dir).is_err() {
+        return false;
+    }
+    let storage = || {
+        Box::new(
+            FileStorage::new(&dir, KeyUnlock::passphrase("pw"))
+                .with_kdf_params(KdfParams { m_cost_kib: 64, t_cost: 1, p_cost: 1 }),
+        )
+    };
+    let signer = |seed: u8| -> Box<dyn NodeSigner> { Box::new(KeyPair::generate(&[seed; 32])) };
+    let config = NodeConfig {
+        data_dir: dir.clone(),
+        rotation: RotationPolicy { rotate_at_permille: 250, retry_secs: 60 },
+        ..Default::default()
+    };
+    let due = opennet_core::MAX_EPOCH_DURATION / 4;
+
+    let ok = (|| {
+        let mut node = NodeRuntime::open_with_signer(config.clone(), storage(), signer(7), 0).ok()?;
+        let node_id = *node.identity().identity().node_id();
+        let key_name = opennet_node::runtime::IDENTITY_KEY_NAME;
+        let key_stored = dir.read_dir().ok()?.flatten().any(|e| e.file_name().to_string_lossy().starts_with(key_name));
+        let refused = node.tick(due).is_err();
+        let kept_epoch = node.identity().identity().epoch_id() == 1;
+        let mut reopened = NodeRuntime::open_with_signer(config.clone(), storage(), signer(7), due).ok()?;
+        let wrong_key = NodeRuntime::open_with_signer(config.clone(), storage(), signer(8), due).is_err();
+        let no_keystore_key = NodeRuntime::open(config, storage(), due).is_err();
+        Some(
+            !key_stored
+                && refused
+                && kept_epoch
+                && reopened.identity().identity().node_id() == &node_id
+                && wrong_key
+                && no_keystore_key,
+        )
+    })()
+    .unwrap_or(false);
+
+    let _ = std::fs::remove_dir_all(&dir);
+    ok
+}
@@ -1,6 +1,10 @@
 //! Monotonic clock abstraction.
 
 use opennet_core::types::Timestamp;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
 
 /// Monotonic clock trait.
 pub trait MonotonicClock: Send + Sync {
@@ -16,31 +20,113 @@ impl MonotonicClock for SystemClock {
         let duration = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default();
-        Timestamp::new(duration.as_secs())
+        Timestamp::from_millis(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
     }
 }
 
 /// Mock clock for testing.
+///
+/// Time moves only through [`advance`](Self::advance) and
+/// [`set`](Self::set), which wake sleepers that became due in deadline
+/// order. Clones share the same time.
+#[derive(Clone)]
 pub struct MockClock {
-    current: std::sync::atomic::AtomicU64,
+    inner: Arc<MockInner>,
+}
+
+pub(crate) struct MockInner {
+    /// Milliseconds since epoch.
+    current: AtomicU64,
+    /// `(deadline millis, sleeper id) -> waker`.
+    sleepers: Mutex<BTreeMap<(u64, u64), Waker>>,
+    next_sleeper: AtomicU64,
 }
 
 impl MockClock {
     pub fn new(initial: u64) -> Self {
-        Self { current: std::sync::atomic::AtomicU64::new(initial) }
+        Self {
+            inner: Arc::new(MockInner {
+                current: AtomicU64::new(Timestamp::new(initial).as_millis()),
+                sleepers: Mutex::new(BTreeMap::new()),
+                next_sleeper: AtomicU64::new(0),
+            }),
+        }
     }
     
     pub fn advance(&self, secs: u64) {
-        self.current.fetch_add(secs, std::sync::atomic::Ordering::SeqCst);
+        self.advance_millis(secs.saturating_mul(1_000));
+    }
+
+    /// Move time forward by `millis` milliseconds.
+    pub fn advance_millis(&self, millis: u64) {
+        self.inner.current.fetch_add(millis, Ordering::SeqCst);
+        self.inner.wake_due();
     }
     
     pub fn set(&self, secs: u64) {
-        self.current.store(secs, std::sync::atomic::Ordering::SeqCst);
+        self.inner.current.store(Timestamp::new(secs).as_millis(), Ordering::SeqCst);
+        self.inner.wake_due();
+    }
+
+    /// Earliest deadline a sleeper is waiting for.
+    pub fn next_deadline(&self) -> Option<Timestamp> {
+        self.inner.lock().keys().next().map(|&(deadline, _)| Timestamp::from_millis(deadline))
+    }
+
+    /// Number of pending sleepers.
+    pub fn sleepers(&self) -> usize {
+        self.inner.lock().len()
+    }
+
+    pub(crate) fn inner(&self) -> &Arc<MockInner> {
+        &self.inner
+    }
+}
+
+impl MockInner {
+    pub(crate) fn now_millis(&self) -> u64 {
+        self.current.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn sleeper_id(&self) -> u64 {
+        self.next_sleeper.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Register `waker` unless `deadline` has passed. The time is read
+    /// under the lock so a concurrent advance cannot be missed.
+    pub(crate) fn register(&self, deadline: u64, id: u64, waker: &Waker) -> bool {
+        let mut sleepers = self.lock();
+        if self.now_millis() >= deadline {
+            sleepers.remove(&(deadline, id));
+            return false;
+        }
+        sleepers.insert((deadline, id), waker.clone());
+        true
+    }
+
+    pub(crate) fn deregister(&self, deadline: u64, id: u64) {
+        self.lock().remove(&(deadline, id));
+    }
+
+    fn wake_due(&self) {
+        let now = self.now_millis();
+        let due = {
+            let mut sleepers = self.lock();
+            let pending = sleepers.split_off(&(now.saturating_add(1), 0));
+            std::mem::replace(&mut *sleepers, pending)
+        };
+        for waker in due.into_values() {
+            waker.wake();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<(u64, u64), Waker>> {
+        self.sleepers.lock().unwrap_or_else(|e| e.into_inner())
     }
 }
 
 impl MonotonicClock for MockClock {
     fn now(&self) -> Timestamp {
-        Timestamp::new(self.current.load(std::sync::atomic::Ordering::SeqCst))
+        Timestamp::from_millis(self.inner.now_millis())
     }
 }
@@ -1,36 +1,108 @@
 //! Drift detection.
+//!
+//! Drift is estimated with the median of recent samples, after dropping
+//! samples more than [`OUTLIER_MADS`] median absolute deviations from it,
+//! so a few peers with broken clocks cannot move the estimate. Each peer
+//! holds at most one sample, its latest, so one chatty peer cannot fill
+//! the window. Samples are kept in milliseconds.
 
+use opennet_core::NodeId;
 use opennet_core::types::Timestamp;
+use std::collections::VecDeque;
+
+/// Samples further than this many MADs from the median are ignored.
+pub const OUTLIER_MADS: u64 = 3;
+
+/// MAD used when the samples agree more closely, so ordinary network
+/// jitter is never treated as an outlier.
+pub const MIN_MAD_MILLIS: u64 = 1_000;
+
+/// Robust drift estimate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriftEstimate {
+    /// Median of local minus peer time in milliseconds, outliers removed.
+    pub median_millis: i64,
+    /// Median absolute deviation of the samples in milliseconds.
+    pub mad_millis: u64,
+    /// Samples the median was taken over.
+    pub samples: usize,
+}
+
+impl DriftEstimate {
+    /// Magnitude of the drift in whole seconds.
+    pub fn drift_secs(&self) -> u64 {
+        self.drift_millis() / 1_000
+    }
+
+    /// Magnitude of the drift in milliseconds.
+    pub fn drift_millis(&self) -> u64 {
+        self.median_millis.unsigned_abs()
+    }
+}
 
 /// Detects clock drift between nodes.
 pub struct DriftDetector {
-    samples: Vec<i64>,
+    /// Latest sample per peer, oldest first.
+    samples: VecDeque<(NodeId, i64)>,
     max_samples: usize,
 }
 
 impl DriftDetector {
     pub fn new() -> Self {
-        Self { samples: Vec::new(), max_samples: 20 }
+        Self::with_capacity(20)
     }
 
-    /// Calculate drift between local and peer timestamp.
-    pub fn calculate_drift(&mut self, local: Timestamp, peer: Timestamp) -> u64 {
-        let drift = (local.as_secs() as i64) - (peer.as_secs() as i64);
-        self.samples.push(drift);
-        
+    /// Detector over the latest samples of at most `max_samples` peers.
+    pub fn with_capacity(max_samples: usize) -> Self {
+        Self { samples: VecDeque::new(), max_samples: max_samples.max(1) }
+    }
+
+    /// Calculate drift between local time and a timestamp from `from`, in
+    /// milliseconds. Replaces `from`'s previous sample.
+    pub fn calculate_drift(&mut self, from: NodeId, local: Timestamp, peer: Timestamp) -> u64 {
+        let drift = local.signed_diff_millis(peer);
+        self.samples.retain(|(node_id, _)| *node_id != from);
+        self.samples.push_back((from, drift));
+
         if self.samples.len() > self.max_samples {
-            self.samples.remove(0);
+            self.samples.pop_front();
         }
         
         drift.unsigned_abs()
     }
 
-    /// Get average drift.
+    /// Get average drift in milliseconds.
     pub fn average_drift(&self) -> i64 {
         if self.samples.is_empty() {
             return 0;
         }
-        self.samples.iter().sum::<i64>() / self.samples.len() as i64
+        (self.samples.iter().map(|&(_, s)| i128::from(s)).sum::<i128>() / self.samples.len() as i128) as i64
+    }
+
+    /// Median and MAD of the samples, outliers removed.
+    pub fn estimate(&self) -> Option<DriftEstimate> {
+        let all: Vec<i64> = self.samples.iter().map(|&(_, s)| s).collect();
+        let median = median(&all)?;
+        let mad = mad(&all, median);
+        let limit = OUTLIER_MADS * mad.max(MIN_MAD_MILLIS);
+        let kept: Vec<i64> = all.into_iter().filter(|s| s.abs_diff(median) <= limit).collect();
+        let median_millis = self::median(&kept)?;
+        Some(DriftEstimate { median_millis, mad_millis: mad, samples: kept.len() })
+    }
+
+    /// Number of peers sampled.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Check if empty.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Forget all samples.
+    pub fn clear(&mut self) {
+        self.samples.clear();
     }
 }
 
@@ -39,3 +111,18 @@ impl Default for DriftDetector {
         Self::new()
     }
 }
+
+fn median(values: &[i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) { sorted[mid - 1] / 2 + sorted[mid] / 2 } else { sorted[mid] })
+}
+
+fn mad(values: &[i64], median: i64) -> u64 {
+    let deviations: Vec<i64> = values.iter().map(|v| v.abs_diff(median).min(i64::MAX as u64) as i64).collect();
+    self::median(&deviations).unwrap_or(0) as u64
+}
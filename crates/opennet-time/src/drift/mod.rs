@@ -2,6 +2,8 @@
 
 pub mod detector;
 pub mod tolerance;
+pub mod monitor;
 
-pub use detector::DriftDetector;
-pub use tolerance::DriftTolerance;
+pub use detector::{DriftDetector, DriftEstimate};
+pub use tolerance::{DriftLevel, DriftTolerance};
+pub use monitor::{DriftMonitor, MIN_DRIFT_SAMPLES};
@@ -0,0 +1,88 @@
+//! Drift level tracking.
+//!
+//! Combines the robust estimate from [`DriftDetector`] with the thresholds
+//! of [`DriftTolerance`] and reports only changes of level, so a node whose
+//! drift stays critical is told once, not on every peer message.
+//!
+//! Only trusted peers are sampled, as for the NMT clock, and the level
+//! does not change until [`MIN_DRIFT_SAMPLES`] peers have been heard from,
+//! so one peer's skewed clock cannot degrade the node on its own.
+
+use opennet_core::NodeId;
+use opennet_core::types::Timestamp;
+use opennet_trust::weight::TrustWeight;
+use crate::monitor::TimeEvent;
+use super::detector::{DriftDetector, DriftEstimate};
+use super::tolerance::{DriftLevel, DriftTolerance};
+
+/// Peers that must be sampled before the drift level changes.
+pub const MIN_DRIFT_SAMPLES: usize = 3;
+
+/// Tracks the drift level against peer timestamps.
+pub struct DriftMonitor {
+    detector: DriftDetector,
+    tolerance: DriftTolerance,
+    level: DriftLevel,
+    min_samples: usize,
+}
+
+impl DriftMonitor {
+    /// Monitor with the given thresholds.
+    pub fn new(tolerance: DriftTolerance) -> Self {
+        Self { detector: DriftDetector::new(), tolerance, level: DriftLevel::Ok, min_samples: MIN_DRIFT_SAMPLES }
+    }
+
+    /// Require `min_samples` sampled peers before changing level.
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.max(1);
+        self
+    }
+
+    /// Record a timestamp from `from`, trusted with `weight`, against
+    /// local time. Untrusted peers are ignored. Returns an event when the
+    /// drift level changes: entering warning or critical, or
+    /// [`TimeEvent::DriftRecovered`] on returning to within tolerance.
+    pub fn observe(&mut self, from: NodeId, weight: TrustWeight, local: Timestamp, peer: Timestamp) -> Option<TimeEvent> {
+        if weight <= TrustWeight::ZERO {
+            return None;
+        }
+        self.detector.calculate_drift(from, local, peer);
+        if self.detector.len() < self.min_samples {
+            return None;
+        }
+        let drift_secs = self.detector.estimate()?.drift_secs();
+        let level = self.tolerance.classify(self.level, drift_secs);
+        if level == self.level {
+            return None;
+        }
+        let previous = std::mem::replace(&mut self.level, level);
+        match level {
+            DriftLevel::Critical => Some(TimeEvent::DriftCritical { drift_secs }),
+            DriftLevel::Warning if previous == DriftLevel::Ok => Some(TimeEvent::DriftWarning { drift_secs }),
+            // Still drifting; recovery is reported only once back to Ok.
+            DriftLevel::Warning => None,
+            DriftLevel::Ok => Some(TimeEvent::DriftRecovered { drift_secs }),
+        }
+    }
+
+    /// Current level.
+    pub fn level(&self) -> DriftLevel {
+        self.level
+    }
+
+    /// Current robust estimate.
+    pub fn estimate(&self) -> Option<DriftEstimate> {
+        self.detector.estimate()
+    }
+
+    /// Thresholds in use.
+    pub fn tolerance(&self) -> &DriftTolerance {
+        &self.tolerance
+    }
+}
+
+impl Default for DriftMonitor {
+    fn default() -> Self {
+        Self::new(DriftTolerance::default())
+    }
+}
@@ -1,16 +1,36 @@
 //! Drift tolerance configuration.
 
+/// Drift severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum DriftLevel {
+    /// Within tolerance.
+    #[default]
+    Ok,
+    /// Above the warning threshold.
+    Warning,
+    /// Above the critical threshold.
+    Critical,
+}
+
 /// Drift tolerance thresholds.
 pub struct DriftTolerance {
     /// Warning threshold in seconds.
     pub warn_secs: u64,
     /// Critical threshold in seconds.
     pub critical_secs: u64,
+    /// How far below a threshold drift must fall to leave that level.
+    pub hysteresis_secs: u64,
 }
 
 impl DriftTolerance {
     pub fn new(warn_secs: u64, critical_secs: u64) -> Self {
-        Self { warn_secs, critical_secs }
+        Self { warn_secs, critical_secs, hysteresis_secs: 30 }
+    }
+
+    /// Set the hysteresis band.
+    pub fn with_hysteresis(mut self, secs: u64) -> Self {
+        self.hysteresis_secs = secs;
+        self
     }
 
     /// Check if drift is acceptable.
@@ -22,6 +42,20 @@ impl DriftTolerance {
     pub fn is_warning(&self, drift_secs: u64) -> bool {
         drift_secs > self.warn_secs && drift_secs <= self.critical_secs
     }
+
+    /// Level for `drift_secs` coming from `current`. A level is entered
+    /// above its threshold but left only once drift is `hysteresis_secs`
+    /// below it, so drift hovering at a threshold does not flap.
+    pub fn classify(&self, current: DriftLevel, drift_secs: u64) -> DriftLevel {
+        let leave = |threshold: u64| drift_secs + self.hysteresis_secs <= threshold;
+        if drift_secs > self.critical_secs || (current == DriftLevel::Critical && !leave(self.critical_secs)) {
+            DriftLevel::Critical
+        } else if drift_secs > self.warn_secs || (current >= DriftLevel::Warning && !leave(self.warn_secs)) {
+            DriftLevel::Warning
+        } else {
+            DriftLevel::Ok
+        }
+    }
 }
 
 impl Default for DriftTolerance {
@@ -2,21 +2,26 @@
 
 use opennet_core::EpochId;
 use opennet_core::types::Timestamp;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 /// Tracks epoch expiry times.
 pub struct EpochExpiry {
     expiry_times: BTreeMap<EpochId, u64>,
+    /// `(expiry_time, epoch_id)`, for range queries.
+    by_time: BTreeSet<(u64, EpochId)>,
 }
 
 impl EpochExpiry {
     pub fn new() -> Self {
-        Self { expiry_times: BTreeMap::new() }
+        Self { expiry_times: BTreeMap::new(), by_time: BTreeSet::new() }
     }
 
     /// Register epoch expiry.
     pub fn register(&mut self, epoch_id: EpochId, expiry_time: u64) {
-        self.expiry_times.insert(epoch_id, expiry_time);
+        if let Some(previous) = self.expiry_times.insert(epoch_id, expiry_time) {
+            self.by_time.remove(&(previous, epoch_id));
+        }
+        self.by_time.insert((expiry_time, epoch_id));
     }
 
     /// Check if epoch is expired.
@@ -26,12 +31,14 @@ impl EpochExpiry {
             .unwrap_or(false)
     }
 
-    /// Get expired epochs at timestamp.
+    /// Get expired epochs at timestamp, in epoch order.
     pub fn get_expired(&self, at: Timestamp) -> Vec<EpochId> {
-        self.expiry_times.iter()
-            .filter(|(_, &expiry)| at.as_secs() >= expiry)
-            .map(|(&id, _)| id)
-            .collect()
+        let mut expired: Vec<EpochId> = self.by_time
+            .range(..=(at.as_secs(), EpochId::MAX))
+            .map(|&(_, id)| id)
+            .collect();
+        expired.sort_unstable();
+        expired
     }
 }
 
@@ -3,7 +3,11 @@
 pub mod validity;
 pub mod expiry;
 pub mod transition;
+pub mod wheel;
+pub mod scheduler;
 
 pub use validity::EpochValidity;
 pub use expiry::EpochExpiry;
 pub use transition::EpochTransition;
+pub use wheel::{TimerId, TimerWheel};
+pub use scheduler::{EpochScheduler, EpochSchedulerConfig, EpochUse};
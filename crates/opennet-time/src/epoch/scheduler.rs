@@ -0,0 +1,186 @@
+//! Epoch lifecycle scheduler.
+//!
+//! Keeps timers on a [`TimerWheel`] for the local epoch and queues
+//! [`TimeEvent::EpochExpiringSoon`] and [`TimeEvent::EpochExpired`] when
+//! the clock passes them. On rotation the previous epoch enters a grace
+//! window in which it still verifies but may no longer sign, so messages
+//! in flight across the rotation are not dropped; its end is reported as
+//! [`TimeEvent::EpochRetired`].
+//!
+//! Nothing runs in the background: [`tick`](EpochScheduler::tick) reads
+//! the clock, so a `MockClock` drives the scheduler deterministically.
+
+use opennet_core::{Epoch, EpochId};
+use crate::clock::MonotonicClock;
+use crate::error::{TimeError, Result};
+use crate::monitor::TimeEvent;
+use super::transition::EpochTransition;
+use super::wheel::{TimerId, TimerWheel};
+
+/// EpochScheduler settings.
+#[derive(Debug, Clone)]
+pub struct EpochSchedulerConfig {
+    /// Warn this long before the epoch ends.
+    pub expiring_soon_secs: u64,
+    /// How long the previous epoch verifies after a rotation.
+    pub grace_secs: u64,
+    /// Timer wheel resolution.
+    pub tick_secs: u64,
+    /// Timer wheel buckets.
+    pub wheel_slots: usize,
+}
+
+impl Default for EpochSchedulerConfig {
+    fn default() -> Self {
+        Self {
+            expiring_soon_secs: 24 * 60 * 60,
+            grace_secs: 60 * 60,
+            tick_secs: 1,
+            wheel_slots: 512,
+        }
+    }
+}
+
+/// What an epoch's key may be used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpochUse {
+    /// Current epoch: sign and verify.
+    Active,
+    /// Previous epoch within its grace window: verify only.
+    VerifyOnly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Deadline {
+    ExpiringSoon,
+    Expired,
+    GraceEnded,
+}
+
+struct Retiring {
+    epoch: Epoch,
+    grace_until: u64,
+    timer: TimerId,
+}
+
+/// Drives the local epoch through expiry and rotation.
+pub struct EpochScheduler<C: MonotonicClock> {
+    clock: C,
+    config: EpochSchedulerConfig,
+    wheel: TimerWheel<(EpochId, Deadline)>,
+    current: Option<(Epoch, Vec<TimerId>)>,
+    retiring: Option<Retiring>,
+    pending_events: Vec<TimeEvent>,
+}
+
+impl<C: MonotonicClock> EpochScheduler<C> {
+    /// Scheduler with default settings.
+    pub fn new(clock: C) -> Self {
+        Self::with_config(clock, EpochSchedulerConfig::default())
+    }
+
+    /// Scheduler with custom settings.
+    pub fn with_config(clock: C, config: EpochSchedulerConfig) -> Self {
+        let wheel = TimerWheel::new(config.tick_secs, config.wheel_slots, clock.now().as_secs());
+        Self { clock, config, wheel, current: None, retiring: None, pending_events: Vec::new() }
+    }
+
+    /// The clock.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Start tracking `epoch` as the current one, replacing any other.
+    pub fn start(&mut self, epoch: Epoch) {
+        if let Some((_, timers)) = self.current.take() {
+            self.cancel(timers);
+        }
+        let end = epoch.end_time();
+        let timers = vec![
+            self.wheel.schedule(end.saturating_sub(self.config.expiring_soon_secs), (epoch.id, Deadline::ExpiringSoon)),
+            self.wheel.schedule(end, (epoch.id, Deadline::Expired)),
+        ];
+        self.current = Some((epoch, timers));
+    }
+
+    /// Rotate to `new`. The previous epoch verifies for the grace window;
+    /// an epoch still in an earlier grace window is retired at once.
+    pub fn rotate(&mut self, new: Epoch) -> Result<EpochTransition> {
+        let now = self.clock.now().as_secs();
+        let Some((old, _)) = &self.current else {
+            return Err(TimeError::InvalidTransition("no current epoch".into()));
+        };
+        let transition = EpochTransition::validate(old, &new, now)?.with_grace(self.config.grace_secs);
+
+        if let Some(previous) = self.retiring.take() {
+            self.wheel.cancel(previous.timer);
+            self.pending_events.push(TimeEvent::EpochRetired { epoch_id: previous.epoch.id });
+        }
+        let (old, timers) = self.current.take().expect("checked above");
+        self.cancel(timers);
+        let timer = self.wheel.schedule(transition.grace_until, (old.id, Deadline::GraceEnded));
+        self.retiring = Some(Retiring { epoch: old, grace_until: transition.grace_until, timer });
+        self.start(new);
+        Ok(transition)
+    }
+
+    /// Fire timers due at the clock's current time.
+    pub fn tick(&mut self) {
+        let now = self.clock.now().as_secs();
+        for (_, (epoch_id, deadline)) in self.wheel.advance(now) {
+            let event = match deadline {
+                Deadline::ExpiringSoon => {
+                    let end = self.current.as_ref().map_or(now, |(epoch, _)| epoch.end_time());
+                    TimeEvent::EpochExpiringSoon { remaining_secs: end.saturating_sub(now) }
+                }
+                Deadline::Expired => TimeEvent::EpochExpired { epoch_id },
+                Deadline::GraceEnded => {
+                    self.retiring = None;
+                    TimeEvent::EpochRetired { epoch_id }
+                }
+            };
+            self.pending_events.push(event);
+        }
+    }
+
+    /// What `epoch_id` may be used for now, if anything.
+    pub fn usage(&self, epoch_id: EpochId) -> Option<EpochUse> {
+        let now = self.clock.now().as_secs();
+        match (&self.current, &self.retiring) {
+            (Some((epoch, _)), _) if epoch.id == epoch_id && epoch.is_valid_at(now) => Some(EpochUse::Active),
+            (_, Some(old)) if old.epoch.id == epoch_id && now < old.grace_until => Some(EpochUse::VerifyOnly),
+            _ => None,
+        }
+    }
+
+    /// Check if signatures from `epoch_id` verify now.
+    pub fn may_verify(&self, epoch_id: EpochId) -> bool {
+        self.usage(epoch_id).is_some()
+    }
+
+    /// Check if `epoch_id` may sign now.
+    pub fn may_sign(&self, epoch_id: EpochId) -> bool {
+        self.usage(epoch_id) == Some(EpochUse::Active)
+    }
+
+    /// Current epoch.
+    pub fn current(&self) -> Option<&Epoch> {
+        self.current.as_ref().map(|(epoch, _)| epoch)
+    }
+
+    /// Previous epoch, while in its grace window.
+    pub fn retiring(&self) -> Option<&Epoch> {
+        self.retiring.as_ref().map(|r| &r.epoch)
+    }
+
+    /// Drain pending events.
+    pub fn drain_events(&mut self) -> Vec<TimeEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
+    fn cancel(&mut self, timers: Vec<TimerId>) {
+        for timer in timers {
+            self.wheel.cancel(timer);
+        }
+    }
+}
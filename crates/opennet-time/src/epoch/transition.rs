@@ -1,6 +1,7 @@
 //! Epoch transition handling.
 
-use opennet_core::EpochId;
+use opennet_core::{Epoch, EpochId};
+use crate::error::{TimeError, Result};
 
 /// Represents an epoch transition.
 #[derive(Debug, Clone)]
@@ -11,15 +12,43 @@ pub struct EpochTransition {
     pub to_epoch: EpochId,
     /// Transition timestamp.
     pub timestamp: u64,
+    /// Until when the previous epoch still verifies.
+    pub grace_until: u64,
 }
 
 impl EpochTransition {
     pub fn new(from: EpochId, to: EpochId, timestamp: u64) -> Self {
-        Self { from_epoch: from, to_epoch: to, timestamp }
+        Self { from_epoch: from, to_epoch: to, timestamp, grace_until: timestamp }
+    }
+
+    /// Transition from `old` to `new` at `at`. The new epoch must follow
+    /// the old one directly, leave no gap, and be valid at `at`.
+    pub fn validate(old: &Epoch, new: &Epoch, at: u64) -> Result<Self> {
+        Epoch::validate_transition(old, new).map_err(|e| TimeError::InvalidTransition(e.to_string()))?;
+        let transition = Self::new(old.id, new.id, at);
+        if !transition.is_valid() {
+            return Err(TimeError::InvalidTransition("epoch id must increase by one".into()));
+        }
+        if !new.is_valid_at(at) {
+            return Err(TimeError::InvalidTransition("new epoch not valid at rotation time".into()));
+        }
+        Ok(transition)
+    }
+
+    /// Let the previous epoch verify for `secs` after the transition.
+    pub fn with_grace(mut self, secs: u64) -> Self {
+        self.grace_until = self.timestamp.saturating_add(secs);
+        self
     }
 
     /// Validate transition is sequential.
     pub fn is_valid(&self) -> bool {
         self.to_epoch == self.from_epoch + 1
     }
+
+    /// Check if signatures from `epoch` verify at `at`: the new epoch
+    /// always, the previous one only within the grace window.
+    pub fn verifies(&self, epoch: EpochId, at: u64) -> bool {
+        epoch == self.to_epoch || (epoch == self.from_epoch && at < self.grace_until)
+    }
 }
@@ -0,0 +1,104 @@
+//! Hashed timer wheel.
+//!
+//! Timers are hashed into `slots` buckets of `tick_secs` each by deadline.
+//! Advancing visits only the buckets for the ticks that passed, or every
+//! bucket once after a jump longer than a full turn, and fires the timers
+//! in them that are due. Timers due on later turns stay where they are.
+//! Fired timers are returned ordered by deadline, then by scheduling
+//! order, so a run is fully determined by the clock readings.
+
+use std::collections::BTreeMap;
+
+/// Handle for cancelling a timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(u64);
+
+struct Timer<T> {
+    id: TimerId,
+    deadline: u64,
+    item: T,
+}
+
+/// Timer wheel with second-resolution deadlines.
+pub struct TimerWheel<T> {
+    tick_secs: u64,
+    slots: Vec<Vec<Timer<T>>>,
+    /// Earliest tick that may still hold due timers.
+    cursor: u64,
+    next_id: u64,
+    index: BTreeMap<TimerId, usize>,
+}
+
+impl<T> TimerWheel<T> {
+    /// Wheel of `slots` buckets of `tick_secs`, starting at `now_secs`.
+    pub fn new(tick_secs: u64, slots: usize, now_secs: u64) -> Self {
+        let tick_secs = tick_secs.max(1);
+        Self {
+            tick_secs,
+            slots: (0..slots.max(1)).map(|_| Vec::new()).collect(),
+            cursor: now_secs / tick_secs,
+            next_id: 0,
+            index: BTreeMap::new(),
+        }
+    }
+
+    /// Fire `item` at `deadline_secs`. A deadline already passed fires on
+    /// the next advance.
+    pub fn schedule(&mut self, deadline_secs: u64, item: T) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        let slot = self.slot_of((deadline_secs / self.tick_secs).max(self.cursor));
+        self.slots[slot].push(Timer { id, deadline: deadline_secs, item });
+        self.index.insert(id, slot);
+        id
+    }
+
+    /// Cancel a pending timer, returning its item.
+    pub fn cancel(&mut self, id: TimerId) -> Option<T> {
+        let slot = self.index.remove(&id)?;
+        let bucket = &mut self.slots[slot];
+        let pos = bucket.iter().position(|t| t.id == id)?;
+        Some(bucket.swap_remove(pos).item)
+    }
+
+    /// Fire every timer due at `now_secs`, as `(deadline, item)`.
+    pub fn advance(&mut self, now_secs: u64) -> Vec<(u64, T)> {
+        let target = now_secs / self.tick_secs;
+        if target < self.cursor {
+            return Vec::new();
+        }
+        let span = (target - self.cursor + 1).min(self.slots.len() as u64);
+        let mut fired = Vec::new();
+        for tick in self.cursor..self.cursor + span {
+            let slot = self.slot_of(tick);
+            let bucket = std::mem::take(&mut self.slots[slot]);
+            let (due, pending): (Vec<_>, Vec<_>) = bucket.into_iter().partition(|t| t.deadline <= now_secs);
+            self.slots[slot] = pending;
+            fired.extend(due);
+        }
+        // The current tick may still hold timers due later within it.
+        self.cursor = target;
+        fired.sort_by_key(|t| (t.deadline, t.id));
+        fired
+            .into_iter()
+            .map(|t| {
+                self.index.remove(&t.id);
+                (t.deadline, t.item)
+            })
+            .collect()
+    }
+
+    /// Pending timers.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Check if no timers are pending.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    fn slot_of(&self, tick: u64) -> usize {
+        (tick % self.slots.len() as u64) as usize
+    }
+}
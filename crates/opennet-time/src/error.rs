@@ -10,10 +10,16 @@ pub enum TimeError {
     DriftExceeded(u64),
     #[error("epoch expired")]
     EpochExpired,
+    #[error("invalid epoch transition: {0}")]
+    InvalidTransition(String),
     #[error("replay detected: nonce {0}")]
     ReplayDetected(u64),
     #[error("invalid timestamp")]
     InvalidTimestamp,
     #[error("insufficient samples")]
     InsufficientSamples,
+    #[error("replay state full")]
+    CapacityExceeded,
+    #[error("storage error: {0}")]
+    StorageError(String),
 }
@@ -13,9 +13,12 @@ pub mod error;
 
 mod monitor;
 mod clock;
+mod timer;
 
 pub use monitor::{EpochMonitor, TimeEvent};
 pub use clock::{MonotonicClock, SystemClock, MockClock};
+pub use timer::{AsyncClock, Interval, Sleep};
+pub use nmt::NmtClock;
 pub use error::{TimeError, Result};
 
 /// Default replay window in seconds.
@@ -1,8 +1,11 @@
 //! EpochMonitor - FSM event producer for time events.
 
+use opennet_core::{Epoch, EpochId, NodeId};
 use opennet_core::types::Timestamp;
+use opennet_trust::weight::TrustWeight;
 use crate::clock::MonotonicClock;
-use crate::drift::DriftDetector;
+use crate::epoch::EpochValidity;
+use crate::drift::{DriftMonitor, DriftTolerance};
 use crate::error::Result;
 
 /// Events produced by EpochMonitor.
@@ -12,46 +15,64 @@ pub enum TimeEvent {
     DriftWarning { drift_secs: u64 },
     /// Drift critical threshold exceeded.
     DriftCritical { drift_secs: u64 },
+    /// Drift back within tolerance after a warning or critical event.
+    DriftRecovered { drift_secs: u64 },
     /// Replay attack detected.
     ReplayDetected { nonce: u64 },
     /// Epoch about to expire.
     EpochExpiringSoon { remaining_secs: u64 },
+    /// Epoch reached its end time.
+    EpochExpired { epoch_id: EpochId },
+    /// Previous epoch's grace window ended; it no longer verifies.
+    EpochRetired { epoch_id: EpochId },
 }
 
 /// Monitors time and epoch state.
 pub struct EpochMonitor<C: MonotonicClock> {
     clock: C,
-    drift_detector: DriftDetector,
+    drift: DriftMonitor,
     pending_events: Vec<TimeEvent>,
-    warn_threshold: u64,
-    critical_threshold: u64,
 }
 
 impl<C: MonotonicClock> EpochMonitor<C> {
     pub fn new(clock: C) -> Self {
+        Self::with_tolerance(clock, DriftTolerance::default())
+    }
+
+    /// Monitor with custom drift thresholds.
+    pub fn with_tolerance(clock: C, tolerance: DriftTolerance) -> Self {
         Self {
             clock,
-            drift_detector: DriftDetector::new(),
+            drift: DriftMonitor::new(tolerance),
             pending_events: Vec::new(),
-            warn_threshold: 180,  // 3 minutes
-            critical_threshold: 300,  // 5 minutes
         }
     }
 
-    /// Check drift against peer timestamp.
-    pub fn check_drift(&mut self, peer_timestamp: Timestamp) -> Result<()> {
+    /// Check drift against a timestamp from `from`, trusted with `weight`.
+    /// Events are queued only when the drift level changes.
+    pub fn check_drift(&mut self, from: NodeId, weight: TrustWeight, peer_timestamp: Timestamp) -> Result<()> {
         let local = self.clock.now();
-        let drift = self.drift_detector.calculate_drift(local, peer_timestamp);
-        
-        if drift >= self.critical_threshold {
-            self.pending_events.push(TimeEvent::DriftCritical { drift_secs: drift });
-        } else if drift >= self.warn_threshold {
-            self.pending_events.push(TimeEvent::DriftWarning { drift_secs: drift });
+        if let Some(event) = self.drift.observe(from, weight, local, peer_timestamp) {
+            self.pending_events.push(event);
         }
-        
         Ok(())
     }
 
+    /// Drift state.
+    pub fn drift(&self) -> &DriftMonitor {
+        &self.drift
+    }
+
+    /// Check how long the local epoch has left.
+    pub fn check_epoch(&mut self, epoch: &Epoch, threshold_secs: u64) {
+        let now = self.clock.now();
+        if EpochValidity::expires_soon(epoch, now, threshold_secs) {
+            self.pending_events.push(TimeEvent::EpochExpiringSoon {
+                remaining_secs: EpochValidity::remaining_secs(epoch, now),
+            });
+        }
+    }
+
     /// Drain pending events.
     pub fn drain_events(&mut self) -> Vec<TimeEvent> {
         std::mem::take(&mut self.pending_events)
@@ -2,15 +2,28 @@
 
 use opennet_core::types::Timestamp;
 use crate::error::{TimeError, Result};
+use std::collections::BTreeMap;
+use opennet_core::NodeId;
+use super::sampler::TimeSample;
 
 /// Calculates Network Median Time from peer samples.
 pub struct NmtCalculator {
     min_samples: usize,
+    min_clusters: usize,
+    max_cluster_share_pct: u64,
 }
 
 impl NmtCalculator {
     pub fn new(min_samples: usize) -> Self {
-        Self { min_samples }
+        Self { min_samples, min_clusters: 1, max_cluster_share_pct: 100 }
+    }
+
+    /// Require samples from `min_clusters` neighborhoods and limit any one
+    /// neighborhood to `max_share_pct` percent of the total weight.
+    pub fn with_cluster_limits(mut self, min_clusters: usize, max_share_pct: u64) -> Self {
+        self.min_clusters = min_clusters.max(1);
+        self.max_cluster_share_pct = max_share_pct.clamp(1, 100);
+        self
     }
 
     /// Calculate NMT from samples.
@@ -31,6 +44,55 @@ impl NmtCalculator {
 
         Ok(Timestamp::new(median))
     }
+
+    /// Trust-weighted median of the samples' offsets from the local clock.
+    ///
+    /// Each neighborhood's weight is scaled down so it holds at most the
+    /// configured share of the total. Samples without trust count for
+    /// nothing.
+    pub fn weighted_offset(&self, samples: &[TimeSample]) -> Result<i64> {
+        if samples.len() < self.min_samples {
+            return Err(TimeError::InsufficientSamples);
+        }
+
+        let mut weighted: Vec<(i64, i64, NodeId)> =
+            samples.iter().map(|s| (s.offset(), s.weight.raw(), s.cluster)).collect();
+
+        let mut clusters: BTreeMap<NodeId, i64> = BTreeMap::new();
+        for &(_, weight, cluster) in &weighted {
+            *clusters.entry(cluster).or_default() += weight;
+        }
+        if clusters.len() < self.min_clusters {
+            return Err(TimeError::InsufficientSamples);
+        }
+        if clusters.len() > 1 && self.max_cluster_share_pct < 100 {
+            let total: i64 = clusters.values().sum();
+            let pct = self.max_cluster_share_pct as i64;
+            for (_, weight, cluster) in weighted.iter_mut() {
+                let cluster_weight = clusters[cluster];
+                // share <= pct  <=>  cluster <= others * pct / (100 - pct)
+                let allowed = (total - cluster_weight) * pct / (100 - pct);
+                if cluster_weight > allowed {
+                    *weight = *weight * allowed / cluster_weight;
+                }
+            }
+        }
+        let mut weighted: Vec<(i64, i64)> = weighted.into_iter().map(|(o, w, _)| (o, w)).collect();
+        weighted.sort_unstable();
+
+        let total: i64 = weighted.iter().map(|&(_, w)| w).sum();
+        if total == 0 {
+            return Err(TimeError::InsufficientSamples);
+        }
+        let mut cumulative = 0;
+        for &(offset, weight) in &weighted {
+            cumulative += weight;
+            if cumulative * 2 >= total {
+                return Ok(offset);
+            }
+        }
+        Err(TimeError::InsufficientSamples)
+    }
 }
 
 impl Default for NmtCalculator {
@@ -0,0 +1,179 @@
+//! Network-corrected clock.
+//!
+//! Wraps the local clock and corrects it toward the trust-weighted median
+//! offset reported by peers. The correction is slewed, at most one second
+//! per `slew_interval_secs` of local time, and the clock never runs
+//! backwards, so a sudden change in the network's view cannot make time
+//! jump.
+//!
+//! Samples are weighted by trust, untrusted peers are not sampled at all,
+//! at most `max_per_cluster` are kept from
+//! any one trust neighborhood, a correction needs `min_clusters`
+//! neighborhoods, and no neighborhood carries more than
+//! `max_cluster_share_pct` of the weight.
+
+use opennet_core::NodeId;
+use opennet_core::types::Timestamp;
+use opennet_trust::weight::TrustWeight;
+use std::sync::Mutex;
+use crate::clock::MonotonicClock;
+use super::calculator::NmtCalculator;
+use super::outlier::OutlierDetector;
+use super::sampler::{TimeSample, TimeSampler};
+
+/// NmtClock settings.
+#[derive(Debug, Clone)]
+pub struct NmtConfig {
+    /// Samples needed before correcting.
+    pub min_samples: usize,
+    /// Samples kept.
+    pub max_samples: usize,
+    /// Samples further than this from the median offset are ignored.
+    pub outlier_tolerance_secs: u64,
+    /// Local seconds per one second of correction.
+    pub slew_interval_secs: u64,
+    /// Samples kept per trust neighborhood.
+    pub max_per_cluster: usize,
+    /// Neighborhoods needed before correcting.
+    pub min_clusters: usize,
+    /// Largest share of the total weight one neighborhood may carry.
+    pub max_cluster_share_pct: u64,
+}
+
+impl Default for NmtConfig {
+    fn default() -> Self {
+        Self {
+            min_samples: 3,
+            max_samples: 10,
+            outlier_tolerance_secs: 300,
+            slew_interval_secs: 10,
+            max_per_cluster: 2,
+            min_clusters: 3,
+            max_cluster_share_pct: 40,
+        }
+    }
+}
+
+struct NmtState {
+    sampler: TimeSampler,
+    target_offset: i64,
+    applied_offset: i64,
+    last_slew: Option<u64>,
+    last_now: Timestamp,
+}
+
+/// Local clock corrected by Network Median Time.
+pub struct NmtClock<C: MonotonicClock> {
+    local: C,
+    config: NmtConfig,
+    outliers: OutlierDetector,
+    calculator: NmtCalculator,
+    state: Mutex<NmtState>,
+}
+
+impl<C: MonotonicClock> NmtClock<C> {
+    /// Wrap `local` with default settings.
+    pub fn new(local: C) -> Self {
+        Self::with_config(local, NmtConfig::default())
+    }
+
+    /// Wrap `local`.
+    pub fn with_config(local: C, config: NmtConfig) -> Self {
+        Self {
+            local,
+            outliers: OutlierDetector::new(config.outlier_tolerance_secs),
+            calculator: NmtCalculator::new(config.min_samples.max(1))
+                .with_cluster_limits(config.min_clusters, config.max_cluster_share_pct),
+            state: Mutex::new(NmtState {
+                sampler: TimeSampler::with_cluster_limit(config.max_samples, config.max_per_cluster),
+                target_offset: 0,
+                applied_offset: 0,
+                last_slew: None,
+                last_now: Timestamp::ZERO,
+            }),
+            config,
+        }
+    }
+
+    /// The uncorrected clock.
+    pub fn local(&self) -> &C {
+        &self.local
+    }
+
+    /// Record the timestamp of a peer message whose signature has been
+    /// verified, with the sender's trust and trust neighborhood. Returns
+    /// the new target offset once enough diverse samples agree.
+    ///
+    /// Peers without trust are not sampled: fresh NodeIds cost nothing.
+    pub fn record_sample(
+        &self,
+        node_id: NodeId,
+        peer_time: Timestamp,
+        weight: TrustWeight,
+        cluster: NodeId,
+    ) -> Option<i64> {
+        if weight <= TrustWeight::ZERO {
+            return None;
+        }
+        let local_time = self.local.now();
+        let mut state = self.lock();
+        Self::slew(&mut state, local_time.as_secs(), self.config.slew_interval_secs);
+        state.sampler.add_sample(node_id, TimeSample { peer_time, local_time, weight, cluster });
+
+        let kept = self.outliers.filter_offsets(&state.sampler.samples());
+        let target = self.calculator.weighted_offset(&kept).ok()?;
+        state.target_offset = target;
+        Some(target)
+    }
+
+    /// Correction currently applied, in seconds.
+    pub fn offset(&self) -> i64 {
+        let local = self.local.now().as_secs();
+        let mut state = self.lock();
+        Self::slew(&mut state, local, self.config.slew_interval_secs);
+        state.applied_offset
+    }
+
+    /// Correction the clock is slewing toward.
+    pub fn target_offset(&self) -> i64 {
+        self.lock().target_offset
+    }
+
+    /// Samples currently held.
+    pub fn sample_count(&self) -> usize {
+        self.lock().sampler.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, NmtState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn slew(state: &mut NmtState, local: u64, interval: u64) {
+        let interval = interval.max(1);
+        let Some(last) = state.last_slew else {
+            state.last_slew = Some(local);
+            return;
+        };
+        let steps = local.saturating_sub(last) / interval;
+        if steps == 0 {
+            return;
+        }
+        state.last_slew = Some(last + steps * interval);
+        let gap = state.target_offset - state.applied_offset;
+        let step = gap.unsigned_abs().min(steps) as i64;
+        state.applied_offset += step * gap.signum();
+    }
+}
+
+impl<C: MonotonicClock> MonotonicClock for NmtClock<C> {
+    fn now(&self) -> Timestamp {
+        let local = self.local.now();
+        let mut state = self.lock();
+        Self::slew(&mut state, local.as_secs(), self.config.slew_interval_secs);
+        let corrected = local
+            .add_signed_millis(state.applied_offset.saturating_mul(1_000))
+            .max(state.last_now);
+        state.last_now = corrected;
+        corrected
+    }
+}
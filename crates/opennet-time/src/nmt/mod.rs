@@ -3,6 +3,9 @@
 pub mod calculator;
 pub mod sampler;
 pub mod outlier;
+pub mod clock;
 
 pub use calculator::NmtCalculator;
-pub use sampler::TimeSampler;
+pub use sampler::{TimeSample, TimeSampler};
+pub use outlier::OutlierDetector;
+pub use clock::{NmtClock, NmtConfig};
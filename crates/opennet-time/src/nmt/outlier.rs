@@ -1,6 +1,7 @@
 //! Outlier detection for time samples.
 
 use opennet_core::types::Timestamp;
+use super::sampler::TimeSample;
 
 /// Detect and filter outlier time samples.
 pub struct OutlierDetector {
@@ -19,6 +20,21 @@ impl OutlierDetector {
             .copied()
             .collect()
     }
+
+    /// Keep samples whose offset is within tolerance of the plain median
+    /// offset, so a few wild clocks cannot drag the reference.
+    pub fn filter_offsets(&self, samples: &[TimeSample]) -> Vec<TimeSample> {
+        let mut offsets: Vec<i64> = samples.iter().map(TimeSample::offset).collect();
+        if offsets.is_empty() {
+            return Vec::new();
+        }
+        offsets.sort_unstable();
+        let reference = offsets[offsets.len() / 2];
+        samples.iter()
+            .filter(|s| s.offset().abs_diff(reference) <= self.tolerance_secs)
+            .copied()
+            .collect()
+    }
 }
 
 impl Default for OutlierDetector {
@@ -1,41 +1,105 @@
 //! Time sample collection.
+//!
+//! One sample is kept per peer. Each sample is tagged with the peer's
+//! trust neighborhood, and no neighborhood may hold more than
+//! `max_per_cluster` samples, so minting many NodeIds behind one voucher
+//! does not buy more of the sample set. When full, the sample from the
+//! least trusted peer is evicted, the oldest among equals; never the one
+//! with the lowest NodeId.
 
 use opennet_core::NodeId;
 use opennet_core::types::Timestamp;
-use std::collections::BTreeMap;
+use opennet_trust::weight::TrustWeight;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A peer's timestamp and the local time it arrived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSample {
+    /// Timestamp from an authenticated peer message.
+    pub peer_time: Timestamp,
+    /// Local clock when the message arrived.
+    pub local_time: Timestamp,
+    /// Trust in the peer when sampled.
+    pub weight: TrustWeight,
+    /// Trust neighborhood of the peer.
+    pub cluster: NodeId,
+}
+
+impl TimeSample {
+    /// Peer clock minus local clock, in seconds.
+    pub fn offset(&self) -> i64 {
+        self.peer_time.signed_diff_millis(self.local_time) / 1_000
+    }
+}
 
 /// Collects time samples from peers.
 pub struct TimeSampler {
-    samples: BTreeMap<NodeId, Timestamp>,
+    samples: BTreeMap<NodeId, (u64, TimeSample)>,
     max_samples: usize,
+    max_per_cluster: usize,
+    next_seq: u64,
 }
 
 impl TimeSampler {
     pub fn new(max_samples: usize) -> Self {
-        Self { samples: BTreeMap::new(), max_samples }
+        Self::with_cluster_limit(max_samples, max_samples)
     }
 
-    /// Add a sample from a peer.
-    pub fn add_sample(&mut self, node_id: NodeId, timestamp: Timestamp) {
-        self.samples.insert(node_id, timestamp);
-        
-        // Evict oldest if over limit
+    /// Sampler holding at most `max_per_cluster` samples per neighborhood.
+    pub fn with_cluster_limit(max_samples: usize, max_per_cluster: usize) -> Self {
+        Self { samples: BTreeMap::new(), max_samples, max_per_cluster: max_per_cluster.max(1), next_seq: 0 }
+    }
+
+    /// Add a sample from a peer, replacing its previous one.
+    pub fn add_sample(&mut self, node_id: NodeId, sample: TimeSample) {
+        self.samples.remove(&node_id);
+        while self.samples.values().filter(|(_, s)| s.cluster == sample.cluster).count() >= self.max_per_cluster {
+            self.evict(Some(sample.cluster));
+        }
+        self.samples.insert(node_id, (self.next_seq, sample));
+        self.next_seq += 1;
+
         while self.samples.len() > self.max_samples {
-            if let Some((&first_key, _)) = self.samples.iter().next() {
-                self.samples.remove(&first_key);
-            }
+            self.evict(None);
         }
     }
 
     /// Get all samples.
-    pub fn samples(&self) -> Vec<Timestamp> {
-        self.samples.values().copied().collect()
+    pub fn samples(&self) -> Vec<TimeSample> {
+        self.samples.values().map(|(_, s)| *s).collect()
+    }
+
+    /// Number of distinct neighborhoods sampled.
+    pub fn clusters(&self) -> usize {
+        self.samples.values().map(|(_, s)| s.cluster).collect::<BTreeSet<_>>().len()
+    }
+
+    /// Number of samples held.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Check if empty.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
     }
 
     /// Clear all samples.
     pub fn clear(&mut self) {
         self.samples.clear();
     }
+
+    fn evict(&mut self, cluster: Option<NodeId>) {
+        let weakest = self
+            .samples
+            .iter()
+            .filter(|(_, (_, s))| cluster.is_none_or(|c| s.cluster == c))
+            .min_by_key(|(_, (seq, s))| (s.weight, s.local_time, *seq))
+            .map(|(id, _)| *id);
+        if let Some(node_id) = weakest {
+            self.samples.remove(&node_id);
+        }
+    }
 }
 
 impl Default for TimeSampler {
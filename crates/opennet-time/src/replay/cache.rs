@@ -0,0 +1,248 @@
+//! Per-sender replay cache.
+//!
+//! A message is accepted only if its timestamp is inside the replay
+//! window and its sequence number has not been seen for its
+//! (NodeId, epoch). Sequence numbers are tracked with a sliding bitmap of
+//! the last [`REPLAY_BITMAP_BITS`] values below the highest seen; anything
+//! older than that is refused.
+//!
+//! A message accepted at local time `t` carries a timestamp within
+//! `window` of `t`, so a replay of it can pass the time check only until
+//! `t + 2 * window`. A sender's state is dropped only after that, so
+//! expiry never reopens a replay. When the cache is full new senders are
+//! refused rather than evicting live state.
+//!
+//! The cache can be saved and restored with
+//! [`ReplayStore`](super::ReplayStore).
+
+use opennet_core::{EpochId, NodeId};
+use opennet_core::types::Timestamp;
+use opennet_wire::cbor::{CborDecoder, CborEncoder};
+use std::collections::BTreeMap;
+use crate::error::{TimeError, Result};
+use super::window::ReplayWindow;
+
+/// Sequence numbers tracked below the highest seen.
+pub const REPLAY_BITMAP_BITS: u64 = 1024;
+
+const WORDS: usize = (REPLAY_BITMAP_BITS / 64) as usize;
+
+/// Sliding window over one sender's sequence numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceWindow {
+    highest: u64,
+    /// Bit `i` set: `highest - i` was seen.
+    bitmap: [u64; WORDS],
+    last_accepted: u64,
+}
+
+impl SequenceWindow {
+    fn new(sequence: u64, now_secs: u64) -> Self {
+        let mut bitmap = [0u64; WORDS];
+        bitmap[0] = 1;
+        Self { highest: sequence, bitmap, last_accepted: now_secs }
+    }
+
+    /// Highest sequence number seen.
+    pub fn highest(&self) -> u64 {
+        self.highest
+    }
+
+    /// Local time of the latest accepted message.
+    pub fn last_accepted(&self) -> u64 {
+        self.last_accepted
+    }
+
+    fn check(&self, sequence: u64) -> Result<()> {
+        if sequence > self.highest {
+            return Ok(());
+        }
+        let age = self.highest - sequence;
+        if age >= REPLAY_BITMAP_BITS || self.bit(age) {
+            return Err(TimeError::ReplayDetected(sequence));
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, sequence: u64, now_secs: u64) {
+        if sequence > self.highest {
+            self.shift(sequence - self.highest);
+            self.highest = sequence;
+        }
+        let age = (self.highest - sequence) as usize;
+        self.bitmap[age / 64] |= 1 << (age % 64);
+        self.last_accepted = self.last_accepted.max(now_secs);
+    }
+
+    fn bit(&self, age: u64) -> bool {
+        let age = age as usize;
+        self.bitmap[age / 64] & (1 << (age % 64)) != 0
+    }
+
+    fn shift(&mut self, by: u64) {
+        if by >= REPLAY_BITMAP_BITS {
+            self.bitmap = [0; WORDS];
+            return;
+        }
+        let (words, bits) = ((by / 64) as usize, (by % 64) as u32);
+        for i in (0..WORDS).rev() {
+            let src = i.checked_sub(words);
+            let high = src.map_or(0, |s| self.bitmap[s] << bits);
+            let low = match src.and_then(|s| s.checked_sub(1)) {
+                Some(s) if bits > 0 => self.bitmap[s] >> (64 - bits),
+                _ => 0,
+            };
+            self.bitmap[i] = high | low;
+        }
+    }
+}
+
+/// Replay cache keyed by (sender, epoch).
+pub struct ReplayCache {
+    window: ReplayWindow,
+    window_secs: u64,
+    max_senders: usize,
+    senders: BTreeMap<(NodeId, EpochId), SequenceWindow>,
+    not_before: u64,
+}
+
+impl ReplayCache {
+    /// Cache accepting timestamps within `window_secs` of now and tracking
+    /// at most `max_senders` (sender, epoch) pairs.
+    pub fn new(window_secs: u64, max_senders: usize) -> Self {
+        Self {
+            window: ReplayWindow::new(window_secs),
+            window_secs,
+            max_senders,
+            senders: BTreeMap::new(),
+            not_before: 0,
+        }
+    }
+
+    /// Refuse every message timestamped at or before `secs`, e.g. after
+    /// state written since then may have been lost.
+    pub fn refuse_until(&mut self, secs: u64) {
+        self.not_before = self.not_before.max(secs);
+    }
+
+    /// Accept a message once, or reject it as stale or replayed.
+    pub fn check_and_record(
+        &mut self,
+        sender: NodeId,
+        epoch: EpochId,
+        sequence: u64,
+        timestamp: Timestamp,
+        now: Timestamp,
+    ) -> Result<()> {
+        self.window.is_valid(timestamp, now)?;
+        if timestamp.as_secs() <= self.not_before {
+            return Err(TimeError::InvalidTimestamp);
+        }
+        let now_secs = now.as_secs();
+        if let Some(state) = self.senders.get_mut(&(sender, epoch)) {
+            state.check(sequence)?;
+            state.record(sequence, now_secs);
+            return Ok(());
+        }
+        if self.senders.len() >= self.max_senders {
+            self.expire(now);
+            if self.senders.len() >= self.max_senders {
+                return Err(TimeError::CapacityExceeded);
+            }
+        }
+        self.senders.insert((sender, epoch), SequenceWindow::new(sequence, now_secs));
+        Ok(())
+    }
+
+    /// Drop senders whose messages can no longer pass the time check.
+    /// Returns how many were dropped.
+    pub fn expire(&mut self, now: Timestamp) -> usize {
+        let horizon = self.window_secs.saturating_mul(2);
+        let before = self.senders.len();
+        self.senders.retain(|_, s| now.as_secs() <= s.last_accepted.saturating_add(horizon));
+        before - self.senders.len()
+    }
+
+    /// State for one sender and epoch.
+    pub fn get(&self, sender: &NodeId, epoch: EpochId) -> Option<&SequenceWindow> {
+        self.senders.get(&(*sender, epoch))
+    }
+
+    /// Number of tracked (sender, epoch) pairs.
+    pub fn len(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// Check if empty.
+    pub fn is_empty(&self) -> bool {
+        self.senders.is_empty()
+    }
+
+    /// Replay window in seconds.
+    pub fn window_secs(&self) -> u64 {
+        self.window_secs
+    }
+
+    /// Most (sender, epoch) pairs tracked.
+    pub fn max_senders(&self) -> usize {
+        self.max_senders
+    }
+
+    /// `[window_secs, not_before, [[node_id, epoch, highest, last_accepted, bitmap]...]]`
+    pub(crate) fn encode(&self, enc: &mut CborEncoder) {
+        enc.encode_array_header(3)
+            .encode_uint(self.window_secs)
+            .encode_uint(self.not_before)
+            .encode_array_header(self.senders.len());
+        for ((node_id, epoch), state) in &self.senders {
+            let bitmap: Vec<u8> = state.bitmap.iter().flat_map(|w| w.to_be_bytes()).collect();
+            enc.encode_array_header(5)
+                .encode_bytes(node_id.as_bytes())
+                .encode_uint(*epoch)
+                .encode_uint(state.highest)
+                .encode_uint(state.last_accepted)
+                .encode_bytes(&bitmap);
+        }
+    }
+
+    pub(crate) fn decode(dec: &mut CborDecoder, max_senders: usize) -> Result<Self> {
+        if dec.decode_array_header().map_err(corrupt)? != 3 {
+            return Err(corrupt("expected 3 fields"));
+        }
+        let mut cache = Self::new(dec.decode_uint().map_err(corrupt)?, max_senders);
+        cache.not_before = dec.decode_uint().map_err(corrupt)?;
+        for _ in 0..dec.decode_array_header().map_err(corrupt)? {
+            if dec.decode_array_header().map_err(corrupt)? != 5 {
+                return Err(corrupt("expected 5-field sender"));
+            }
+            let node_id: [u8; 32] = dec
+                .decode_bytes()
+                .map_err(corrupt)?
+                .try_into()
+                .map_err(|_| corrupt("node id length"))?;
+            let epoch = dec.decode_uint().map_err(corrupt)?;
+            let highest = dec.decode_uint().map_err(corrupt)?;
+            let last_accepted = dec.decode_uint().map_err(corrupt)?;
+            let bytes = dec.decode_bytes().map_err(corrupt)?;
+            if bytes.len() != WORDS * 8 {
+                return Err(corrupt("bitmap length"));
+            }
+            let mut bitmap = [0u64; WORDS];
+            for (word, chunk) in bitmap.iter_mut().zip(bytes.chunks_exact(8)) {
+                *word = u64::from_be_bytes(chunk.try_into().expect("8 bytes"));
+            }
+            cache.senders.insert((NodeId::from_bytes(node_id), epoch), SequenceWindow { highest, bitmap, last_accepted });
+        }
+        Ok(cache)
+    }
+}
+
+impl Default for ReplayCache {
+    fn default() -> Self {
+        Self::new(crate::DEFAULT_REPLAY_WINDOW, 65_536)
+    }
+}
+
+fn corrupt(e: impl std::fmt::Display) -> TimeError {
+    TimeError::StorageError(format!("corrupt replay state: {}", e))
+}
@@ -2,6 +2,10 @@
 
 pub mod window;
 pub mod nonce;
+pub mod cache;
+pub mod persist;
 
 pub use window::ReplayWindow;
 pub use nonce::NonceTracker;
+pub use cache::{ReplayCache, SequenceWindow, REPLAY_BITMAP_BITS};
+pub use persist::{ReplayStore, SequenceCounter};
@@ -1,37 +1,102 @@
 //! Nonce tracking for replay protection.
 
-use std::collections::BTreeSet;
+use opennet_core::types::Timestamp;
+use opennet_wire::cbor::{CborDecoder, CborEncoder};
+use std::collections::BTreeMap;
 use crate::error::{TimeError, Result};
+use super::window::ReplayWindow;
 
 /// Tracks seen nonces to prevent replay.
+///
+/// Only messages timestamped inside the replay window are accepted, so a
+/// nonce first accepted at local time `t` is forgotten once `2 * window`
+/// has passed and no replay of it can pass the time check any more. When
+/// full, expired nonces are dropped first and new ones refused only if
+/// every tracked nonce is still live. Use
+/// [`ReplayCache`](super::ReplayCache) for per-sender sequence tracking.
 pub struct NonceTracker {
-    seen: BTreeSet<u64>,
+    window: ReplayWindow,
+    window_secs: u64,
+    /// Nonce and the local time it was accepted.
+    seen: BTreeMap<u64, u64>,
     max_size: usize,
 }
 
 impl NonceTracker {
-    pub fn new(max_size: usize) -> Self {
-        Self { seen: BTreeSet::new(), max_size }
+    /// Tracker accepting timestamps within `window_secs` of now and
+    /// holding at most `max_size` live nonces.
+    pub fn new(window_secs: u64, max_size: usize) -> Self {
+        Self { window: ReplayWindow::new(window_secs), window_secs, seen: BTreeMap::new(), max_size }
     }
 
-    /// Check and record a nonce.
-    pub fn check_and_record(&mut self, nonce: u64) -> Result<()> {
-        if self.seen.contains(&nonce) {
+    /// Check and record a nonce carried by a message timestamped `timestamp`.
+    pub fn check_and_record(&mut self, nonce: u64, timestamp: Timestamp, now: Timestamp) -> Result<()> {
+        self.window.is_valid(timestamp, now)?;
+        if self.seen.contains_key(&nonce) {
             return Err(TimeError::ReplayDetected(nonce));
         }
-
-        self.seen.insert(nonce);
-
-        // Evict oldest if over limit
-        while self.seen.len() > self.max_size {
-            if let Some(&first) = self.seen.iter().next() {
-                self.seen.remove(&first);
+        if self.seen.len() >= self.max_size {
+            self.expire(now);
+            if self.seen.len() >= self.max_size {
+                return Err(TimeError::CapacityExceeded);
             }
         }
 
+        self.seen.insert(nonce, now.as_secs());
         Ok(())
     }
 
+    /// Drop nonces whose messages can no longer pass the time check.
+    /// Returns how many were dropped.
+    pub fn expire(&mut self, now: Timestamp) -> usize {
+        let horizon = self.window_secs.saturating_mul(2);
+        let before = self.seen.len();
+        self.seen.retain(|_, accepted| now.as_secs() <= accepted.saturating_add(horizon));
+        before - self.seen.len()
+    }
+
+    /// Number of tracked nonces.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Check if empty.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// Encode for persistence: `[window_secs, max_size, [[nonce, accepted_at]...]]`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut enc = CborEncoder::new();
+        enc.encode_array_header(3)
+            .encode_uint(self.window_secs)
+            .encode_uint(self.max_size as u64)
+            .encode_array_header(self.seen.len());
+        for (nonce, accepted) in &self.seen {
+            enc.encode_array_header(2).encode_uint(*nonce).encode_uint(*accepted);
+        }
+        enc.into_bytes()
+    }
+
+    /// Restore from [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let corrupt = |e: opennet_wire::WireError| TimeError::StorageError(e.to_string());
+        let mut dec = CborDecoder::new(bytes);
+        if dec.decode_array_header().map_err(corrupt)? != 3 {
+            return Err(TimeError::StorageError("expected 3-field nonce state".into()));
+        }
+        let window_secs = dec.decode_uint().map_err(corrupt)?;
+        let mut tracker = Self::new(window_secs, dec.decode_uint().map_err(corrupt)? as usize);
+        for _ in 0..dec.decode_array_header().map_err(corrupt)? {
+            if dec.decode_array_header().map_err(corrupt)? != 2 {
+                return Err(TimeError::StorageError("expected 2-field nonce".into()));
+            }
+            let nonce = dec.decode_uint().map_err(corrupt)?;
+            tracker.seen.insert(nonce, dec.decode_uint().map_err(corrupt)?);
+        }
+        Ok(tracker)
+    }
+
     /// Clear all tracked nonces.
     pub fn clear(&mut self) {
         self.seen.clear();
@@ -40,6 +105,6 @@ impl NonceTracker {
 
 impl Default for NonceTracker {
     fn default() -> Self {
-        Self::new(10000)
+        Self::new(crate::DEFAULT_REPLAY_WINDOW, 10000)
     }
 }
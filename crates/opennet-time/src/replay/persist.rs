@@ -0,0 +1,141 @@
+//! Replay and sequence state that survives restarts.
+//!
+//! [`ReplayStore`] saves the [`ReplayCache`] with a clean-shutdown flag.
+//! The flag is cleared as soon as the state is loaded, so after a crash
+//! the next boot knows messages accepted since the last save were lost
+//! and refuses every timestamp such a message could carry.
+//!
+//! [`SequenceCounter`] hands out our own outgoing sequence numbers. It
+//! reserves a block ahead on disk before using it, and after a restart
+//! continues from the reserved mark, so no number is ever reused.
+
+use opennet_core::types::Timestamp;
+use opennet_wire::cbor::{CborDecoder, CborEncoder};
+use opennet_core::fs::write_atomic;
+use std::path::PathBuf;
+use crate::error::{TimeError, Result};
+use super::cache::ReplayCache;
+
+/// Replay state file format version.
+pub const REPLAY_STATE_VERSION: u64 = 1;
+
+/// Default number of sequence numbers reserved per disk write.
+pub const DEFAULT_SEQUENCE_RESERVE: u64 = 1024;
+
+/// Replay cache file.
+pub struct ReplayStore {
+    path: PathBuf,
+}
+
+impl ReplayStore {
+    /// Store at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Load the saved cache, or a fresh one with `window_secs` if none was
+    /// saved. If the last shutdown was not clean, timestamps up to
+    /// `now + window` are refused. The file is marked unclean until the
+    /// next [`save`](Self::save) with `clean` set.
+    pub fn load(&self, window_secs: u64, max_senders: usize, now: Timestamp) -> Result<ReplayCache> {
+        let bytes = match std::fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let cache = ReplayCache::new(window_secs, max_senders);
+                self.save(&cache, now, false)?;
+                return Ok(cache);
+            }
+            Err(e) => return Err(storage(e)),
+        };
+        let mut dec = CborDecoder::new(&bytes);
+        if dec.decode_array_header().map_err(storage)? != 4 {
+            return Err(storage("expected 4-field replay state"));
+        }
+        let version = dec.decode_uint().map_err(storage)?;
+        if version != REPLAY_STATE_VERSION {
+            return Err(storage(format!("unsupported replay state version {}", version)));
+        }
+        let clean = dec.decode_uint().map_err(storage)? == 1;
+        let _saved_at = dec.decode_uint().map_err(storage)?;
+        let mut cache = ReplayCache::decode(&mut dec, max_senders)?;
+        if !dec.is_empty() {
+            return Err(storage("trailing bytes in replay state"));
+        }
+
+        if !clean {
+            cache.refuse_until(now.as_secs().saturating_add(cache.window_secs()));
+        }
+        cache.expire(now);
+        self.save(&cache, now, false)?;
+        Ok(cache)
+    }
+
+    /// Save the cache. Pass `clean` only on orderly shutdown.
+    pub fn save(&self, cache: &ReplayCache, now: Timestamp, clean: bool) -> Result<()> {
+        let mut enc = CborEncoder::new();
+        enc.encode_array_header(4)
+            .encode_uint(REPLAY_STATE_VERSION)
+            .encode_uint(u64::from(clean))
+            .encode_uint(now.as_secs());
+        cache.encode(&mut enc);
+        write_atomic(&self.path, &enc.into_bytes()).map_err(storage)
+    }
+}
+
+/// Outgoing sequence numbers that never repeat across restarts.
+pub struct SequenceCounter {
+    path: PathBuf,
+    next: u64,
+    reserved: u64,
+    reserve: u64,
+}
+
+impl SequenceCounter {
+    /// Open the counter at `path`, continuing from the reserved mark.
+    pub fn open(path: impl Into<PathBuf>, reserve: u64) -> Result<Self> {
+        let path = path.into();
+        let next = match std::fs::read(&path) {
+            Ok(bytes) => {
+                let mut dec = CborDecoder::new(&bytes);
+                let mark = dec.decode_uint().map_err(storage)?;
+                if !dec.is_empty() {
+                    return Err(storage("trailing bytes in sequence state"));
+                }
+                mark
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 1,
+            Err(e) => return Err(storage(e)),
+        };
+        let mut counter = Self { path, next, reserved: next, reserve: reserve.max(1) };
+        counter.reserve_ahead()?;
+        Ok(counter)
+    }
+
+    /// Allocate the next sequence number.
+    pub fn allocate(&mut self) -> Result<u64> {
+        if self.next >= self.reserved {
+            self.reserve_ahead()?;
+        }
+        let sequence = self.next;
+        self.next += 1;
+        Ok(sequence)
+    }
+
+    /// Mark on disk; every number below it may have been used.
+    pub fn high_water_mark(&self) -> u64 {
+        self.reserved
+    }
+
+    fn reserve_ahead(&mut self) -> Result<()> {
+        let mark = self.next.checked_add(self.reserve).ok_or_else(|| storage("sequence space exhausted"))?;
+        let mut enc = CborEncoder::new();
+        enc.encode_uint(mark);
+        write_atomic(&self.path, &enc.into_bytes()).map_err(storage)?;
+        self.reserved = mark;
+        Ok(())
+    }
+}
+
+fn storage(e: impl std::fmt::Display) -> TimeError {
+    TimeError::StorageError(e.to_string())
+}
@@ -13,15 +13,9 @@ impl ReplayWindow {
         Self { window_secs }
     }
 
-    /// Check if message timestamp is within window.
+    /// Check if message timestamp is within window, to the millisecond.
     pub fn is_valid(&self, msg_timestamp: Timestamp, current: Timestamp) -> Result<()> {
-        let diff = if current.as_secs() > msg_timestamp.as_secs() {
-            current.as_secs() - msg_timestamp.as_secs()
-        } else {
-            msg_timestamp.as_secs() - current.as_secs()
-        };
-
-        if diff > self.window_secs {
+        if !msg_timestamp.within_range(current, self.window_secs) {
             return Err(TimeError::InvalidTimestamp);
         }
 
@@ -0,0 +1,105 @@
+//! Clocks that can wait.
+//!
+//! [`AsyncClock`] adds sleeping to [`MonotonicClock`], so code with
+//! timeouts takes its clock as a parameter instead of calling the runtime
+//! directly. [`SystemClock`] sleeps on tokio (with the `tokio` feature);
+//! [`MockClock`] sleeps in virtual time and wakes only when a test moves
+//! it forward.
+
+use opennet_core::types::Timestamp;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use crate::clock::{MockClock, MockInner, MonotonicClock};
+
+/// Future returned by [`AsyncClock::sleep_until`].
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Clock that can wait.
+pub trait AsyncClock: MonotonicClock {
+    /// Complete once the clock reaches `deadline`.
+    fn sleep_until(&self, deadline: Timestamp) -> Sleep;
+
+    /// Complete after `secs` seconds.
+    fn sleep(&self, secs: u64) -> Sleep {
+        self.sleep_until(self.now().add_secs(secs))
+    }
+
+    /// Ticks every `period_secs`, the first one immediately.
+    fn interval(&self, period_secs: u64) -> Interval<'_, Self>
+    where
+        Self: Sized,
+    {
+        Interval { clock: self, period_secs: period_secs.max(1), next: self.now() }
+    }
+}
+
+/// Periodic ticks from an [`AsyncClock`]. Ticks missed while the caller
+/// was busy are skipped, not replayed in a burst.
+pub struct Interval<'a, C: AsyncClock> {
+    clock: &'a C,
+    period_secs: u64,
+    next: Timestamp,
+}
+
+impl<C: AsyncClock> Interval<'_, C> {
+    /// Wait for the next tick and return its scheduled time.
+    pub async fn tick(&mut self) -> Timestamp {
+        let scheduled = self.next;
+        self.clock.sleep_until(scheduled).await;
+        let now = self.clock.now();
+        let mut next = scheduled.add_secs(self.period_secs);
+        if next <= now {
+            next = now.add_secs(self.period_secs);
+        }
+        self.next = next;
+        scheduled
+    }
+
+    /// Time of the next tick.
+    pub fn next_tick(&self) -> Timestamp {
+        self.next
+    }
+}
+
+impl AsyncClock for MockClock {
+    fn sleep_until(&self, deadline: Timestamp) -> Sleep {
+        let inner = self.inner().clone();
+        let id = inner.sleeper_id();
+        Box::pin(MockSleep { inner, deadline: deadline.as_millis(), id })
+    }
+}
+
+struct MockSleep {
+    inner: Arc<MockInner>,
+    /// Milliseconds since epoch.
+    deadline: u64,
+    id: u64,
+}
+
+impl Future for MockSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.inner.register(self.deadline, self.id, cx.waker()) {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    }
+}
+
+impl Drop for MockSleep {
+    fn drop(&mut self) {
+        self.inner.deregister(self.deadline, self.id);
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncClock for crate::clock::SystemClock {
+    fn sleep_until(&self, deadline: Timestamp) -> Sleep {
+        let wait = deadline.as_millis().saturating_sub(self.now().as_millis());
+        Box::pin(tokio::time::sleep(std::time::Duration::from_millis(wait)))
+    }
+}
@@ -0,0 +1,86 @@
+//! Token bucket rate limiter.
+//!
+//! Tokens are tracked in thousandths so refill stays exact with
+//! millisecond timestamps and no floating point.
+
+const MILLI: u64 = 1000;
+
+/// Token bucket driven by caller-supplied millisecond timestamps.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: u64,
+    refill_per_sec: u64,
+    tokens_milli: u64,
+    last_ms: u64,
+}
+
+impl TokenBucket {
+    /// Create a full bucket.
+    pub fn new(capacity: u64, refill_per_sec: u64, now_ms: u64) -> Self {
+        Self {
+            capacity,
+            refill_per_sec,
+            tokens_milli: capacity.saturating_mul(MILLI),
+            last_ms: now_ms,
+        }
+    }
+
+    /// Change capacity and refill rate, keeping the current token count.
+    pub fn set_rate(&mut self, capacity: u64, refill_per_sec: u64, now_ms: u64) {
+        self.refill(now_ms);
+        self.capacity = capacity;
+        self.refill_per_sec = refill_per_sec;
+        self.tokens_milli = self.tokens_milli.min(capacity.saturating_mul(MILLI));
+    }
+
+    /// Take `cost` tokens if available.
+    pub fn try_take(&mut self, cost: u64, now_ms: u64) -> bool {
+        self.refill(now_ms);
+        let cost_milli = cost.saturating_mul(MILLI);
+        if self.tokens_milli < cost_milli {
+            return false;
+        }
+        self.tokens_milli -= cost_milli;
+        true
+    }
+
+    /// Whole tokens currently available.
+    pub fn available(&mut self, now_ms: u64) -> u64 {
+        self.refill(now_ms);
+        self.tokens_milli / MILLI
+    }
+
+    /// Fill level in permille of capacity.
+    pub fn fill_permille(&mut self, now_ms: u64) -> u64 {
+        self.refill(now_ms);
+        if self.capacity == 0 {
+            return 0;
+        }
+        self.tokens_milli / self.capacity
+    }
+
+    /// Bucket is full, so dropping it loses no state.
+    pub fn is_full(&mut self, now_ms: u64) -> bool {
+        self.refill(now_ms);
+        self.tokens_milli >= self.capacity.saturating_mul(MILLI)
+    }
+
+    /// Bucket capacity.
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    fn refill(&mut self, now_ms: u64) {
+        // Timestamps that go backwards add nothing.
+        let elapsed = now_ms.saturating_sub(self.last_ms);
+        if elapsed == 0 {
+            return;
+        }
+        // ms * tokens/sec == milli-tokens
+        let added = elapsed.saturating_mul(self.refill_per_sec);
+        self.tokens_milli = self.tokens_milli
+            .saturating_add(added)
+            .min(self.capacity.saturating_mul(MILLI));
+        self.last_ms = now_ms;
+    }
+}
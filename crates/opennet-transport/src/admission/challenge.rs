@@ -0,0 +1,145 @@
+//! Cookie / proof-of-work challenges.
+//!
+//! Under load, handshakes must echo a cookie bound to the source prefix,
+//! together with a nonce whose hash has the required number of leading
+//! zero bits. Cookies are derived from a local secret and a time window,
+//! so no per-peer state is kept until the proof checks. The claimed
+//! NodeId is not authenticated yet and plays no part.
+//!
+//! Each solved (cookie, nonce) is accepted once: it is remembered until
+//! its cookie expires, so one solution cannot be replayed.
+
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use super::prefix::IpPrefix;
+
+/// Challenge sent to a peer instead of admitting its handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    /// Cookie to echo back.
+    pub cookie: [u8; 32],
+    /// Required leading zero bits.
+    pub difficulty: u8,
+}
+
+/// Peer's answer to a challenge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChallengeResponse {
+    /// Echoed cookie.
+    pub cookie: [u8; 32],
+    /// Proof-of-work nonce.
+    pub nonce: u64,
+}
+
+impl ChallengeResponse {
+    /// Search for a nonce satisfying the challenge.
+    pub fn solve(challenge: &Challenge) -> Self {
+        Self::solve_from(challenge, 0)
+    }
+
+    /// Search for a nonce satisfying the challenge, starting at `nonce`.
+    pub fn solve_from(challenge: &Challenge, mut nonce: u64) -> Self {
+        while leading_zero_bits(&work_hash(&challenge.cookie, nonce)) < challenge.difficulty as u32 {
+            nonce = nonce.wrapping_add(1);
+        }
+        Self { cookie: challenge.cookie, nonce }
+    }
+}
+
+/// Issues and verifies challenges.
+pub struct CookieIssuer {
+    secret: [u8; 32],
+    window_ms: u64,
+    difficulty: u8,
+    max_spent: usize,
+    /// Accepted (cookie, nonce) pairs by the window their cookie was issued in.
+    spent: BTreeMap<u64, BTreeSet<([u8; 32], u64)>>,
+}
+
+impl CookieIssuer {
+    /// Create an issuer with a local secret.
+    pub fn new(secret: [u8; 32], window_ms: u64, difficulty: u8) -> Self {
+        Self {
+            secret,
+            window_ms: window_ms.max(1),
+            difficulty,
+            max_spent: 65_536,
+            spent: BTreeMap::new(),
+        }
+    }
+
+    /// Remember at most `max_spent` accepted responses; further responses
+    /// are refused until older ones expire.
+    pub fn with_max_spent(mut self, max_spent: usize) -> Self {
+        self.max_spent = max_spent;
+        self
+    }
+
+    /// Issue a challenge for a source prefix.
+    pub fn issue(&self, prefix: &IpPrefix, now_ms: u64) -> Challenge {
+        Challenge {
+            cookie: self.cookie(prefix, now_ms / self.window_ms),
+            difficulty: self.difficulty,
+        }
+    }
+
+    /// Verify a response to a cookie from the current or previous window
+    /// and mark it spent. A response is accepted at most once.
+    pub fn verify(&mut self, prefix: &IpPrefix, response: &ChallengeResponse, now_ms: u64) -> bool {
+        let window = now_ms / self.window_ms;
+        self.expire(window);
+        let issued = if response.cookie == self.cookie(prefix, window) {
+            window
+        } else if window > 0 && response.cookie == self.cookie(prefix, window - 1) {
+            window - 1
+        } else {
+            return false;
+        };
+        if leading_zero_bits(&work_hash(&response.cookie, response.nonce)) < self.difficulty as u32 {
+            return false;
+        }
+        if self.spent_count() >= self.max_spent {
+            return false;
+        }
+        self.spent.entry(issued).or_default().insert((response.cookie, response.nonce))
+    }
+
+    /// Responses remembered as spent.
+    pub fn spent_count(&self) -> usize {
+        self.spent.values().map(BTreeSet::len).sum()
+    }
+
+    /// Forget responses whose cookies can no longer verify.
+    fn expire(&mut self, window: u64) {
+        self.spent.retain(|issued, _| issued.saturating_add(1) >= window);
+    }
+
+    fn cookie(&self, prefix: &IpPrefix, window: u64) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"opennet/admission-cookie");
+        hasher.update(self.secret);
+        hasher.update(prefix.to_bytes());
+        hasher.update(window.to_be_bytes());
+        hasher.finalize().into()
+    }
+}
+
+fn work_hash(cookie: &[u8; 32], nonce: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(cookie);
+    hasher.update(nonce.to_be_bytes());
+    hasher.finalize().into()
+}
+
+fn leading_zero_bits(hash: &[u8; 32]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        if *byte == 0 {
+            bits += 8;
+        } else {
+            bits += byte.leading_zeros();
+            break;
+        }
+    }
+    bits
+}
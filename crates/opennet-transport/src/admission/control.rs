@@ -1,18 +1,254 @@
 use opennet_core::NodeId;
+use opennet_trust::events::negative::{NegativeEvent, PenaltyReason, DEFAULT_PENALTY_FACTOR};
+use opennet_trust::weight::TrustWeight;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use super::bucket::TokenBucket;
+use super::challenge::{ChallengeResponse, CookieIssuer};
+use super::limits::{AdmissionKind, RateLimit, RateLimitConfig};
+use super::prefix::IpPrefix;
 use crate::error::{TransportError, Result};
 
-pub struct AdmissionControl { threshold: f64 }
+/// Trust threshold plus token-bucket rate limiting per NodeId, per IP
+/// prefix and globally, with a proof-of-work challenge mode under load.
+///
+/// Handshakes are admitted in two steps: [`admit_handshake`](Self::admit_handshake)
+/// before authentication charges only the source prefix and the global
+/// allowance, and [`admit_peer`](Self::admit_peer) charges the NodeId's
+/// own allowance once its signature has verified.
+///
+/// Time is passed in as milliseconds so decisions are deterministic.
+pub struct AdmissionControl {
+    threshold: f64,
+    config: RateLimitConfig,
+    issuer: CookieIssuer,
+    buckets: BTreeMap<AdmissionKind, KindBuckets>,
+    violations: BTreeMap<NodeId, u32>,
+    pending_events: Vec<NegativeEvent>,
+}
+
+struct KindBuckets {
+    per_node: BTreeMap<NodeId, TokenBucket>,
+    per_prefix: BTreeMap<IpPrefix, TokenBucket>,
+    global: TokenBucket,
+}
 
 impl AdmissionControl {
-    pub fn new(threshold: f64) -> Self { Self { threshold } }
+    pub fn new(threshold: f64) -> Self {
+        Self::with_config(threshold, RateLimitConfig::default())
+    }
+
+    /// Create with explicit limits and a random cookie secret.
+    pub fn with_config(threshold: f64, config: RateLimitConfig) -> Self {
+        Self::with_cookie_secret(threshold, config, rand::random())
+    }
+
+    /// Create with explicit limits and cookie secret.
+    pub fn with_cookie_secret(threshold: f64, config: RateLimitConfig, cookie_secret: [u8; 32]) -> Self {
+        let issuer = CookieIssuer::new(cookie_secret, config.challenge_window_ms, config.challenge_difficulty)
+            .with_max_spent(config.max_tracked);
+        let buckets = [AdmissionKind::Handshake, AdmissionKind::Stream, AdmissionKind::Message]
+            .into_iter()
+            .map(|kind| {
+                let global = config.limits(kind).global;
+                (kind, KindBuckets {
+                    per_node: BTreeMap::new(),
+                    per_prefix: BTreeMap::new(),
+                    global: TokenBucket::new(global.capacity, global.refill_per_sec, 0),
+                })
+            })
+            .collect();
+        Self {
+            threshold,
+            config,
+            issuer,
+            buckets,
+            violations: BTreeMap::new(),
+            pending_events: Vec::new(),
+        }
+    }
+
     pub fn check(&self, _node_id: &NodeId, trust_weight: f64) -> Result<()> {
         if trust_weight < self.threshold {
             return Err(TransportError::TrustTooLow);
         }
         Ok(())
     }
+
+    /// Admit a handshake before anything about the peer is authenticated.
+    ///
+    /// Only the source prefix and global allowances are charged; the
+    /// claimed NodeId is not trusted yet. Under load a valid, unspent
+    /// `response` to a challenge issued for the prefix is required; it
+    /// replaces the global allowance but not the per-prefix one. Nothing
+    /// here produces penalties.
+    pub fn admit_handshake(&mut self, addr: IpAddr, now_ms: u64, response: Option<&ChallengeResponse>) -> Result<()> {
+        let prefix = self.prefix(addr);
+        let use_global = if self.under_load(now_ms) {
+            match response {
+                Some(response) if self.issuer.verify(&prefix, response, now_ms) => false,
+                Some(_) => return Err(TransportError::InvalidChallenge),
+                None => return Err(TransportError::ChallengeRequired(self.issuer.issue(&prefix, now_ms))),
+            }
+        } else {
+            true
+        };
+        let limit = self.config.handshake.per_prefix;
+        let max_tracked = self.config.max_tracked;
+        let buckets = self.kind_buckets(AdmissionKind::Handshake);
+        let Some(net) = tracked_bucket(&mut buckets.per_prefix, prefix, limit, max_tracked, now_ms) else {
+            return Err(TransportError::RateLimited);
+        };
+        if net.available(now_ms) < 1 || (use_global && buckets.global.available(now_ms) < 1) {
+            return Err(TransportError::RateLimited);
+        }
+        net.try_take(1, now_ms);
+        if use_global {
+            buckets.global.try_take(1, now_ms);
+        }
+        Ok(())
+    }
+
+    /// Charge a peer's own handshake allowance once its handshake
+    /// signature has verified, so the NodeId is no longer just a claim.
+    pub fn admit_peer(&mut self, node_id: &NodeId, trust_weight: f64, now_ms: u64) -> Result<()> {
+        let limit = self.config.handshake.per_node.scaled(trust_permille(trust_weight), self.config.trust_multiplier);
+        let max_tracked = self.config.max_tracked;
+        let buckets = self.kind_buckets(AdmissionKind::Handshake);
+        let admitted = tracked_bucket(&mut buckets.per_node, *node_id, limit, max_tracked, now_ms)
+            .is_some_and(|bucket| bucket.try_take(1, now_ms));
+        if admitted {
+            return Ok(());
+        }
+        self.record_violation(*node_id);
+        Err(TransportError::RateLimited)
+    }
+
+    /// Admit a new stream from an authenticated peer.
+    pub fn admit_stream(&mut self, node_id: &NodeId, addr: IpAddr, trust_weight: f64, now_ms: u64) -> Result<()> {
+        let prefix = self.prefix(addr);
+        self.admit_with(AdmissionKind::Stream, node_id, prefix, trust_weight, now_ms)
+    }
+
+    /// Admit a message from an authenticated peer.
+    pub fn admit_message(&mut self, node_id: &NodeId, addr: IpAddr, trust_weight: f64, now_ms: u64) -> Result<()> {
+        let prefix = self.prefix(addr);
+        self.admit_with(AdmissionKind::Message, node_id, prefix, trust_weight, now_ms)
+    }
+
+    /// Whether handshakes currently require a challenge.
+    pub fn under_load(&mut self, now_ms: u64) -> bool {
+        let threshold = self.config.challenge_threshold_permille;
+        self.kind_buckets(AdmissionKind::Handshake).global.fill_permille(now_ms) < threshold
+    }
+
+    /// Drop buckets that have refilled completely and their violation counts.
+    pub fn prune(&mut self, now_ms: u64) {
+        for kind in self.buckets.values_mut() {
+            kind.per_node.retain(|_, bucket| !bucket.is_full(now_ms));
+            kind.per_prefix.retain(|_, bucket| !bucket.is_full(now_ms));
+        }
+        let buckets = &self.buckets;
+        self.violations.retain(|node_id, _| buckets.values().any(|k| k.per_node.contains_key(node_id)));
+    }
+
+    /// Drain penalties for authenticated peers that keep exceeding limits.
+    pub fn drain_events(&mut self) -> Vec<NegativeEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
+    fn admit_with(
+        &mut self,
+        kind: AdmissionKind,
+        node_id: &NodeId,
+        prefix: IpPrefix,
+        trust_weight: f64,
+        now_ms: u64,
+    ) -> Result<()> {
+        let limits = *self.config.limits(kind);
+        let node_limit = limits.per_node.scaled(trust_permille(trust_weight), self.config.trust_multiplier);
+        let max_tracked = self.config.max_tracked;
+        let buckets = self.kind_buckets(kind);
+
+        let admitted = match (
+            tracked_bucket(&mut buckets.per_node, *node_id, node_limit, max_tracked, now_ms),
+            tracked_bucket(&mut buckets.per_prefix, prefix, limits.per_prefix, max_tracked, now_ms),
+        ) {
+            (Some(node), Some(net)) => {
+                let global_ok = buckets.global.available(now_ms) >= 1;
+                if global_ok && node.available(now_ms) >= 1 && net.available(now_ms) >= 1 {
+                    node.try_take(1, now_ms);
+                    net.try_take(1, now_ms);
+                    buckets.global.try_take(1, now_ms);
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        };
+
+        if admitted {
+            return Ok(());
+        }
+        self.record_violation(*node_id);
+        Err(TransportError::RateLimited)
+    }
+
+    fn kind_buckets(&mut self, kind: AdmissionKind) -> &mut KindBuckets {
+        self.buckets.get_mut(&kind).expect("buckets exist for every admission kind")
+    }
+
+    fn prefix(&self, addr: IpAddr) -> IpPrefix {
+        IpPrefix::from_addr(addr, self.config.ipv4_prefix_len, self.config.ipv6_prefix_len)
+    }
+
+    fn record_violation(&mut self, node_id: NodeId) {
+        let count = self.violations.entry(node_id).or_insert(0);
+        *count += 1;
+        if *count >= self.config.violation_threshold {
+            *count = 0;
+            self.penalize(node_id, PenaltyReason::RateLimitExceeded);
+        }
+    }
+
+    fn penalize(&mut self, node_id: NodeId, reason: PenaltyReason) {
+        self.pending_events.push(NegativeEvent {
+            node_id,
+            penalty: TrustWeight::from_f64(DEFAULT_PENALTY_FACTOR),
+            reason,
+        });
+    }
 }
 
 impl Default for AdmissionControl {
     fn default() -> Self { Self::new(0.3) }
 }
+
+/// Get (or create) the bucket for `key`, rescaled to `limit`.
+///
+/// Returns `None` when the table is full of active buckets; new keys are
+/// refused rather than evicting someone who is currently being limited.
+fn tracked_bucket<K: Ord>(
+    map: &mut BTreeMap<K, TokenBucket>,
+    key: K,
+    limit: RateLimit,
+    max_tracked: usize,
+    now_ms: u64,
+) -> Option<&mut TokenBucket> {
+    if !map.contains_key(&key) && map.len() >= max_tracked {
+        map.retain(|_, bucket| !bucket.is_full(now_ms));
+        if map.len() >= max_tracked {
+            return None;
+        }
+    }
+    let bucket = map
+        .entry(key)
+        .or_insert_with(|| TokenBucket::new(limit.capacity, limit.refill_per_sec, now_ms));
+    bucket.set_rate(limit.capacity, limit.refill_per_sec, now_ms);
+    Some(bucket)
+}
+
+fn trust_permille(trust_weight: f64) -> u64 {
+    (trust_weight.clamp(0.0, 1.0) * 1000.0) as u64
+}
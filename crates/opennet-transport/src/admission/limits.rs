@@ -0,0 +1,119 @@
+//! Rate limit configuration.
+
+/// Kind of work being admitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AdmissionKind {
+    /// New handshake (NodeHello).
+    Handshake,
+    /// New stream on an established session.
+    Stream,
+    /// Individual message.
+    Message,
+}
+
+/// Capacity and refill rate for one token bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Burst size in tokens.
+    pub capacity: u64,
+    /// Tokens added per second.
+    pub refill_per_sec: u64,
+}
+
+impl RateLimit {
+    /// Create a rate limit.
+    pub const fn new(capacity: u64, refill_per_sec: u64) -> Self {
+        Self { capacity, refill_per_sec }
+    }
+
+    /// Scale by a trust weight in permille.
+    ///
+    /// A node with zero trust gets the base allowance; full trust gets
+    /// `1 + multiplier` times the base.
+    pub fn scaled(self, trust_permille: u64, multiplier: u64) -> Self {
+        let bonus = |base: u64| base.saturating_mul(trust_permille.min(1000)).saturating_mul(multiplier) / 1000;
+        Self {
+            capacity: self.capacity.saturating_add(bonus(self.capacity)),
+            refill_per_sec: self.refill_per_sec.saturating_add(bonus(self.refill_per_sec)),
+        }
+    }
+}
+
+/// Per-NodeId, per-prefix and global limits for one admission kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KindLimits {
+    /// Limit per authenticated NodeId (scaled by trust).
+    pub per_node: RateLimit,
+    /// Limit per source IP prefix.
+    pub per_prefix: RateLimit,
+    /// Limit across all peers.
+    pub global: RateLimit,
+}
+
+/// Admission control configuration.
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Handshake limits.
+    pub handshake: KindLimits,
+    /// Stream open limits.
+    pub stream: KindLimits,
+    /// Message limits.
+    pub message: KindLimits,
+    /// Extra allowance multiplier applied at full trust.
+    pub trust_multiplier: u64,
+    /// IPv4 prefix length used for per-prefix buckets.
+    pub ipv4_prefix_len: u8,
+    /// IPv6 prefix length used for per-prefix buckets.
+    pub ipv6_prefix_len: u8,
+    /// Global handshake fill level (permille) below which challenges are required.
+    pub challenge_threshold_permille: u64,
+    /// Required leading zero bits in a challenge solution.
+    pub challenge_difficulty: u8,
+    /// Validity window of a challenge cookie in milliseconds.
+    pub challenge_window_ms: u64,
+    /// Rejections per NodeId before a penalty is emitted.
+    pub violation_threshold: u32,
+    /// Maximum tracked NodeIds or prefixes per admission kind.
+    pub max_tracked: usize,
+}
+
+impl RateLimitConfig {
+    /// Limits for an admission kind.
+    pub fn limits(&self, kind: AdmissionKind) -> &KindLimits {
+        match kind {
+            AdmissionKind::Handshake => &self.handshake,
+            AdmissionKind::Stream => &self.stream,
+            AdmissionKind::Message => &self.message,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            handshake: KindLimits {
+                per_node: RateLimit::new(4, 1),
+                per_prefix: RateLimit::new(16, 4),
+                global: RateLimit::new(256, 64),
+            },
+            stream: KindLimits {
+                per_node: RateLimit::new(32, 8),
+                per_prefix: RateLimit::new(128, 32),
+                global: RateLimit::new(4096, 1024),
+            },
+            message: KindLimits {
+                per_node: RateLimit::new(256, 64),
+                per_prefix: RateLimit::new(1024, 256),
+                global: RateLimit::new(65536, 16384),
+            },
+            trust_multiplier: 4,
+            ipv4_prefix_len: 24,
+            ipv6_prefix_len: 48,
+            challenge_threshold_permille: 250,
+            challenge_difficulty: 16,
+            challenge_window_ms: 30_000,
+            violation_threshold: 16,
+            max_tracked: 65536,
+        }
+    }
+}
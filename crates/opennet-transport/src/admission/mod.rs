@@ -1,3 +1,11 @@
 pub mod control;
 pub mod threshold;
+pub mod bucket;
+pub mod limits;
+pub mod prefix;
+pub mod challenge;
 pub use control::AdmissionControl;
+pub use bucket::TokenBucket;
+pub use limits::{AdmissionKind, KindLimits, RateLimit, RateLimitConfig};
+pub use prefix::IpPrefix;
+pub use challenge::{Challenge, ChallengeResponse, CookieIssuer};
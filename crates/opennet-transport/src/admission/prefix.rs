@@ -0,0 +1,61 @@
+//! IP prefix keys for per-network rate limiting.
+
+use std::net::IpAddr;
+
+/// Masked IP prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IpPrefix {
+    /// IPv4 network address and prefix length.
+    V4([u8; 4], u8),
+    /// IPv6 network address and prefix length.
+    V6([u8; 16], u8),
+}
+
+impl IpPrefix {
+    /// Mask an address down to its prefix.
+    pub fn from_addr(addr: IpAddr, v4_len: u8, v6_len: u8) -> Self {
+        match addr {
+            IpAddr::V4(v4) => {
+                let len = v4_len.min(32);
+                Self::V4(mask(v4.octets(), len), len)
+            }
+            IpAddr::V6(v6) => {
+                // IPv4-mapped addresses share buckets with plain IPv4.
+                if let Some(v4) = v6.to_ipv4_mapped() {
+                    return Self::from_addr(IpAddr::V4(v4), v4_len, v6_len);
+                }
+                let len = v6_len.min(128);
+                Self::V6(mask(v6.octets(), len), len)
+            }
+        }
+    }
+
+    /// Canonical bytes (for cookie derivation).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::V4(octets, len) => {
+                let mut out = vec![4, *len];
+                out.extend_from_slice(octets);
+                out
+            }
+            Self::V6(octets, len) => {
+                let mut out = vec![6, *len];
+                out.extend_from_slice(octets);
+                out
+            }
+        }
+    }
+}
+
+fn mask<const N: usize>(mut octets: [u8; N], len: u8) -> [u8; N] {
+    let len = len as usize;
+    for (i, byte) in octets.iter_mut().enumerate() {
+        let bit = i * 8;
+        if bit >= len {
+            *byte = 0;
+        } else if bit + 8 > len {
+            *byte &= 0xffu8 << (8 - (len - bit));
+        }
+    }
+    octets
+}
@@ -1,4 +1,5 @@
 use thiserror::Error;
+use crate::admission::Challenge;
 pub type Result<T> = std::result::Result<T, TransportError>;
 
 #[derive(Debug, Error)]
@@ -11,6 +12,14 @@ pub enum TransportError {
     SessionInvalid,
     #[error("trust too low")]
     TrustTooLow,
+    #[error("rate limited")]
+    RateLimited,
+    #[error("challenge required")]
+    ChallengeRequired(Challenge),
+    #[error("invalid challenge response")]
+    InvalidChallenge,
+    #[error("relay unavailable")]
+    RelayUnavailable,
     #[error("io error: {0}")]
     IoError(#[from] std::io::Error),
 }
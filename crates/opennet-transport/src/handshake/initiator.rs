@@ -1,9 +1,71 @@
-use opennet_core::NodeId;
-use crate::error::Result;
+//! Initiator side of the handshake.
 
-pub struct HandshakeInitiator { local_node_id: NodeId }
+use opennet_core::{Epoch, NodeId};
+use opennet_core::types::{AnyPublicKey, AnySignature, PublicKey, Timestamp};
+use opennet_identity::algorithm::{features_for, negotiate_features, negotiated_algorithm};
+use opennet_identity::NodeSigner;
+use opennet_wire::messages::{NodeHello, NodeWelcome};
+use super::verification::{check_key_binding, sign_handshake_any, transcript, verify_handshake_any};
+use crate::error::{Result, TransportError};
+use crate::nat::ObservedAddrs;
+
+/// Sends our NodeHello and handles the NodeWelcome.
+pub struct HandshakeInitiator {
+    local_node_id: NodeId,
+    epoch: Epoch,
+    public_key: AnyPublicKey,
+    features: u64,
+}
 
 impl HandshakeInitiator {
-    pub fn new(node_id: NodeId) -> Self { Self { local_node_id: node_id } }
-    pub async fn initiate(&self, _remote: &NodeId) -> Result<()> { Ok(()) }
+    /// Initiator for our identity at `epoch`, advertising the features
+    /// this build supports for our key.
+    pub fn new(node_id: NodeId, epoch: Epoch, public_key: AnyPublicKey) -> Self {
+        let features = features_for(public_key.algorithm());
+        Self { local_node_id: node_id, epoch, public_key, features }
+    }
+
+    /// Feature bits we support.
+    pub fn with_features(mut self, features: u64) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Hello to open a handshake.
+    pub fn hello(&self, now: Timestamp) -> NodeHello {
+        NodeHello::new(self.local_node_id, self.epoch.clone(), self.public_key.clone(), now).with_features(self.features)
+    }
+
+    /// Handle the responder's welcome to `hello`: check the key it carries
+    /// is bound to its NodeId, directly or through the `known` key for its
+    /// epoch, and signed the transcript; record the address it saw us
+    /// connect from and return the features both sides accepted.
+    pub fn on_welcome(
+        &self,
+        hello: &NodeHello,
+        welcome: &NodeWelcome,
+        known: Option<&PublicKey>,
+        observed: &mut ObservedAddrs,
+    ) -> Result<u64> {
+        if welcome.features != negotiate_features(self.features, welcome.features) {
+            return Err(TransportError::HandshakeFailed("welcome accepts features we did not offer".into()));
+        }
+        check_key_binding(&welcome.node_id, &welcome.public_key, known)?;
+        let Some(signature) = &welcome.signature else {
+            return Err(TransportError::HandshakeFailed("unsigned welcome".into()));
+        };
+        let algorithm = negotiated_algorithm(welcome.features);
+        if !verify_handshake_any(&welcome.public_key, algorithm, &transcript(hello, welcome), signature)? {
+            return Err(TransportError::HandshakeFailed("invalid responder signature".into()));
+        }
+        if let Some(addr) = welcome.observed_addr {
+            observed.record(welcome.node_id, addr);
+        }
+        Ok(welcome.features)
+    }
+
+    /// Our transcript signature, proving our key to the responder.
+    pub fn sign(&self, hello: &NodeHello, welcome: &NodeWelcome, signer: &dyn NodeSigner) -> Result<AnySignature> {
+        sign_handshake_any(signer, negotiated_algorithm(welcome.features), &transcript(hello, welcome))
+    }
 }
@@ -1,9 +1,79 @@
-use opennet_core::NodeId;
-use crate::error::Result;
+//! Responder side of the handshake.
 
-pub struct HandshakeResponder { local_node_id: NodeId }
+use opennet_core::{Epoch, NodeId};
+use opennet_core::types::{AnyPublicKey, AnySignature, PublicKey, Timestamp};
+use opennet_identity::algorithm::{features_for, negotiate_features, negotiated_algorithm};
+use opennet_identity::NodeSigner;
+use opennet_wire::messages::{NodeHello, NodeWelcome};
+use std::net::SocketAddr;
+use super::verification::{check_key_binding, sign_handshake_any, transcript, verify_handshake_any};
+use crate::error::{Result, TransportError};
+
+/// Answers NodeHello messages with our signed NodeWelcome.
+pub struct HandshakeResponder {
+    local_node_id: NodeId,
+    epoch: Epoch,
+    public_key: AnyPublicKey,
+    features: u64,
+}
 
 impl HandshakeResponder {
-    pub fn new(node_id: NodeId) -> Self { Self { local_node_id: node_id } }
-    pub async fn respond(&self) -> Result<NodeId> { Ok(self.local_node_id) }
+    /// Responder for our identity at `epoch`, offering the features this
+    /// build supports for our key.
+    pub fn new(node_id: NodeId, epoch: Epoch, public_key: AnyPublicKey) -> Self {
+        let features = features_for(public_key.algorithm());
+        Self { local_node_id: node_id, epoch, public_key, features }
+    }
+
+    /// Feature bits we support.
+    pub fn with_features(mut self, features: u64) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Answer a hello that arrived from `remote`, refusing protocol
+    /// versions we no longer speak. The welcome accepts the features both
+    /// sides support, reflects `remote` back so the initiator learns its
+    /// public address, and is signed by `signer`.
+    pub fn welcome(
+        &self,
+        hello: &NodeHello,
+        remote: SocketAddr,
+        now: Timestamp,
+        signer: &dyn NodeSigner,
+    ) -> Result<NodeWelcome> {
+        if !hello.is_supported_version() {
+            return Err(TransportError::HandshakeFailed(format!("unsupported protocol version {}", hello.version)));
+        }
+        let mut welcome = NodeWelcome {
+            node_id: self.local_node_id,
+            epoch: self.epoch.clone(),
+            public_key: self.public_key.clone(),
+            timestamp: now,
+            features: negotiate_features(self.features, hello.features),
+            observed_addr: Some(remote),
+            signature: None,
+        };
+        let algorithm = negotiated_algorithm(welcome.features);
+        welcome.signature = Some(sign_handshake_any(signer, algorithm, &transcript(hello, &welcome))?);
+        Ok(welcome)
+    }
+
+    /// Check the key in the initiator's hello is bound to its NodeId,
+    /// directly or through the `known` key for its epoch, and signed the
+    /// transcript.
+    pub fn verify_initiator(
+        &self,
+        hello: &NodeHello,
+        welcome: &NodeWelcome,
+        known: Option<&PublicKey>,
+        signature: &AnySignature,
+    ) -> Result<()> {
+        check_key_binding(&hello.node_id, &hello.public_key, known)?;
+        let algorithm = negotiated_algorithm(welcome.features);
+        if !verify_handshake_any(&hello.public_key, algorithm, &transcript(hello, welcome), signature)? {
+            return Err(TransportError::HandshakeFailed("invalid initiator signature".into()));
+        }
+        Ok(())
+    }
 }
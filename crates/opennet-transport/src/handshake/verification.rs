@@ -1,6 +1,92 @@
-use opennet_core::types::{PublicKey, Signature};
-use crate::error::Result;
+use opennet_core::NodeId;
+use opennet_core::types::{AnyPublicKey, AnySignature, PublicKey, Signature, SignatureAlgorithm};
+use opennet_identity::algorithm::verify_any;
+use opennet_identity::{verify_signature, NodeSigner, SigningContext};
+use opennet_wire::cbor::CborEncoder;
+use opennet_wire::messages::{NodeHello, NodeWelcome};
+use crate::error::{TransportError, Result};
 
-pub fn verify_handshake(_public_key: &PublicKey, _message: &[u8], _signature: &Signature) -> Result<bool> {
-    Ok(true)
+/// Sign the handshake transcript with the node's signer.
+pub fn sign_handshake(signer: &dyn NodeSigner, transcript: &[u8]) -> Result<Signature> {
+    signer
+        .sign(&handshake_bytes(transcript))
+        .map_err(|e| TransportError::HandshakeFailed(e.to_string()))
+}
+
+pub fn verify_handshake(public_key: &PublicKey, transcript: &[u8], signature: &Signature) -> Result<bool> {
+    Ok(verify_signature(public_key, &handshake_bytes(transcript), signature).is_ok())
+}
+
+/// Sign the transcript with the node's full key.
+///
+/// `algorithm` is the strongest one negotiated for the session. A hybrid
+/// key that the peer cannot verify fails the handshake rather than
+/// falling back to its Ed25519 half.
+pub fn sign_handshake_any(signer: &dyn NodeSigner, algorithm: SignatureAlgorithm, transcript: &[u8]) -> Result<AnySignature> {
+    if !permits(algorithm, signer.algorithm()) {
+        return Err(TransportError::HandshakeFailed("peer cannot verify our key's algorithm".into()));
+    }
+    signer.sign_any(&handshake_bytes(transcript)).map_err(|e| TransportError::HandshakeFailed(e.to_string()))
+}
+
+/// Verify a transcript signature under the peer's full key.
+///
+/// The key and signature must use the negotiated `algorithm`: a hybrid
+/// key is never checked on its Ed25519 half alone, and an Ed25519 key is
+/// refused once hybrid signatures were negotiated.
+pub fn verify_handshake_any(
+    public_key: &AnyPublicKey,
+    algorithm: SignatureAlgorithm,
+    transcript: &[u8],
+    signature: &AnySignature,
+) -> Result<bool> {
+    if !permits(algorithm, public_key.algorithm()) {
+        return Ok(false);
+    }
+    Ok(verify_any(public_key, &handshake_bytes(transcript), signature).is_ok())
+}
+
+/// Whether a session negotiated for `negotiated` can use a key of `key`.
+fn permits(negotiated: SignatureAlgorithm, key: SignatureAlgorithm) -> bool {
+    key == negotiated
+}
+
+/// Check that the key a peer presented is its own: the full key derives
+/// its NodeId, or its Ed25519 half is the key `known` for the peer's
+/// current epoch, e.g. from its rotation log.
+pub fn check_key_binding(node_id: &NodeId, public_key: &AnyPublicKey, known: Option<&PublicKey>) -> Result<()> {
+    if &NodeId::from_any_public_key(public_key) == node_id {
+        return Ok(());
+    }
+    match (known, public_key.ed25519()) {
+        (Some(known), Ok(key)) if known == &key => Ok(()),
+        _ => Err(TransportError::HandshakeFailed("key is not bound to the NodeId".into())),
+    }
+}
+
+/// Transcript both sides sign: the hello and the welcome, including each
+/// side's full algorithm-tagged key, without the welcome's signature or
+/// the address it reflects.
+pub fn transcript(hello: &NodeHello, welcome: &NodeWelcome) -> Vec<u8> {
+    let mut enc = CborEncoder::new();
+    enc.encode_array_header(11)
+        .encode_bytes(hello.node_id.as_bytes())
+        .encode_uint(hello.epoch.id)
+        .encode_bytes(&hello.public_key.to_bytes())
+        .encode_timestamp(hello.timestamp)
+        .encode_uint(u64::from(hello.version))
+        .encode_uint(hello.features)
+        .encode_bytes(welcome.node_id.as_bytes())
+        .encode_uint(welcome.epoch.id)
+        .encode_bytes(&welcome.public_key.to_bytes())
+        .encode_timestamp(welcome.timestamp)
+        .encode_uint(welcome.features);
+    enc.into_bytes()
+}
+
+/// Transcript in the handshake signing context.
+pub fn handshake_bytes(transcript: &[u8]) -> Vec<u8> {
+    SigningContext::Handshake.signing_bytes(|enc| {
+        enc.encode_bytes(transcript);
+    })
 }
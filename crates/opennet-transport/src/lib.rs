@@ -10,6 +10,7 @@ pub mod tcp;
 pub mod session;
 pub mod admission;
 pub mod handshake;
+pub mod nat;
 pub mod error;
 
 mod backpressure;
@@ -0,0 +1,13 @@
+//! NAT traversal: observed-address reflection, coordinated hole punching
+//! and relay fallback.
+//!
+//! Everything here is sans-IO: callers feed in messages, probe packets and
+//! millisecond timestamps, and send whatever actions come back.
+
+pub mod observed;
+pub mod punch;
+pub mod relay;
+
+pub use observed::{ObservedAddrs, NatMapping, Reachability};
+pub use punch::{HolePunch, PunchAction, PunchConfig, coordinate};
+pub use relay::RelayTable;
@@ -0,0 +1,113 @@
+//! Observed-address tracking from `NodeWelcome.observed_addr` reflections.
+
+use opennet_core::NodeId;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+
+/// Reports from distinct peers needed before an address is trusted.
+pub const MIN_CONFIRMATIONS: usize = 2;
+
+/// How the local NAT maps outbound flows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NatMapping {
+    /// Not enough reports yet.
+    Unknown,
+    /// Same public address for every destination (cone NAT, punchable).
+    EndpointIndependent,
+    /// Different public port per destination (symmetric NAT, needs relay).
+    EndpointDependent,
+}
+
+/// Whether a node can be dialed directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reachability {
+    /// Not enough information.
+    Unknown,
+    /// Publicly reachable at this address.
+    Public(SocketAddr),
+    /// Behind a NAT with the given mapping behavior.
+    BehindNat(NatMapping),
+}
+
+/// Addresses peers report seeing us connect from.
+pub struct ObservedAddrs {
+    reports: BTreeMap<NodeId, SocketAddr>,
+    max_reports: usize,
+}
+
+impl ObservedAddrs {
+    /// Create tracker keeping at most `max_reports` reporters.
+    pub fn new(max_reports: usize) -> Self {
+        Self { reports: BTreeMap::new(), max_reports }
+    }
+
+    /// Record a reflection from a peer (latest report per peer wins).
+    pub fn record(&mut self, reporter: NodeId, addr: SocketAddr) {
+        if !self.reports.contains_key(&reporter) && self.reports.len() >= self.max_reports {
+            return;
+        }
+        self.reports.insert(reporter, addr);
+    }
+
+    /// Forget a peer's report (e.g. on disconnect).
+    pub fn remove(&mut self, reporter: &NodeId) {
+        self.reports.remove(reporter);
+    }
+
+    /// Most-reported address, once confirmed by enough distinct peers.
+    pub fn public_addr(&self) -> Option<SocketAddr> {
+        let mut counts: BTreeMap<SocketAddr, usize> = BTreeMap::new();
+        for addr in self.reports.values() {
+            *counts.entry(*addr).or_insert(0) += 1;
+        }
+        // Ties go to the lowest address so every run agrees.
+        counts.into_iter()
+            .filter(|(_, n)| *n >= MIN_CONFIRMATIONS)
+            .max_by(|(a, na), (b, nb)| na.cmp(nb).then(b.cmp(a)))
+            .map(|(addr, _)| addr)
+    }
+
+    /// Infer NAT mapping behavior from the reports.
+    pub fn mapping(&self) -> NatMapping {
+        if self.reports.len() < MIN_CONFIRMATIONS {
+            return NatMapping::Unknown;
+        }
+        let mut ports: BTreeMap<std::net::IpAddr, std::collections::BTreeSet<u16>> = BTreeMap::new();
+        for addr in self.reports.values() {
+            ports.entry(addr.ip()).or_default().insert(addr.port());
+        }
+        if ports.values().any(|p| p.len() > 1) {
+            NatMapping::EndpointDependent
+        } else {
+            NatMapping::EndpointIndependent
+        }
+    }
+
+    /// Reachability given the address we are bound to.
+    pub fn reachability(&self, local: SocketAddr) -> Reachability {
+        match self.public_addr() {
+            Some(public) if public == local => Reachability::Public(public),
+            _ => match self.mapping() {
+                NatMapping::Unknown => Reachability::Unknown,
+                mapping => Reachability::BehindNat(mapping),
+            },
+        }
+    }
+
+    /// Candidate addresses to advertise: confirmed public first, then local.
+    pub fn candidates(&self, local: &[SocketAddr]) -> Vec<SocketAddr> {
+        let mut out: Vec<SocketAddr> = self.public_addr().into_iter().collect();
+        for addr in local {
+            if !out.contains(addr) {
+                out.push(*addr);
+            }
+        }
+        out
+    }
+}
+
+impl Default for ObservedAddrs {
+    fn default() -> Self {
+        Self::new(32)
+    }
+}
@@ -0,0 +1,239 @@
+//! Coordinated UDP hole punching.
+//!
+//! Both sides learn each other's candidate addresses from the rendezvous
+//! and start sending probes at the same moment from the socket the QUIC
+//! endpoint is bound to. Outbound probes open the local NAT mapping, so
+//! the peer's probes get through; the first probe received fixes the
+//! address the QUIC connection is then dialed on.
+
+use opennet_core::NodeId;
+use opennet_core::types::Timestamp;
+use opennet_wire::messages::{PunchConnect, PunchRequest, RelayRequest};
+use std::net::SocketAddr;
+
+/// Probe packet magic.
+const PROBE_MAGIC: &[u8; 8] = b"ONPUNCH\0";
+
+/// Probe packet length: magic plus nonce.
+pub const PROBE_LEN: usize = 16;
+
+/// Hole punching timing.
+#[derive(Debug, Clone, Copy)]
+pub struct PunchConfig {
+    /// Delay the rendezvous asks both sides to wait before probing.
+    pub start_delay_ms: u64,
+    /// Interval between probe rounds.
+    pub probe_interval_ms: u64,
+    /// Probe rounds before falling back to relay.
+    pub max_rounds: u32,
+}
+
+impl Default for PunchConfig {
+    fn default() -> Self {
+        Self { start_delay_ms: 200, probe_interval_ms: 100, max_rounds: 20 }
+    }
+}
+
+/// What the caller should do next.
+#[derive(Debug, Clone)]
+pub enum PunchAction {
+    /// Send a probe datagram.
+    SendProbe { to: SocketAddr, packet: [u8; PROBE_LEN] },
+    /// Path is open; dial or accept QUIC on this address.
+    Connected { addr: SocketAddr },
+    /// Punching failed; send this to the rendezvous.
+    FallbackRelay { via: NodeId, request: RelayRequest },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PunchState {
+    /// Waiting for the rendezvous to answer.
+    Requested,
+    /// Probing candidate addresses.
+    Probing { addrs: Vec<SocketAddr>, next_round_ms: u64, rounds: u32 },
+    /// Path open.
+    Connected(SocketAddr),
+    /// Gave up and asked for relay.
+    Relayed,
+}
+
+/// One hole punching attempt, on either side.
+pub struct HolePunch {
+    local: NodeId,
+    peer: NodeId,
+    rendezvous: NodeId,
+    initiator: bool,
+    nonce: u64,
+    config: PunchConfig,
+    state: PunchState,
+}
+
+impl HolePunch {
+    /// Start an attempt as initiator; send the request to `rendezvous`.
+    pub fn initiate(
+        local: NodeId,
+        target: NodeId,
+        rendezvous: NodeId,
+        candidates: Vec<SocketAddr>,
+        nonce: u64,
+        now_ms: u64,
+        config: PunchConfig,
+    ) -> (Self, PunchRequest) {
+        let request = PunchRequest {
+            initiator: local,
+            target,
+            initiator_addrs: candidates,
+            nonce,
+            timestamp: Timestamp::from_millis(now_ms),
+        };
+        let punch = Self {
+            local,
+            peer: target,
+            rendezvous,
+            initiator: true,
+            nonce,
+            config,
+            state: PunchState::Requested,
+        };
+        (punch, request)
+    }
+
+    /// Join an attempt as target after the rendezvous sent `connect`.
+    pub fn respond(local: NodeId, rendezvous: NodeId, connect: &PunchConnect, now_ms: u64, config: PunchConfig) -> Self {
+        let mut punch = Self {
+            local,
+            peer: connect.peer,
+            rendezvous,
+            initiator: false,
+            nonce: connect.nonce,
+            config,
+            state: PunchState::Requested,
+        };
+        punch.on_connect(connect, now_ms);
+        punch
+    }
+
+    /// Handle the rendezvous' `PunchConnect`.
+    pub fn on_connect(&mut self, connect: &PunchConnect, now_ms: u64) {
+        if connect.nonce != self.nonce || connect.peer != self.peer || self.state != PunchState::Requested {
+            return;
+        }
+        self.state = PunchState::Probing {
+            addrs: connect.peer_addrs.clone(),
+            next_round_ms: now_ms.saturating_add(connect.start_delay_ms),
+            rounds: 0,
+        };
+    }
+
+    /// Handle an incoming datagram that may be a probe.
+    pub fn on_probe(&mut self, from: SocketAddr, packet: &[u8]) -> Vec<PunchAction> {
+        if parse_probe(packet) != Some(self.nonce) {
+            return Vec::new();
+        }
+        match self.state {
+            PunchState::Probing { .. } | PunchState::Requested => {
+                self.state = PunchState::Connected(from);
+                // Answer so the peer sees a probe through the now-open mapping.
+                vec![
+                    PunchAction::SendProbe { to: from, packet: probe_packet(self.nonce) },
+                    PunchAction::Connected { addr: from },
+                ]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Drive timers.
+    pub fn poll(&mut self, now_ms: u64) -> Vec<PunchAction> {
+        let PunchState::Probing { addrs, next_round_ms, rounds } = &mut self.state else {
+            return Vec::new();
+        };
+        if now_ms < *next_round_ms {
+            return Vec::new();
+        }
+        if *rounds >= self.config.max_rounds {
+            self.state = PunchState::Relayed;
+            // Only the initiator asks for relay; the target just stops.
+            if !self.initiator {
+                return Vec::new();
+            }
+            return vec![PunchAction::FallbackRelay {
+                via: self.rendezvous,
+                request: RelayRequest { initiator: self.local, target: self.peer, nonce: self.nonce },
+            }];
+        }
+        *rounds += 1;
+        *next_round_ms = now_ms.saturating_add(self.config.probe_interval_ms);
+        addrs.iter()
+            .map(|to| PunchAction::SendProbe { to: *to, packet: probe_packet(self.nonce) })
+            .collect()
+    }
+
+    /// Connected address, if the punch succeeded.
+    pub fn connected_addr(&self) -> Option<SocketAddr> {
+        match self.state {
+            PunchState::Connected(addr) => Some(addr),
+            _ => None,
+        }
+    }
+
+    /// Punch gave up and fell back to relay.
+    pub fn is_relayed(&self) -> bool {
+        self.state == PunchState::Relayed
+    }
+
+    /// Attempt nonce.
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Rendezvous side: build the `PunchConnect` pair for a request.
+///
+/// `initiator_seen` and `target_seen` are the addresses the rendezvous
+/// observes on its own sessions with both peers; they go first because
+/// they are what the NATs actually map.
+pub fn coordinate(
+    request: &PunchRequest,
+    initiator_seen: SocketAddr,
+    target_seen: SocketAddr,
+    config: &PunchConfig,
+) -> (PunchConnect, PunchConnect) {
+    let mut initiator_addrs = vec![initiator_seen];
+    for addr in &request.initiator_addrs {
+        if !initiator_addrs.contains(addr) {
+            initiator_addrs.push(*addr);
+        }
+    }
+    let to_initiator = PunchConnect {
+        peer: request.target,
+        peer_addrs: vec![target_seen],
+        nonce: request.nonce,
+        start_delay_ms: config.start_delay_ms,
+    };
+    let to_target = PunchConnect {
+        peer: request.initiator,
+        peer_addrs: initiator_addrs,
+        nonce: request.nonce,
+        start_delay_ms: config.start_delay_ms,
+    };
+    (to_initiator, to_target)
+}
+
+/// Encode a probe datagram.
+pub fn probe_packet(nonce: u64) -> [u8; PROBE_LEN] {
+    let mut packet = [0u8; PROBE_LEN];
+    packet[..8].copy_from_slice(PROBE_MAGIC);
+    packet[8..].copy_from_slice(&nonce.to_be_bytes());
+    packet
+}
+
+/// Decode a probe datagram, returning its nonce.
+pub fn parse_probe(packet: &[u8]) -> Option<u64> {
+    if packet.len() != PROBE_LEN || &packet[..8] != PROBE_MAGIC {
+        return None;
+    }
+    let mut nonce = [0u8; 8];
+    nonce.copy_from_slice(&packet[8..]);
+    Some(u64::from_be_bytes(nonce))
+}
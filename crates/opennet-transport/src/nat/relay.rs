@@ -0,0 +1,129 @@
+//! Relay fallback circuits kept by the rendezvous peer.
+//!
+//! A circuit is opened only for a punch the rendezvous itself coordinated:
+//! the RelayRequest must arrive on the initiator's authenticated session,
+//! name the same target and nonce as the PunchRequest, and the target must
+//! still be connected. Traffic is forwarded only between the two ends of
+//! an open circuit, with the circuit's nonce, up to a byte budget.
+
+use opennet_core::NodeId;
+use opennet_wire::messages::{PunchRequest, RelayData, RelayRequest};
+use std::collections::BTreeMap;
+use crate::error::{TransportError, Result};
+
+/// Default bytes forwarded per circuit before it is closed.
+pub const DEFAULT_CIRCUIT_BYTES: u64 = 64 * 1024 * 1024;
+
+struct Circuit {
+    nonce: u64,
+    forwarded: u64,
+}
+
+/// Relay circuits between pairs of connected peers.
+pub struct RelayTable {
+    /// (initiator, target) -> nonce of punches we coordinated.
+    pending: BTreeMap<(NodeId, NodeId), u64>,
+    /// (lower NodeId, higher NodeId) -> circuit.
+    circuits: BTreeMap<(NodeId, NodeId), Circuit>,
+    max_circuits: usize,
+    max_bytes: u64,
+}
+
+impl RelayTable {
+    /// Create table with a circuit limit.
+    pub fn new(max_circuits: usize) -> Self {
+        Self {
+            pending: BTreeMap::new(),
+            circuits: BTreeMap::new(),
+            max_circuits,
+            max_bytes: DEFAULT_CIRCUIT_BYTES,
+        }
+    }
+
+    /// Bytes forwarded per circuit before it is closed.
+    pub fn with_byte_limit(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Remember a punch we coordinated, so its initiator may ask for relay.
+    pub fn on_punch_request(&mut self, request: &PunchRequest) {
+        if self.pending.len() >= self.max_circuits && !self.pending.contains_key(&(request.initiator, request.target)) {
+            return;
+        }
+        self.pending.insert((request.initiator, request.target), request.nonce);
+    }
+
+    /// Open a circuit for a relay request received on `from`'s
+    /// authenticated session. `target_connected` tells whether we still
+    /// have a session with the target.
+    pub fn open(&mut self, from: &NodeId, request: &RelayRequest, target_connected: bool) -> Result<()> {
+        let coordinated = self.pending.get(&(request.initiator, request.target)) == Some(&request.nonce);
+        if from != &request.initiator || request.initiator == request.target || !coordinated || !target_connected {
+            return Err(TransportError::RelayUnavailable);
+        }
+        let key = pair(request.initiator, request.target);
+        if !self.circuits.contains_key(&key) && self.circuits.len() >= self.max_circuits {
+            return Err(TransportError::RelayUnavailable);
+        }
+        self.pending.remove(&(request.initiator, request.target));
+        self.circuits.insert(key, Circuit { nonce: request.nonce, forwarded: 0 });
+        Ok(())
+    }
+
+    /// Forward `data` received on `from`'s authenticated session.
+    ///
+    /// Returns the destination and the message to send it, naming `from`
+    /// as the source. The circuit is closed once its budget is spent.
+    pub fn forward(&mut self, from: &NodeId, data: &RelayData) -> Result<(NodeId, RelayData)> {
+        let key = pair(*from, data.peer);
+        let circuit = self
+            .circuits
+            .get_mut(&key)
+            .filter(|c| c.nonce == data.nonce)
+            .ok_or(TransportError::RelayUnavailable)?;
+        let forwarded = circuit.forwarded.saturating_add(data.payload.len() as u64);
+        if forwarded > self.max_bytes {
+            self.circuits.remove(&key);
+            return Err(TransportError::RelayUnavailable);
+        }
+        circuit.forwarded = forwarded;
+        Ok((data.peer, RelayData { peer: *from, nonce: data.nonce, payload: data.payload.clone() }))
+    }
+
+    /// Whether traffic from `from` to `to` may be forwarded.
+    pub fn can_forward(&self, from: &NodeId, to: &NodeId) -> bool {
+        self.circuits.contains_key(&pair(*from, *to))
+    }
+
+    /// Close a circuit.
+    pub fn close(&mut self, a: &NodeId, b: &NodeId) {
+        self.circuits.remove(&pair(*a, *b));
+    }
+
+    /// Drop all circuits and pending punches involving a peer (e.g. on disconnect).
+    pub fn remove_peer(&mut self, node_id: &NodeId) {
+        self.circuits.retain(|(a, b), _| a != node_id && b != node_id);
+        self.pending.retain(|(a, b), _| a != node_id && b != node_id);
+    }
+
+    /// Number of open circuits.
+    pub fn len(&self) -> usize {
+        self.circuits.len()
+    }
+
+    /// Check if empty.
+    pub fn is_empty(&self) -> bool {
+        self.circuits.is_empty()
+    }
+}
+
+impl Default for RelayTable {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+fn pair(a: NodeId, b: NodeId) -> (NodeId, NodeId) {
+    if a <= b { (a, b) } else { (b, a) }
+}
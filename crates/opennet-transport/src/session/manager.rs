@@ -12,6 +12,42 @@ impl SessionManager {
     }
     pub fn remove(&mut self, binding: &SessionBinding) { self.sessions.remove(binding); }
     pub fn get(&self, binding: &SessionBinding) -> Option<u64> { self.sessions.get(binding).copied() }
+
+    /// Whether any session is open with `node_id`.
+    pub fn has_peer(&self, node_id: &opennet_core::NodeId) -> bool {
+        self.sessions.keys().any(|b| &b.node_id == node_id)
+    }
+
+    /// Remove sessions whose binding matches `f`. Returns how many.
+    pub fn remove_where(&mut self, mut f: impl FnMut(&SessionBinding) -> bool) -> usize {
+        let before = self.sessions.len();
+        self.sessions.retain(|b, _| !f(b));
+        before - self.sessions.len()
+    }
+
+    /// Move a peer's session to its new epoch after a verified rotation.
+    ///
+    /// A session already open at `to_epoch` is kept; otherwise the one
+    /// from the newest older epoch moves there. Any other sessions from
+    /// older epochs are closed. Returns how many sessions were rebound.
+    pub fn rebind(&mut self, node_id: &opennet_core::NodeId, to_epoch: u64) -> usize {
+        let stale: Vec<SessionBinding> = self.sessions.keys()
+            .filter(|b| &b.node_id == node_id && b.epoch_id < to_epoch)
+            .cloned()
+            .collect();
+        let newest = stale.iter().max_by_key(|b| b.epoch_id).and_then(|b| self.sessions.get(b).copied());
+        for binding in &stale {
+            self.sessions.remove(binding);
+        }
+        let target = SessionBinding::new(*node_id, to_epoch);
+        match newest {
+            Some(id) if !self.sessions.contains_key(&target) => {
+                self.sessions.insert(target, id);
+                1
+            }
+            _ => 0,
+        }
+    }
 }
 
 impl Default for SessionManager {
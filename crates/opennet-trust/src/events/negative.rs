@@ -16,6 +16,7 @@ pub enum PenaltyReason {
     InvalidSignature,
     TimeoutExceeded,
     MalformedMessage,
+    RateLimitExceeded,
 }
 
 /// Default penalty factor.
@@ -42,6 +42,20 @@ impl TrustGraph {
         self.edges.get(source)
     }
 
+    /// The node's trust neighborhood: the source of its strongest
+    /// incoming edge, or the node itself if nobody vouches for it.
+    ///
+    /// Identities minted and vouched for by one party share a
+    /// neighborhood however many NodeIds they use.
+    pub fn neighborhood(&self, node_id: &NodeId) -> NodeId {
+        self.edges
+            .iter()
+            .filter_map(|(source, targets)| targets.get(node_id).map(|edge| (edge.weight, *source)))
+            .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+            .map(|(_, source)| source)
+            .unwrap_or(*node_id)
+    }
+
     /// Get all nodes.
     pub fn nodes(&self) -> impl Iterator<Item = &NodeId> {
         self.weights.keys()
@@ -1,5 +1,6 @@
 //! CBOR decoder with canonical validation.
 
+use opennet_core::types::Timestamp;
 use crate::error::{WireError, Result};
 
 /// CBOR decoder with canonical validation.
@@ -33,6 +34,11 @@ impl<'a> CborDecoder<'a> {
         Ok(value)
     }
 
+    /// Decode timestamp as milliseconds since epoch.
+    pub fn decode_timestamp(&mut self) -> Result<Timestamp> {
+        self.decode_uint().map(Timestamp::from_millis)
+    }
+
     /// Decode signed integer.
     pub fn decode_int(&mut self) -> Result<i64> {
         let (major, value) = self.decode_type_and_value()?;
@@ -50,7 +56,7 @@ impl<'a> CborDecoder<'a> {
             return Err(WireError::InvalidCbor("expected bytes".into()));
         }
         let len = len as usize;
-        if self.pos + len > self.data.len() {
+        if len > self.data.len() - self.pos {
             return Err(WireError::InvalidCbor("unexpected end".into()));
         }
         let bytes = self.data[self.pos..self.pos + len].to_vec();
@@ -65,7 +71,7 @@ impl<'a> CborDecoder<'a> {
             return Err(WireError::InvalidCbor("expected text".into()));
         }
         let len = len as usize;
-        if self.pos + len > self.data.len() {
+        if len > self.data.len() - self.pos {
             return Err(WireError::InvalidCbor("unexpected end".into()));
         }
         let text = std::str::from_utf8(&self.data[self.pos..self.pos + len])
@@ -1,5 +1,6 @@
 //! CBOR encoder with canonical output.
 
+use opennet_core::types::Timestamp;
 use std::collections::BTreeMap;
 use crate::error::Result;
 
@@ -25,6 +26,11 @@ impl CborEncoder {
         self
     }
 
+    /// Encode timestamp as milliseconds since epoch.
+    pub fn encode_timestamp(&mut self, timestamp: Timestamp) -> &mut Self {
+        self.encode_uint(timestamp.as_millis())
+    }
+
     /// Encode signed integer.
     pub fn encode_int(&mut self, value: i64) -> &mut Self {
         if value >= 0 {
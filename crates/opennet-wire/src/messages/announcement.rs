@@ -0,0 +1,13 @@
+//! Identity announcement message, gossiped periodically and after rotation.
+
+use opennet_core::NodeId;
+use serde::{Deserialize, Serialize};
+
+/// Carries a signed identity announcement.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnouncementMessage {
+    /// Announcing node.
+    pub node_id: NodeId,
+    /// Canonical CBOR announcement, including its signature.
+    pub announcement: Vec<u8>,
+}
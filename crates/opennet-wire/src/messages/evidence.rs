@@ -0,0 +1,16 @@
+//! Compromise evidence message, gossiped so every peer can check it.
+
+use opennet_core::NodeId;
+use serde::{Deserialize, Serialize};
+
+/// Carries a self-contained compromise evidence bundle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvidenceMessage {
+    /// Node the evidence is about.
+    pub node_id: NodeId,
+    /// Evidence kind (1 = equivocation, 2 = impossible timing,
+    /// 3 = conflicting epoch).
+    pub kind: u8,
+    /// Canonical CBOR evidence bundle.
+    pub bundle: Vec<u8>,
+}
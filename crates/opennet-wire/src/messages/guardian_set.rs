@@ -0,0 +1,13 @@
+//! Guardian set message, gossiped when a node registers its guardians.
+
+use opennet_core::NodeId;
+use serde::{Deserialize, Serialize};
+
+/// Carries a signed guardian set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuardianSetMessage {
+    /// Node the guardians vouch for.
+    pub node_id: NodeId,
+    /// Canonical CBOR guardian set, including the owner's signature.
+    pub set: Vec<u8>,
+}
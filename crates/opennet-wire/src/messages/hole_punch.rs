@@ -0,0 +1,67 @@
+//! Hole punching messages - NAT traversal via a rendezvous peer.
+//!
+//! ```text
+//! A --PunchRequest--> R
+//! R --PunchConnect--> A    R --PunchConnect--> B
+//! A <==== UDP probes ====> B   (both start at the same moment)
+//! A --RelayRequest--> R    (if probing fails)
+//! A --RelayData--> R --RelayData--> B
+//! ```
+
+use opennet_core::NodeId;
+use opennet_core::types::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// Ask a mutually connected peer to coordinate a hole punch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PunchRequest {
+    /// Node that wants to connect.
+    pub initiator: NodeId,
+    /// Node to connect to.
+    pub target: NodeId,
+    /// Initiator's candidate addresses (observed and local).
+    pub initiator_addrs: Vec<SocketAddr>,
+    /// Attempt identifier, echoed in probes.
+    pub nonce: u64,
+    /// Request timestamp.
+    pub timestamp: Timestamp,
+}
+
+/// Sent by the rendezvous to both sides to start probing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PunchConnect {
+    /// The other side of the punch.
+    pub peer: NodeId,
+    /// The other side's candidate addresses.
+    pub peer_addrs: Vec<SocketAddr>,
+    /// Attempt identifier.
+    pub nonce: u64,
+    /// Delay before probing starts, so both sides fire together.
+    pub start_delay_ms: u64,
+}
+
+/// Ask the rendezvous to relay traffic after a failed punch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayRequest {
+    /// Node that wants to connect.
+    pub initiator: NodeId,
+    /// Node to connect to.
+    pub target: NodeId,
+    /// Attempt identifier.
+    pub nonce: u64,
+}
+
+/// Traffic carried over a relay circuit.
+///
+/// Sent to the relay, `peer` is the destination; forwarded by the relay,
+/// it is the source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayData {
+    /// Other end of the circuit.
+    pub peer: NodeId,
+    /// Circuit nonce from the RelayRequest.
+    pub nonce: u64,
+    /// Opaque payload.
+    pub payload: Vec<u8>,
+}
@@ -8,6 +8,12 @@ pub mod stream_open;
 pub mod stream_data;
 pub mod stream_close;
 pub mod revocation;
+pub mod rotation;
+pub mod evidence;
+pub mod announcement;
+pub mod guardian_set;
+pub mod recovery;
+pub mod hole_punch;
 
 pub use node_hello::NodeHello;
 pub use node_welcome::NodeWelcome;
@@ -17,6 +23,12 @@ pub use stream_open::StreamOpen;
 pub use stream_data::StreamData;
 pub use stream_close::StreamClose;
 pub use revocation::RevocationMessage;
+pub use rotation::RotationMessage;
+pub use evidence::EvidenceMessage;
+pub use announcement::AnnouncementMessage;
+pub use guardian_set::GuardianSetMessage;
+pub use recovery::RecoveryMessage;
+pub use hole_punch::{PunchRequest, PunchConnect, RelayRequest, RelayData};
 
 /// Message type identifiers.
 #[repr(u16)]
@@ -30,4 +42,13 @@ pub enum MessageType {
     StreamData = 0x0021,
     StreamClose = 0x0022,
     Revocation = 0x0030,
+    Rotation = 0x0031,
+    Evidence = 0x0032,
+    Announcement = 0x0033,
+    GuardianSet = 0x0034,
+    Recovery = 0x0035,
+    PunchRequest = 0x0040,
+    PunchConnect = 0x0041,
+    RelayRequest = 0x0042,
+    RelayData = 0x0043,
 }
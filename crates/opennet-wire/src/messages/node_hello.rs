@@ -1,7 +1,7 @@
 //! NodeHello message - initial handshake.
 
 use opennet_core::{NodeId, Epoch};
-use opennet_core::types::{PublicKey, Timestamp};
+use opennet_core::types::{AnyPublicKey, Timestamp};
 use serde::{Deserialize, Serialize};
 
 /// NodeHello message sent to initiate connection.
@@ -11,8 +11,8 @@ pub struct NodeHello {
     pub node_id: NodeId,
     /// Current epoch.
     pub epoch: Epoch,
-    /// Current public key.
-    pub public_key: PublicKey,
+    /// Current public key, with its algorithm.
+    pub public_key: AnyPublicKey,
     /// Current timestamp.
     pub timestamp: Timestamp,
     /// Protocol version.
@@ -26,7 +26,7 @@ impl NodeHello {
     pub fn new(
         node_id: NodeId,
         epoch: Epoch,
-        public_key: PublicKey,
+        public_key: AnyPublicKey,
         timestamp: Timestamp,
     ) -> Self {
         Self {
@@ -38,4 +38,15 @@ impl NodeHello {
             features: 0,
         }
     }
+
+    /// Advertise feature bits.
+    pub fn with_features(mut self, features: u64) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Check the sender speaks a protocol version we accept.
+    pub fn is_supported_version(&self) -> bool {
+        self.version >= opennet_core::MIN_PROTOCOL_VERSION
+    }
 }
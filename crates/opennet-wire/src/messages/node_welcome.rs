@@ -1,8 +1,9 @@
 //! NodeWelcome message - handshake response.
 
 use opennet_core::{NodeId, Epoch};
-use opennet_core::types::{PublicKey, Timestamp};
+use opennet_core::types::{AnyPublicKey, AnySignature, Timestamp};
 use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
 
 /// NodeWelcome message sent in response to NodeHello.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,10 +12,15 @@ pub struct NodeWelcome {
     pub node_id: NodeId,
     /// Current epoch.
     pub epoch: Epoch,
-    /// Current public key.
-    pub public_key: PublicKey,
+    /// Current public key, with its algorithm.
+    pub public_key: AnyPublicKey,
     /// Current timestamp.
     pub timestamp: Timestamp,
     /// Accepted features (intersection).
     pub features: u64,
+    /// Source address the responder saw the initiator connect from.
+    pub observed_addr: Option<SocketAddr>,
+    /// Responder's signature over the handshake transcript with its full
+    /// key, as far as the accepted features allow.
+    pub signature: Option<AnySignature>,
 }
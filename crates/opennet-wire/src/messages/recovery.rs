@@ -0,0 +1,13 @@
+//! Recovery message, gossiped once guardians have vouched for a new key.
+
+use opennet_core::NodeId;
+use serde::{Deserialize, Serialize};
+
+/// Carries a recovery certificate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryMessage {
+    /// Recovered node.
+    pub node_id: NodeId,
+    /// Canonical CBOR recovery certificate with its attestations.
+    pub certificate: Vec<u8>,
+}
@@ -0,0 +1,24 @@
+//! Key rotation message, gossiped so every peer learns a node's new key.
+
+use opennet_core::NodeId;
+use opennet_core::types::{PublicKey, Signature, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// Announces that a node moved from `current_epoch` to `new_epoch`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RotationMessage {
+    /// Rotating node.
+    pub node_id: NodeId,
+    /// Epoch being left.
+    pub current_epoch: u64,
+    /// Epoch being entered (`current_epoch + 1`).
+    pub new_epoch: u64,
+    /// Public key for `new_epoch`.
+    pub new_public_key: PublicKey,
+    /// Rotation timestamp.
+    pub timestamp: Timestamp,
+    /// Signature by the `current_epoch` key.
+    pub old_key_signature: Signature,
+    /// Signature by the `new_epoch` key.
+    pub new_key_signature: Signature,
+}
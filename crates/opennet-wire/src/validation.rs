@@ -28,7 +28,7 @@ pub fn validate_frame(data: &[u8]) -> Result<()> {
 /// Validate message type is known.
 pub fn validate_message_type(msg_type: u16) -> Result<()> {
     match msg_type {
-        0x0001..=0x0002 | 0x0010..=0x0011 | 0x0020..=0x0022 | 0x0030 => Ok(()),
+        0x0001..=0x0002 | 0x0010..=0x0011 | 0x0020..=0x0022 | 0x0030..=0x0035 | 0x0040..=0x0043 => Ok(()),
         _ => Err(WireError::UnknownMessageType(msg_type)),
     }
 }